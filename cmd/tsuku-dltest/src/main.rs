@@ -1,74 +1,227 @@
-use libc::{dlclose, dlerror, dlopen, RTLD_LOCAL, RTLD_NOW};
+use libc::{c_int, dlclose, dlerror, dlopen, dlsym, RTLD_GLOBAL, RTLD_LAZY, RTLD_LOCAL, RTLD_NOW};
 use serde::Serialize;
 use std::env;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::io::{self, Write};
+use std::num::NonZeroUsize;
 use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const USAGE: &str =
+    "usage: tsuku-dltest [--lazy] [--global] [--symbol <name>] [--jobs <n>] <path>...";
+
 #[derive(Serialize)]
 struct Output {
     path: String,
     ok: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    /// The dlopen flags used, e.g. "RTLD_NOW|RTLD_LOCAL"
+    flags: String,
+    /// Whether the `--symbol` resolved; absent without `--symbol` or when
+    /// the library didn't load
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol_found: Option<bool>,
 }
 
-fn main() -> ExitCode {
-    let args: Vec<String> = env::args().skip(1).collect();
+/// How libraries are opened. Defaults to RTLD_NOW | RTLD_LOCAL, which
+/// surfaces unresolved symbols at load time without leaking them into the
+/// global namespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct LoadFlags {
+    /// Resolve symbols on first use (RTLD_LAZY) instead of at load time
+    lazy: bool,
+    /// Make the library's symbols available to later loads (RTLD_GLOBAL)
+    global: bool,
+}
+
+impl LoadFlags {
+    fn bits(self) -> c_int {
+        let binding = if self.lazy { RTLD_LAZY } else { RTLD_NOW };
+        let scope = if self.global { RTLD_GLOBAL } else { RTLD_LOCAL };
+        binding | scope
+    }
+}
+
+impl fmt::Display for LoadFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let binding = if self.lazy { "RTLD_LAZY" } else { "RTLD_NOW" };
+        let scope = if self.global {
+            "RTLD_GLOBAL"
+        } else {
+            "RTLD_LOCAL"
+        };
+        write!(f, "{}|{}", binding, scope)
+    }
+}
+
+/// The parsed command line.
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    flags: LoadFlags,
+    /// Symbol to look up in each library after it loads
+    symbol: Option<CString>,
+    /// Libraries loaded at once (default: the number of CPUs); ignored with
+    /// `--global`, which loads them in order
+    jobs: Option<NonZeroUsize>,
+    paths: Vec<String>,
+}
+
+/// Split the command line into options and library paths. Everything after
+/// `--` is a path.
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--lazy" => options.flags.lazy = true,
+            "--global" => options.flags.global = true,
+            "--symbol" => {
+                let name = args.next().ok_or("--symbol needs a name")?;
+                options.symbol = Some(symbol_name(name)?);
+            }
+            "--jobs" => {
+                let n = args.next().ok_or("--jobs needs a number")?;
+                options.jobs = Some(job_count(n)?);
+            }
+            "--" => options.paths.extend(args.by_ref().cloned()),
+            option if option.starts_with("--symbol=") => {
+                options.symbol = Some(symbol_name(&option["--symbol=".len()..])?);
+            }
+            option if option.starts_with("--jobs=") => {
+                options.jobs = Some(job_count(&option["--jobs=".len()..])?);
+            }
+            option if option.starts_with("--") => {
+                return Err(format!("unknown option: {}", option));
+            }
+            _ => options.paths.push(arg.clone()),
+        }
+    }
+    Ok(options)
+}
 
-    if args.is_empty() {
-        eprintln!("usage: tsuku-dltest <path>...");
-        return ExitCode::from(2);
+fn symbol_name(name: &str) -> Result<CString, String> {
+    if name.is_empty() {
+        return Err("--symbol needs a name".to_string());
     }
+    CString::new(name).map_err(|_| "symbol name contains null byte".to_string())
+}
+
+fn job_count(n: &str) -> Result<NonZeroUsize, String> {
+    n.parse()
+        .map_err(|_| format!("--jobs needs a positive number, got '{}'", n))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
 
     if args.len() == 1 && args[0] == "--version" {
         eprintln!("tsuku-dltest v{}", VERSION);
         return ExitCode::from(0);
     }
 
-    let mut all_ok = true;
-    let mut results = Vec::with_capacity(args.len());
-
-    for path in &args {
-        let result = match try_load(path) {
-            Ok(()) => Output {
-                path: path.clone(),
-                ok: true,
-                error: None,
-            },
-            Err(e) => {
-                all_ok = false;
-                Output {
-                    path: path.clone(),
-                    ok: false,
-                    error: Some(e),
-                }
-            }
-        };
-        results.push(result);
-    }
+    let options = match parse_args(&args) {
+        Ok(options) if options.paths.is_empty() => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("tsuku-dltest: {}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let jobs = options
+        .jobs
+        .or_else(|| thread::available_parallelism().ok())
+        .map_or(1, NonZeroUsize::get);
+    let results = load_all(&options, jobs);
 
     serde_json::to_writer(io::stdout(), &results).unwrap();
     io::stdout().flush().unwrap();
 
-    if all_ok {
+    if results.iter().all(|result| result.ok) {
         ExitCode::from(0)
     } else {
         ExitCode::from(1)
     }
 }
 
+/// How many libraries `load_all` loads at once for up to `jobs`. With
+/// `--global` each library may resolve symbols against the ones loaded
+/// before it, so they're loaded one at a time, in order.
+fn worker_count(options: &Options, jobs: usize) -> usize {
+    if options.flags.global {
+        return 1;
+    }
+    jobs.clamp(1, options.paths.len().max(1))
+}
+
+/// Load every path, up to `jobs` at a time, returning results in the order
+/// of the paths.
+fn load_all(options: &Options, jobs: usize) -> Vec<Output> {
+    let paths = &options.paths;
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Output>>> = Mutex::new(paths.iter().map(|_| None).collect());
+
+    // dlopen blocks, so each worker takes the next unclaimed path until none are left
+    thread::scope(|scope| {
+        for _ in 0..worker_count(options, jobs) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else {
+                    break;
+                };
+                let output = load_one(path, options);
+                results.lock().unwrap()[index] = Some(output);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|output| output.expect("every path is loaded"))
+        .collect()
+}
+
+fn load_one(path: &str, options: &Options) -> Output {
+    match try_load(path, options.flags, options.symbol.as_deref()) {
+        Ok(symbol_found) => Output {
+            path: path.to_string(),
+            ok: true,
+            error: None,
+            flags: options.flags.to_string(),
+            symbol_found,
+        },
+        Err(e) => Output {
+            path: path.to_string(),
+            ok: false,
+            error: Some(e),
+            flags: options.flags.to_string(),
+            symbol_found: None,
+        },
+    }
+}
+
 /// Attempt to load a library with dlopen, then immediately unload it.
-fn try_load(path: &str) -> Result<(), String> {
+///
+/// With `symbol`, also reports whether it resolves in the loaded library.
+fn try_load(path: &str, flags: LoadFlags, symbol: Option<&CStr>) -> Result<Option<bool>, String> {
     let c_path = CString::new(path).map_err(|_| "path contains null byte".to_string())?;
 
     unsafe {
         // Clear any previous error (critical for correct error reporting)
         dlerror();
 
-        let handle = dlopen(c_path.as_ptr(), RTLD_NOW | RTLD_LOCAL);
+        let handle = dlopen(c_path.as_ptr(), flags.bits());
 
         if handle.is_null() {
             let err = dlerror();
@@ -78,6 +231,14 @@ fn try_load(path: &str) -> Result<(), String> {
             return Err(CStr::from_ptr(err).to_string_lossy().into_owned());
         }
 
+        // A null address is a valid symbol value, so only dlerror() tells
+        // a missing symbol apart
+        let symbol_found = symbol.map(|symbol| {
+            dlerror();
+            let address = dlsym(handle, symbol.as_ptr());
+            !address.is_null() || dlerror().is_null()
+        });
+
         // Check dlclose return value - failure is worth reporting
         if dlclose(handle) != 0 {
             let err = dlerror();
@@ -88,7 +249,126 @@ fn try_load(path: &str) -> Result<(), String> {
                 ));
             }
         }
+
+        Ok(symbol_found)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    const LIBC: &str = "libc.so.6";
+    #[cfg(target_os = "macos")]
+    const LIBC: &str = "/usr/lib/libSystem.B.dylib";
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_load_succeeds_under_each_flag_combination() {
+        for lazy in [false, true] {
+            for global in [false, true] {
+                let flags = LoadFlags { lazy, global };
+                assert_eq!(try_load(LIBC, flags, None), Ok(None), "{}", flags);
+            }
+        }
+        assert!(try_load("/nonexistent/libnothing.so", LoadFlags::default(), None).is_err());
+    }
+
+    #[test]
+    fn test_symbol_lookup() {
+        let flags = LoadFlags::default();
+        assert_eq!(try_load(LIBC, flags, Some(c"malloc")), Ok(Some(true)));
+        assert_eq!(
+            try_load(LIBC, flags, Some(c"tsuku_dltest_no_such_symbol")),
+            Ok(Some(false))
+        );
+    }
+
+    #[test]
+    fn test_parse_args_flags() {
+        let options = parse_args(&args(&["/a.so", "/b.so"])).unwrap();
+        assert_eq!(options.flags, LoadFlags::default());
+        assert_eq!(options.flags.to_string(), "RTLD_NOW|RTLD_LOCAL");
+        assert_eq!(options.paths, args(&["/a.so", "/b.so"]));
+
+        let options = parse_args(&args(&["--lazy", "/a.so", "--global"])).unwrap();
+        assert_eq!(options.flags.to_string(), "RTLD_LAZY|RTLD_GLOBAL");
+        assert_eq!(options.flags.bits(), RTLD_LAZY | RTLD_GLOBAL);
+        assert_eq!(options.paths, args(&["/a.so"]));
+
+        let options = parse_args(&args(&["--", "--lazy"])).unwrap();
+        assert_eq!(options.paths, args(&["--lazy"]));
 
-    Ok(())
+        assert!(parse_args(&args(&["--bogus", "/a.so"])).is_err());
+    }
+
+    #[test]
+    fn test_load_all_keeps_input_order() {
+        let paths: Vec<String> = (0..24)
+            .map(|i| {
+                if i % 3 == 0 {
+                    format!("/nonexistent/lib{}.so", i)
+                } else {
+                    LIBC.to_string()
+                }
+            })
+            .collect();
+        let options = Options {
+            paths: paths.clone(),
+            ..Options::default()
+        };
+
+        for jobs in [1, 4, 64] {
+            let results = load_all(&options, jobs);
+            let returned: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+            assert_eq!(returned, paths, "jobs={}", jobs);
+            for (i, result) in results.iter().enumerate() {
+                assert_eq!(result.ok, i % 3 != 0, "{}", result.path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_global_loads_one_at_a_time() {
+        let mut options = Options {
+            paths: args(&["/a.so", "/b.so", "/c.so"]),
+            ..Options::default()
+        };
+        assert_eq!(worker_count(&options, 8), 3);
+        assert_eq!(worker_count(&options, 2), 2);
+
+        options.flags.global = true;
+        assert_eq!(worker_count(&options, 8), 1);
+    }
+
+    #[test]
+    fn test_parse_args_jobs() {
+        let options = parse_args(&args(&["--jobs", "3", "/a.so"])).unwrap();
+        assert_eq!(options.jobs, NonZeroUsize::new(3));
+        assert_eq!(options.paths, args(&["/a.so"]));
+
+        let options = parse_args(&args(&["--jobs=8", "/a.so"])).unwrap();
+        assert_eq!(options.jobs, NonZeroUsize::new(8));
+
+        assert!(parse_args(&args(&["--jobs", "0", "/a.so"])).is_err());
+        assert!(parse_args(&args(&["--jobs", "many", "/a.so"])).is_err());
+        assert!(parse_args(&args(&["/a.so", "--jobs"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_args_symbol() {
+        let options = parse_args(&args(&["--symbol", "foo", "/a.so"])).unwrap();
+        assert_eq!(options.symbol.as_deref(), Some(c"foo"));
+        assert_eq!(options.paths, args(&["/a.so"]));
+
+        let options = parse_args(&args(&["--symbol=bar", "/a.so"])).unwrap();
+        assert_eq!(options.symbol.as_deref(), Some(c"bar"));
+
+        assert!(parse_args(&args(&["/a.so", "--symbol"])).is_err());
+        assert!(parse_args(&args(&["--symbol=", "/a.so"])).is_err());
+    }
 }
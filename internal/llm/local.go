@@ -422,6 +422,12 @@ func fromProtoResponse(resp *pb.CompletionResponse) *CompletionResponse {
 		StopReason: resp.StopReason,
 	}
 
+	// The daemon reports "eos" when the model finishes without calling a
+	// tool, which callers know as the end of the turn.
+	if result.StopReason == "eos" {
+		result.StopReason = "end_turn"
+	}
+
 	// Convert usage
 	if resp.Usage != nil {
 		result.Usage = Usage{
@@ -568,6 +568,16 @@ func TestFromProtoResponse(t *testing.T) {
 	require.Equal(t, "value1", resp.ToolCalls[0].Arguments["arg1"])
 }
 
+// TestFromProtoResponseEOS verifies that the daemon's "eos" ends the turn.
+func TestFromProtoResponseEOS(t *testing.T) {
+	resp := fromProtoResponse(&pb.CompletionResponse{
+		Content:    "No tool needed",
+		StopReason: "eos",
+	})
+
+	require.Equal(t, "end_turn", resp.StopReason)
+}
+
 // TestEnsureVersionCompatible tests the version handshake logic.
 func TestEnsureVersionCompatible(t *testing.T) {
 	setup := func(t *testing.T, statusResp *pb.StatusResponse, statusErr error) *LocalProvider {
@@ -0,0 +1,9 @@
+// Package proto holds the gRPC client stubs for the tsuku-llm daemon,
+// generated from proto/llm.proto at the repo root.
+//
+// The stubs must be regenerated in the same change as any edit to llm.proto,
+// or the Go client falls behind the daemon. Run `go generate ./internal/llm/proto/...`
+// with protoc, protoc-gen-go and protoc-gen-go-grpc on PATH.
+package proto
+
+//go:generate protoc --proto_path=../../../proto --go_out=. --go_opt=paths=source_relative --go-grpc_out=. --go-grpc_opt=paths=source_relative llm.proto
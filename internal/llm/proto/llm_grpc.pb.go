@@ -1,7 +1,7 @@
 // Code generated by protoc-gen-go-grpc. DO NOT EDIT.
 // versions:
 // - protoc-gen-go-grpc v1.2.0
-// - protoc             v5.28.3
+// - protoc             v7.36.2
 // source: llm.proto
 
 package proto
@@ -25,11 +25,47 @@ type InferenceServiceClient interface {
 	// Complete generates a completion for the given request.
 	// Supports tool calling for structured extraction tasks.
 	Complete(ctx context.Context, in *CompletionRequest, opts ...grpc.CallOption) (*CompletionResponse, error)
+	// CompleteStream generates a completion and streams it back as it is
+	// produced. Each chunk carries a content delta; the final chunk carries
+	// the stop reason, tool calls, and usage.
+	CompleteStream(ctx context.Context, in *CompletionRequest, opts ...grpc.CallOption) (InferenceService_CompleteStreamClient, error)
+	// Embed returns a pooled, L2-normalized embedding of the input text.
+	Embed(ctx context.Context, in *EmbedRequest, opts ...grpc.CallOption) (*EmbedResponse, error)
+	// CountTokens reports how many tokens the prompt for a completion would
+	// take, so clients can fit requests in the context window without running
+	// one. It needs no inference context, so it answers at once even while
+	// long completions run. Requests over the server's message or tool limits
+	// fail with INVALID_ARGUMENT, as Complete does.
+	CountTokens(ctx context.Context, in *CountTokensRequest, opts ...grpc.CallOption) (*CountTokensResponse, error)
+	// Tokenize returns the model's token ids for a string, for checking the
+	// tokenizer without running a completion. Like CountTokens, it answers
+	// at once even while every context is busy.
+	Tokenize(ctx context.Context, in *TokenizeRequest, opts ...grpc.CallOption) (*TokenizeResponse, error)
+	// Detokenize turns token ids back into text; the inverse of Tokenize.
+	// Fails with INVALID_ARGUMENT for ids outside the model's vocabulary.
+	Detokenize(ctx context.Context, in *DetokenizeRequest, opts ...grpc.CallOption) (*DetokenizeResponse, error)
 	// Shutdown gracefully terminates the inference server.
 	// Called when tsuku exits or when idle timeout is reached.
 	Shutdown(ctx context.Context, in *ShutdownRequest, opts ...grpc.CallOption) (*ShutdownResponse, error)
 	// GetStatus returns the current server status including loaded model info.
+	// By default it also resets the idle timeout (see --status-resets-idle).
 	GetStatus(ctx context.Context, in *StatusRequest, opts ...grpc.CallOption) (*StatusResponse, error)
+	// Ping reports whether the server is ready without resetting the idle
+	// timeout, for health checks and monitors.
+	Ping(ctx context.Context, in *PingRequest, opts ...grpc.CallOption) (*PingResponse, error)
+	// GetMetrics returns inference metrics in the Prometheus text format.
+	GetMetrics(ctx context.Context, in *MetricsRequest, opts ...grpc.CallOption) (*MetricsResponse, error)
+	// CancelStartup aborts the model download the server is starting with.
+	// The partial download is removed and the server exits. Only available
+	// while the model is downloading; fails with FAILED_PRECONDITION after.
+	CancelStartup(ctx context.Context, in *CancelStartupRequest, opts ...grpc.CallOption) (*CancelStartupResponse, error)
+	// ReloadModel switches to another downloaded manifest model without
+	// restarting the server. New inference requests are rejected as
+	// unavailable while it runs. Requests already running finish on the old
+	// model, which is then unloaded before the new one loads, so the two are
+	// never in memory together. If the new model fails to load, no model is
+	// served until a later ReloadModel succeeds.
+	ReloadModel(ctx context.Context, in *ReloadModelRequest, opts ...grpc.CallOption) (*ReloadModelResponse, error)
 }
 
 type inferenceServiceClient struct {
@@ -49,6 +85,74 @@ func (c *inferenceServiceClient) Complete(ctx context.Context, in *CompletionReq
 	return out, nil
 }
 
+func (c *inferenceServiceClient) CompleteStream(ctx context.Context, in *CompletionRequest, opts ...grpc.CallOption) (InferenceService_CompleteStreamClient, error) {
+	stream, err := c.cc.NewStream(ctx, &InferenceService_ServiceDesc.Streams[0], "/tsuku.llm.v1.InferenceService/CompleteStream", opts...)
+	if err != nil {
+		return nil, err
+	}
+	x := &inferenceServiceCompleteStreamClient{stream}
+	if err := x.ClientStream.SendMsg(in); err != nil {
+		return nil, err
+	}
+	if err := x.ClientStream.CloseSend(); err != nil {
+		return nil, err
+	}
+	return x, nil
+}
+
+type InferenceService_CompleteStreamClient interface {
+	Recv() (*CompletionResponse, error)
+	grpc.ClientStream
+}
+
+type inferenceServiceCompleteStreamClient struct {
+	grpc.ClientStream
+}
+
+func (x *inferenceServiceCompleteStreamClient) Recv() (*CompletionResponse, error) {
+	m := new(CompletionResponse)
+	if err := x.ClientStream.RecvMsg(m); err != nil {
+		return nil, err
+	}
+	return m, nil
+}
+
+func (c *inferenceServiceClient) Embed(ctx context.Context, in *EmbedRequest, opts ...grpc.CallOption) (*EmbedResponse, error) {
+	out := new(EmbedResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/Embed", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) CountTokens(ctx context.Context, in *CountTokensRequest, opts ...grpc.CallOption) (*CountTokensResponse, error) {
+	out := new(CountTokensResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/CountTokens", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) Tokenize(ctx context.Context, in *TokenizeRequest, opts ...grpc.CallOption) (*TokenizeResponse, error) {
+	out := new(TokenizeResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/Tokenize", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) Detokenize(ctx context.Context, in *DetokenizeRequest, opts ...grpc.CallOption) (*DetokenizeResponse, error) {
+	out := new(DetokenizeResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/Detokenize", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
 func (c *inferenceServiceClient) Shutdown(ctx context.Context, in *ShutdownRequest, opts ...grpc.CallOption) (*ShutdownResponse, error) {
 	out := new(ShutdownResponse)
 	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/Shutdown", in, out, opts...)
@@ -67,6 +171,42 @@ func (c *inferenceServiceClient) GetStatus(ctx context.Context, in *StatusReques
 	return out, nil
 }
 
+func (c *inferenceServiceClient) Ping(ctx context.Context, in *PingRequest, opts ...grpc.CallOption) (*PingResponse, error) {
+	out := new(PingResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/Ping", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) GetMetrics(ctx context.Context, in *MetricsRequest, opts ...grpc.CallOption) (*MetricsResponse, error) {
+	out := new(MetricsResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/GetMetrics", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) CancelStartup(ctx context.Context, in *CancelStartupRequest, opts ...grpc.CallOption) (*CancelStartupResponse, error) {
+	out := new(CancelStartupResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/CancelStartup", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *inferenceServiceClient) ReloadModel(ctx context.Context, in *ReloadModelRequest, opts ...grpc.CallOption) (*ReloadModelResponse, error) {
+	out := new(ReloadModelResponse)
+	err := c.cc.Invoke(ctx, "/tsuku.llm.v1.InferenceService/ReloadModel", in, out, opts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
 // InferenceServiceServer is the server API for InferenceService service.
 // All implementations must embed UnimplementedInferenceServiceServer
 // for forward compatibility
@@ -74,11 +214,47 @@ type InferenceServiceServer interface {
 	// Complete generates a completion for the given request.
 	// Supports tool calling for structured extraction tasks.
 	Complete(context.Context, *CompletionRequest) (*CompletionResponse, error)
+	// CompleteStream generates a completion and streams it back as it is
+	// produced. Each chunk carries a content delta; the final chunk carries
+	// the stop reason, tool calls, and usage.
+	CompleteStream(*CompletionRequest, InferenceService_CompleteStreamServer) error
+	// Embed returns a pooled, L2-normalized embedding of the input text.
+	Embed(context.Context, *EmbedRequest) (*EmbedResponse, error)
+	// CountTokens reports how many tokens the prompt for a completion would
+	// take, so clients can fit requests in the context window without running
+	// one. It needs no inference context, so it answers at once even while
+	// long completions run. Requests over the server's message or tool limits
+	// fail with INVALID_ARGUMENT, as Complete does.
+	CountTokens(context.Context, *CountTokensRequest) (*CountTokensResponse, error)
+	// Tokenize returns the model's token ids for a string, for checking the
+	// tokenizer without running a completion. Like CountTokens, it answers
+	// at once even while every context is busy.
+	Tokenize(context.Context, *TokenizeRequest) (*TokenizeResponse, error)
+	// Detokenize turns token ids back into text; the inverse of Tokenize.
+	// Fails with INVALID_ARGUMENT for ids outside the model's vocabulary.
+	Detokenize(context.Context, *DetokenizeRequest) (*DetokenizeResponse, error)
 	// Shutdown gracefully terminates the inference server.
 	// Called when tsuku exits or when idle timeout is reached.
 	Shutdown(context.Context, *ShutdownRequest) (*ShutdownResponse, error)
 	// GetStatus returns the current server status including loaded model info.
+	// By default it also resets the idle timeout (see --status-resets-idle).
 	GetStatus(context.Context, *StatusRequest) (*StatusResponse, error)
+	// Ping reports whether the server is ready without resetting the idle
+	// timeout, for health checks and monitors.
+	Ping(context.Context, *PingRequest) (*PingResponse, error)
+	// GetMetrics returns inference metrics in the Prometheus text format.
+	GetMetrics(context.Context, *MetricsRequest) (*MetricsResponse, error)
+	// CancelStartup aborts the model download the server is starting with.
+	// The partial download is removed and the server exits. Only available
+	// while the model is downloading; fails with FAILED_PRECONDITION after.
+	CancelStartup(context.Context, *CancelStartupRequest) (*CancelStartupResponse, error)
+	// ReloadModel switches to another downloaded manifest model without
+	// restarting the server. New inference requests are rejected as
+	// unavailable while it runs. Requests already running finish on the old
+	// model, which is then unloaded before the new one loads, so the two are
+	// never in memory together. If the new model fails to load, no model is
+	// served until a later ReloadModel succeeds.
+	ReloadModel(context.Context, *ReloadModelRequest) (*ReloadModelResponse, error)
 	mustEmbedUnimplementedInferenceServiceServer()
 }
 
@@ -89,12 +265,39 @@ type UnimplementedInferenceServiceServer struct {
 func (UnimplementedInferenceServiceServer) Complete(context.Context, *CompletionRequest) (*CompletionResponse, error) {
 	return nil, status.Errorf(codes.Unimplemented, "method Complete not implemented")
 }
+func (UnimplementedInferenceServiceServer) CompleteStream(*CompletionRequest, InferenceService_CompleteStreamServer) error {
+	return status.Errorf(codes.Unimplemented, "method CompleteStream not implemented")
+}
+func (UnimplementedInferenceServiceServer) Embed(context.Context, *EmbedRequest) (*EmbedResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Embed not implemented")
+}
+func (UnimplementedInferenceServiceServer) CountTokens(context.Context, *CountTokensRequest) (*CountTokensResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method CountTokens not implemented")
+}
+func (UnimplementedInferenceServiceServer) Tokenize(context.Context, *TokenizeRequest) (*TokenizeResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Tokenize not implemented")
+}
+func (UnimplementedInferenceServiceServer) Detokenize(context.Context, *DetokenizeRequest) (*DetokenizeResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Detokenize not implemented")
+}
 func (UnimplementedInferenceServiceServer) Shutdown(context.Context, *ShutdownRequest) (*ShutdownResponse, error) {
 	return nil, status.Errorf(codes.Unimplemented, "method Shutdown not implemented")
 }
 func (UnimplementedInferenceServiceServer) GetStatus(context.Context, *StatusRequest) (*StatusResponse, error) {
 	return nil, status.Errorf(codes.Unimplemented, "method GetStatus not implemented")
 }
+func (UnimplementedInferenceServiceServer) Ping(context.Context, *PingRequest) (*PingResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Ping not implemented")
+}
+func (UnimplementedInferenceServiceServer) GetMetrics(context.Context, *MetricsRequest) (*MetricsResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method GetMetrics not implemented")
+}
+func (UnimplementedInferenceServiceServer) CancelStartup(context.Context, *CancelStartupRequest) (*CancelStartupResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method CancelStartup not implemented")
+}
+func (UnimplementedInferenceServiceServer) ReloadModel(context.Context, *ReloadModelRequest) (*ReloadModelResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method ReloadModel not implemented")
+}
 func (UnimplementedInferenceServiceServer) mustEmbedUnimplementedInferenceServiceServer() {}
 
 // UnsafeInferenceServiceServer may be embedded to opt out of forward compatibility for this service.
@@ -126,6 +329,99 @@ func _InferenceService_Complete_Handler(srv interface{}, ctx context.Context, de
 	return interceptor(ctx, in, info, handler)
 }
 
+func _InferenceService_CompleteStream_Handler(srv interface{}, stream grpc.ServerStream) error {
+	m := new(CompletionRequest)
+	if err := stream.RecvMsg(m); err != nil {
+		return err
+	}
+	return srv.(InferenceServiceServer).CompleteStream(m, &inferenceServiceCompleteStreamServer{stream})
+}
+
+type InferenceService_CompleteStreamServer interface {
+	Send(*CompletionResponse) error
+	grpc.ServerStream
+}
+
+type inferenceServiceCompleteStreamServer struct {
+	grpc.ServerStream
+}
+
+func (x *inferenceServiceCompleteStreamServer) Send(m *CompletionResponse) error {
+	return x.ServerStream.SendMsg(m)
+}
+
+func _InferenceService_Embed_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(EmbedRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).Embed(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/Embed",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).Embed(ctx, req.(*EmbedRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_CountTokens_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(CountTokensRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).CountTokens(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/CountTokens",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).CountTokens(ctx, req.(*CountTokensRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_Tokenize_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(TokenizeRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).Tokenize(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/Tokenize",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).Tokenize(ctx, req.(*TokenizeRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_Detokenize_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(DetokenizeRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).Detokenize(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/Detokenize",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).Detokenize(ctx, req.(*DetokenizeRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
 func _InferenceService_Shutdown_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
 	in := new(ShutdownRequest)
 	if err := dec(in); err != nil {
@@ -162,6 +458,78 @@ func _InferenceService_GetStatus_Handler(srv interface{}, ctx context.Context, d
 	return interceptor(ctx, in, info, handler)
 }
 
+func _InferenceService_Ping_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(PingRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).Ping(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/Ping",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).Ping(ctx, req.(*PingRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_GetMetrics_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(MetricsRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).GetMetrics(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/GetMetrics",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).GetMetrics(ctx, req.(*MetricsRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_CancelStartup_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(CancelStartupRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).CancelStartup(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/CancelStartup",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).CancelStartup(ctx, req.(*CancelStartupRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _InferenceService_ReloadModel_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(ReloadModelRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(InferenceServiceServer).ReloadModel(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: "/tsuku.llm.v1.InferenceService/ReloadModel",
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(InferenceServiceServer).ReloadModel(ctx, req.(*ReloadModelRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
 // InferenceService_ServiceDesc is the grpc.ServiceDesc for InferenceService service.
 // It's only intended for direct use with grpc.RegisterService,
 // and not to be introspected or modified (even as a copy)
@@ -173,6 +541,22 @@ var InferenceService_ServiceDesc = grpc.ServiceDesc{
 			MethodName: "Complete",
 			Handler:    _InferenceService_Complete_Handler,
 		},
+		{
+			MethodName: "Embed",
+			Handler:    _InferenceService_Embed_Handler,
+		},
+		{
+			MethodName: "CountTokens",
+			Handler:    _InferenceService_CountTokens_Handler,
+		},
+		{
+			MethodName: "Tokenize",
+			Handler:    _InferenceService_Tokenize_Handler,
+		},
+		{
+			MethodName: "Detokenize",
+			Handler:    _InferenceService_Detokenize_Handler,
+		},
 		{
 			MethodName: "Shutdown",
 			Handler:    _InferenceService_Shutdown_Handler,
@@ -181,7 +565,29 @@ var InferenceService_ServiceDesc = grpc.ServiceDesc{
 			MethodName: "GetStatus",
 			Handler:    _InferenceService_GetStatus_Handler,
 		},
+		{
+			MethodName: "Ping",
+			Handler:    _InferenceService_Ping_Handler,
+		},
+		{
+			MethodName: "GetMetrics",
+			Handler:    _InferenceService_GetMetrics_Handler,
+		},
+		{
+			MethodName: "CancelStartup",
+			Handler:    _InferenceService_CancelStartup_Handler,
+		},
+		{
+			MethodName: "ReloadModel",
+			Handler:    _InferenceService_ReloadModel_Handler,
+		},
+	},
+	Streams: []grpc.StreamDesc{
+		{
+			StreamName:    "CompleteStream",
+			Handler:       _InferenceService_CompleteStream_Handler,
+			ServerStreams: true,
+		},
 	},
-	Streams:  []grpc.StreamDesc{},
 	Metadata: "llm.proto",
 }
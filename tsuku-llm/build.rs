@@ -4,9 +4,19 @@
 //! 1. Compiles the gRPC proto file
 //! 2. Compiles llama.cpp via cmake
 //! 3. Generates Rust bindings via bindgen
+//!
+//! Packagers can point the build away from the `llama.cpp` submodule:
+//!
+//! - `TSUKU_LLAMA_CPP_DIR`: llama.cpp source tree to build and take headers
+//!   from instead of the submodule.
+//! - `TSUKU_LLAMA_CPP_SYSTEM=1`: skip the cmake build and link an installed
+//!   `libllama` found via pkg-config. Bindgen still reads `include/llama.h`
+//!   and `ggml/include` from the source tree (the submodule or
+//!   `TSUKU_LLAMA_CPP_DIR`), so one must be present and match the installed
+//!   library's version.
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 1. Compile the proto file
@@ -15,18 +25,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_client(false) // Server only - Go has its own client
         .compile_protos(&["../proto/llm.proto"], &["../proto"])?;
 
-    // 2. Compile llama.cpp via cmake
-    compile_llama_cpp()?;
+    println!("cargo:rerun-if-env-changed=TSUKU_LLAMA_CPP_DIR");
+    println!("cargo:rerun-if-env-changed=TSUKU_LLAMA_CPP_SYSTEM");
+    let llama_cpp_dir = env::var_os("TSUKU_LLAMA_CPP_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("llama.cpp"));
+
+    // 2. Compile llama.cpp via cmake, or link the system library
+    if env::var("TSUKU_LLAMA_CPP_SYSTEM").is_ok_and(|v| v == "1") {
+        link_system_llama_cpp()?;
+    } else {
+        compile_llama_cpp(&llama_cpp_dir)?;
+    }
 
     // 3. Generate Rust bindings via bindgen
-    generate_bindings()?;
+    generate_bindings(&llama_cpp_dir)?;
+
+    Ok(())
+}
 
+/// Link a pre-built shared libllama located via pkg-config.
+fn link_system_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
+    let library = pkg_config::Config::new().probe("llama")?;
+    println!(
+        "cargo:warning=Linking system llama.cpp {} instead of building it",
+        library.version
+    );
     Ok(())
 }
 
 /// Compile llama.cpp static library via cmake.
-fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmake_config = cmake::Config::new("llama.cpp");
+fn compile_llama_cpp(source_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmake_config = cmake::Config::new(source_dir);
 
     // Common settings
     cmake_config
@@ -45,7 +75,10 @@ fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
         // driver can JIT-compile for newer architectures.
         if let Ok(archs) = env::var("TSUKU_CUDA_ARCHITECTURES") {
             cmake_config.define("CMAKE_CUDA_ARCHITECTURES", &archs);
-            println!("cargo:warning=Building with CUDA support (architectures: {})", archs);
+            println!(
+                "cargo:warning=Building with CUDA support (architectures: {})",
+                archs
+            );
         } else {
             println!("cargo:warning=Building with CUDA support (native architectures)");
         }
@@ -56,6 +89,27 @@ fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
         cmake_config.define("GGML_CUDA", "OFF");
     }
 
+    #[cfg(feature = "hipblas")]
+    {
+        cmake_config.define("GGML_HIP", "ON");
+        // Same idea as TSUKU_CUDA_ARCHITECTURES: pin the gfx targets instead of
+        // letting rocm detect the GPU on the build machine (e.g. "gfx1030;gfx1100").
+        if let Ok(targets) = env::var("TSUKU_HIP_TARGETS") {
+            cmake_config.define("AMDGPU_TARGETS", &targets);
+            println!(
+                "cargo:warning=Building with ROCm/HIP support (targets: {})",
+                targets
+            );
+        } else {
+            println!("cargo:warning=Building with ROCm/HIP support (native targets)");
+        }
+    }
+
+    #[cfg(not(feature = "hipblas"))]
+    {
+        cmake_config.define("GGML_HIP", "OFF");
+    }
+
     #[cfg(feature = "metal")]
     {
         cmake_config.define("GGML_METAL", "ON");
@@ -78,6 +132,16 @@ fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
         cmake_config.define("GGML_VULKAN", "OFF");
     }
 
+    // OpenBLAS speeds up prompt processing on the CPU. Without the feature
+    // GGML_BLAS is left at llama.cpp's default, which is Accelerate on macOS.
+    #[cfg(feature = "openblas")]
+    {
+        cmake_config
+            .define("GGML_BLAS", "ON")
+            .define("GGML_BLAS_VENDOR", "OpenBLAS");
+        println!("cargo:warning=Building with OpenBLAS support");
+    }
+
     // Build the library
     let dst = cmake_config.build();
 
@@ -136,6 +200,17 @@ fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
         println!("cargo:rustc-link-lib=cublasLt");
     }
 
+    #[cfg(feature = "hipblas")]
+    {
+        let rocm_path = env::var("ROCM_PATH").unwrap_or_else(|_| "/opt/rocm".to_string());
+        println!("cargo:rustc-link-search=native={}/lib", rocm_path);
+        // Same static-before-dynamic ordering as CUDA above.
+        println!("cargo:rustc-link-lib=static=ggml-hip");
+        println!("cargo:rustc-link-lib=amdhip64");
+        println!("cargo:rustc-link-lib=hipblas");
+        println!("cargo:rustc-link-lib=rocblas");
+    }
+
     #[cfg(feature = "vulkan")]
     {
         // ggml-vulkan (static) must come before -lvulkan (dynamic) for the
@@ -144,20 +219,32 @@ fn compile_llama_cpp() -> Result<(), Box<dyn std::error::Error>> {
         println!("cargo:rustc-link-lib=vulkan");
     }
 
+    #[cfg(feature = "openblas")]
+    {
+        // macOS already links ggml-blas above. Static before dynamic, as for
+        // the GPU backends.
+        #[cfg(not(target_os = "macos"))]
+        println!("cargo:rustc-link-lib=static=ggml-blas");
+        println!("cargo:rustc-link-lib=openblas");
+    }
+
     // Rerun if llama.cpp sources change
-    println!("cargo:rerun-if-changed=llama.cpp/");
+    println!("cargo:rerun-if-changed={}", source_dir.display());
 
     Ok(())
 }
 
 /// Generate Rust bindings for llama.cpp via bindgen.
-fn generate_bindings() -> Result<(), Box<dyn std::error::Error>> {
+fn generate_bindings(source_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let llama_h = source_dir.join("include/llama.h");
+    let ggml_h = source_dir.join("ggml/include/ggml.h");
+
     let bindings = bindgen::Builder::default()
         // Input header
-        .header("llama.cpp/include/llama.h")
+        .header(llama_h.to_string_lossy())
         // Include path
-        .clang_arg("-Illama.cpp/include")
-        .clang_arg("-Illama.cpp/ggml/include")
+        .clang_arg(format!("-I{}", source_dir.join("include").display()))
+        .clang_arg(format!("-I{}", source_dir.join("ggml/include").display()))
         // Add system include paths for GCC headers
         .clang_arg("-I/usr/lib/gcc/x86_64-linux-gnu/13/include")
         .clang_arg("-I/usr/include")
@@ -190,8 +277,8 @@ fn generate_bindings() -> Result<(), Box<dyn std::error::Error>> {
     bindings.write_to_file(out_path.join("bindings.rs"))?;
 
     // Rerun if header changes
-    println!("cargo:rerun-if-changed=llama.cpp/include/llama.h");
-    println!("cargo:rerun-if-changed=llama.cpp/ggml/include/ggml.h");
+    println!("cargo:rerun-if-changed={}", llama_h.display());
+    println!("cargo:rerun-if-changed={}", ggml_h.display());
 
     Ok(())
 }
@@ -0,0 +1,134 @@
+//! CPU affinity for the inference threads.
+//!
+//! Lets operators on shared machines confine the daemon to a fixed set of
+//! cores (e.g. `--cpu-affinity 0-3`) for predictable latency.
+
+use std::collections::BTreeSet;
+
+/// Parse a core list such as `"0-3"` or `"0,2,4"` (or a mix, `"0-1,6"`).
+///
+/// Every core must be below `n_cores`.
+pub fn parse_cpu_list(spec: &str, n_cores: usize) -> Result<BTreeSet<usize>, String> {
+    let parse_core = |s: &str| {
+        let core = s
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid CPU number '{}'", s.trim()))?;
+        if core >= n_cores {
+            return Err(format!(
+                "CPU {} is out of range (this machine has {} cores: 0-{})",
+                core,
+                n_cores,
+                n_cores.saturating_sub(1)
+            ));
+        }
+        Ok(core)
+    };
+
+    let mut cores = BTreeSet::new();
+    for part in spec.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            return Err(format!("empty entry in CPU list '{}'", spec));
+        }
+        match part.split_once('-') {
+            Some((start, end)) => {
+                // Both ends are checked before expanding, so a huge range
+                // can't allocate a huge set
+                let (start, end) = (parse_core(start)?, parse_core(end)?);
+                if start > end {
+                    return Err(format!("invalid CPU range '{}'", part));
+                }
+                cores.extend(start..=end);
+            }
+            None => {
+                cores.insert(parse_core(part)?);
+            }
+        }
+    }
+
+    Ok(cores)
+}
+
+/// Number of online CPU cores.
+pub fn online_cores() -> usize {
+    #[cfg(unix)]
+    {
+        let n = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+        if n > 0 {
+            return n as usize;
+        }
+    }
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Pin every thread of this process to `cores`.
+///
+/// Threads created afterwards (including llama.cpp's compute threads)
+/// inherit the mask from the thread that spawns them.
+#[cfg(target_os = "linux")]
+pub fn set_cpu_affinity(cores: &BTreeSet<usize>) -> std::io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &core in cores {
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+
+    // sched_setaffinity applies to a single thread, so walk all of them
+    for entry in std::fs::read_dir("/proc/self/task")? {
+        let Ok(tid) = entry?.file_name().to_string_lossy().parse::<libc::pid_t>() else {
+            continue;
+        };
+        let result =
+            unsafe { libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// CPU pinning is only implemented on Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn set_cpu_affinity(_cores: &BTreeSet<usize>) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "CPU affinity is only supported on Linux",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_range() {
+        let cores = parse_cpu_list("0-3", 8).unwrap();
+        assert_eq!(cores.into_iter().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_parse_cpu_list() {
+        let cores = parse_cpu_list("0,2,4", 8).unwrap();
+        assert_eq!(cores.into_iter().collect::<Vec<_>>(), vec![0, 2, 4]);
+
+        let cores = parse_cpu_list("0-1, 6", 8).unwrap();
+        assert_eq!(cores.into_iter().collect::<Vec<_>>(), vec![0, 1, 6]);
+    }
+
+    #[test]
+    fn test_parse_cpu_list_rejects_out_of_range() {
+        assert!(parse_cpu_list("0-8", 8).is_err());
+        assert!(parse_cpu_list("12", 8).is_err());
+        assert!(parse_cpu_list("0-18446744073709551615", 8).is_err());
+    }
+
+    #[test]
+    fn test_parse_cpu_list_rejects_malformed() {
+        assert!(parse_cpu_list("3-1", 8).is_err());
+        assert!(parse_cpu_list("a", 8).is_err());
+        assert!(parse_cpu_list("0,,1", 8).is_err());
+        assert!(parse_cpu_list("", 8).is_err());
+    }
+}
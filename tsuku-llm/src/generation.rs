@@ -0,0 +1,669 @@
+//! Token generation loop.
+//!
+//! Drives the sample/decode cycle after the prompt has been decoded. The loop
+//! is written against the `InferenceContext` trait rather than `LlamaContext`
+//! directly so it can be exercised in tests without loading a model.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::llama::{self, LlamaContext, Sampler};
+use crate::logits;
+
+/// Operations the generation loop needs from an inference context.
+pub trait InferenceContext {
+    /// Decode a batch of tokens starting at `pos`.
+    fn decode(&mut self, tokens: &[i32], pos: i32) -> llama::Result<()>;
+
+    /// Get logits for a batch index from the last decode.
+    fn get_logits(&self, idx: i32) -> &[f32];
+
+    /// Check if a token is an end-of-generation token.
+    fn is_eog(&self, token: i32) -> bool;
+
+    /// The end-of-sequence token, used to bias toward shorter outputs.
+    fn eos_token(&self) -> i32;
+
+    /// The beginning-of-sequence token.
+    fn bos_token(&self) -> i32;
+
+    /// Convert tokens back to text.
+    fn detokenize(&self, tokens: &[i32]) -> llama::Result<String>;
+
+    /// Raw bytes of one token's text, which may end mid-character.
+    fn token_bytes(&self, token: i32) -> llama::Result<Vec<u8>>;
+
+    /// Clear the KV cache so the next decode starts fresh.
+    fn clear_kv_cache(&mut self);
+
+    /// Drop cached positions from `pos` onwards. Returns false if that isn't
+    /// supported and the whole cache was cleared instead.
+    fn truncate_kv_cache(&mut self, pos: i32) -> bool;
+}
+
+impl InferenceContext for LlamaContext {
+    fn decode(&mut self, tokens: &[i32], pos: i32) -> llama::Result<()> {
+        LlamaContext::decode(self, tokens, pos)
+    }
+
+    fn get_logits(&self, idx: i32) -> &[f32] {
+        LlamaContext::get_logits(self, idx)
+    }
+
+    fn is_eog(&self, token: i32) -> bool {
+        self.model().is_eog(token)
+    }
+
+    fn eos_token(&self) -> i32 {
+        self.model().eos_token()
+    }
+
+    fn bos_token(&self) -> i32 {
+        self.model().bos_token()
+    }
+
+    fn detokenize(&self, tokens: &[i32]) -> llama::Result<String> {
+        LlamaContext::detokenize(self, tokens)
+    }
+
+    fn token_bytes(&self, token: i32) -> llama::Result<Vec<u8>> {
+        self.token_to_bytes(token)
+    }
+
+    fn clear_kv_cache(&mut self) {
+        LlamaContext::clear_kv_cache(self)
+    }
+
+    fn truncate_kv_cache(&mut self, pos: i32) -> bool {
+        LlamaContext::truncate_kv_cache(self, pos)
+    }
+}
+
+/// Why the generation loop stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopCause {
+    /// The model produced an end-of-generation token.
+    Eog,
+    /// The token budget was exhausted.
+    MaxTokens,
+    /// The wall-clock generation timeout was reached.
+    Timeout,
+    /// The consumer asked to stop (e.g. the stream receiver went away).
+    Cancelled,
+    /// `llama_decode` failed after generation had started.
+    DecodeError(String),
+}
+
+/// Limits for a single generation.
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    /// Maximum number of tokens to generate.
+    pub max_tokens: usize,
+    /// Maximum wall-clock time for the generation.
+    pub timeout: Duration,
+    /// Per-token boost to the EOS logit (0 = disabled).
+    pub length_penalty: f32,
+    /// Bias added to the logits of these tokens.
+    pub logit_bias: Vec<(i32, f32)>,
+    /// Penalty for recently generated tokens (1.0 or less = disabled).
+    pub repetition_penalty: f32,
+}
+
+/// Result of a generation run.
+#[derive(Debug, Clone)]
+pub struct Generation {
+    /// Generated tokens (excluding the terminating EOG token).
+    pub tokens: Vec<i32>,
+    /// Why generation stopped.
+    pub stop: StopCause,
+}
+
+/// Run the sample/decode loop.
+///
+/// The prompt must already be decoded into the context, with `prompt_len`
+/// tokens occupying positions `0..prompt_len`. `on_token` is called for each
+/// accepted token and returns `false` to stop generation early.
+pub fn generate<C, F>(
+    ctx: &mut C,
+    sampler: &Sampler,
+    prompt_len: usize,
+    config: &GenerationConfig,
+    mut on_token: F,
+) -> Generation
+where
+    C: InferenceContext + ?Sized,
+    F: FnMut(&C, i32) -> bool,
+{
+    let mut tokens: Vec<i32> = Vec::new();
+
+    // After prompt decode, logits are for the last token of the final batch
+    // (-1, since the prompt may be decoded in several batches).
+    // After single-token decodes, logits are at index 0.
+    let mut logits_idx = -1;
+    let start = Instant::now();
+    let processors = logits::pipeline(config, ctx.eos_token());
+    let mut processed = Vec::new();
+
+    for pos in (prompt_len as i32..).take(config.max_tokens) {
+        if start.elapsed() > config.timeout {
+            warn!("Generation timeout reached after {:?}", config.timeout);
+            return Generation {
+                tokens,
+                stop: StopCause::Timeout,
+            };
+        }
+
+        let next_token = if processors.is_empty() {
+            sampler.sample(ctx.get_logits(logits_idx))
+        } else {
+            processed.clear();
+            processed.extend_from_slice(ctx.get_logits(logits_idx));
+            logits::apply(&processors, &mut processed, &tokens);
+            sampler.sample(&processed)
+        };
+
+        // Check for end-of-generation tokens using the model's vocabulary.
+        // For Qwen 2.5, this includes <|im_end|> (151645), <|endoftext|> (151643), etc.
+        if ctx.is_eog(next_token) {
+            debug!("EOG token {} encountered", next_token);
+            return Generation {
+                tokens,
+                stop: StopCause::Eog,
+            };
+        }
+
+        tokens.push(next_token);
+
+        if !on_token(ctx, next_token) {
+            debug!(
+                "Generation cancelled by consumer after {} tokens",
+                tokens.len()
+            );
+            return Generation {
+                tokens,
+                stop: StopCause::Cancelled,
+            };
+        }
+
+        if let Err(e) = ctx.decode(&[next_token], pos) {
+            return Generation {
+                tokens,
+                stop: StopCause::DecodeError(e.to_string()),
+            };
+        }
+
+        logits_idx = 0;
+    }
+
+    Generation {
+        tokens,
+        stop: StopCause::MaxTokens,
+    }
+}
+
+/// Generate `n` independent continuations of `prompt`, which must already be
+/// decoded into the context at positions `0..prompt.len()`.
+///
+/// Each sample after the first rewinds the KV cache to the end of the prompt
+/// and re-decodes its last token to get the prompt's logits back, so the
+/// prompt isn't decoded again. If the cache can't be rewound it was cleared,
+/// and the whole prompt is decoded again. A failure there ends sampling
+/// early with a `DecodeError` sample.
+///
+/// Each sample runs until its own stop, so one that ends early keeps its
+/// shorter output and stop cause instead of running to the longest.
+pub fn generate_samples<C, F>(
+    ctx: &mut C,
+    sampler: &Sampler,
+    prompt: &[i32],
+    n: usize,
+    config: &GenerationConfig,
+    mut on_token: F,
+) -> Vec<Generation>
+where
+    C: InferenceContext + ?Sized,
+    F: FnMut(&C, i32) -> bool,
+{
+    let mut samples = Vec::with_capacity(n);
+    for i in 0..n {
+        if i > 0 {
+            let (&last, _) = prompt.split_last().expect("prompt is never empty");
+            let last_pos = prompt.len() as i32 - 1;
+            let rewound = if ctx.truncate_kv_cache(last_pos) {
+                ctx.decode(&[last], last_pos)
+            } else {
+                ctx.decode(prompt, 0)
+            };
+            if let Err(e) = rewound {
+                samples.push(Generation {
+                    tokens: Vec::new(),
+                    stop: StopCause::DecodeError(e.to_string()),
+                });
+                break;
+            }
+        }
+
+        let generation = generate(ctx, sampler, prompt.len(), config, &mut on_token);
+        let cancelled = generation.stop == StopCause::Cancelled;
+        samples.push(generation);
+        if cancelled {
+            break;
+        }
+    }
+    samples
+}
+
+/// Remembers which prompt prefix a context's KV cache holds.
+///
+/// Requests usually share a large system prompt, and re-decoding it each
+/// time dominates latency. When a prompt starts with the same prefix as the
+/// previous one on this context, only the tokens after it are decoded.
+#[derive(Debug, Default)]
+pub struct PrefixCache {
+    /// Hash and length of the prefix currently in the KV cache.
+    cached: Option<(u64, usize)>,
+}
+
+impl PrefixCache {
+    /// Decode a prompt made of `prefix` followed by `suffix`.
+    ///
+    /// On a hit, whatever the previous request left after the prefix (its
+    /// messages and output) is dropped and decoding resumes at the end of
+    /// the prefix. A different prefix, e.g. a changed system prompt or tool
+    /// list, clears the cache and decodes from the start.
+    pub fn decode_prompt<C: InferenceContext + ?Sized>(
+        &mut self,
+        ctx: &mut C,
+        prefix: &[i32],
+        suffix: &[i32],
+    ) -> llama::Result<()> {
+        let key = (hash_tokens(prefix), prefix.len());
+        let pos = prefix.len() as i32;
+
+        if self.cached == Some(key) && ctx.truncate_kv_cache(pos) {
+            debug!("Reusing {} cached prefix tokens", prefix.len());
+        } else {
+            self.cached = None;
+            ctx.clear_kv_cache();
+            ctx.decode(prefix, 0)?;
+            self.cached = Some(key);
+        }
+        ctx.decode(suffix, pos)
+    }
+}
+
+fn hash_tokens(tokens: &[i32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    tokens.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Turns a stream of token bytes into text without splitting characters.
+///
+/// Tokenizers work on bytes, so a multibyte character (CJK, emoji) can be
+/// split across tokens. Decoding each token on its own would replace both
+/// halves with U+FFFD; this holds back a trailing incomplete sequence until
+/// the token that completes it arrives.
+#[derive(Debug, Default)]
+pub struct StreamingDetokenizer {
+    pending: Vec<u8>,
+}
+
+impl StreamingDetokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append one token's bytes and return the text that is now complete.
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+
+        let mut text = String::new();
+        let mut rest = &self.pending[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).expect("prefix is valid UTF-8"));
+                    match e.error_len() {
+                        // Bytes no later token can complete
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // A character cut off at the end; wait for more
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = rest.to_vec();
+        text
+    }
+
+    /// Flush whatever is left once the stream ends.
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+/// Scripted inference context for exercising the generation loop in tests.
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use crate::llama::LlamaError;
+
+    /// A fake context that emits a fixed token script.
+    ///
+    /// Each call to `get_logits` returns a one-hot vector favoring the next
+    /// scripted token. Tokens are rendered as `"<id>"` by `detokenize`.
+    pub struct MockContext {
+        script: Vec<i32>,
+        step: usize,
+        logits: Vec<f32>,
+        eog_token: i32,
+        /// Fail the Nth decode call (1-based).
+        pub fail_decode_at: Option<usize>,
+        /// Number of tokens passed to `decode` so far.
+        pub decoded_tokens: usize,
+        /// Tokens passed to the most recent `decode`.
+        pub last_batch: Vec<i32>,
+        /// Number of times the KV cache was cleared.
+        pub kv_clears: usize,
+        /// End position and script step after each decode, so truncating
+        /// the KV cache rewinds the script too.
+        checkpoints: Vec<(i32, usize)>,
+        /// Scripts to switch to, in order, on each KV cache truncation, so
+        /// samples branching from a prompt can differ.
+        pub branches: Vec<Vec<i32>>,
+    }
+
+    impl MockContext {
+        pub const VOCAB_SIZE: usize = 64;
+        pub const BOS_TOKEN: i32 = 1;
+
+        /// Create a context that generates `script` and then the EOG token.
+        pub fn new(script: Vec<i32>, eog_token: i32) -> Self {
+            let mut ctx = Self {
+                script,
+                step: 0,
+                logits: vec![0.0; Self::VOCAB_SIZE],
+                eog_token,
+                fail_decode_at: None,
+                decoded_tokens: 0,
+                last_batch: Vec::new(),
+                kv_clears: 0,
+                checkpoints: Vec::new(),
+                branches: Vec::new(),
+            };
+            ctx.refresh_logits();
+            ctx
+        }
+
+        fn refresh_logits(&mut self) {
+            let next = self
+                .script
+                .get(self.step)
+                .copied()
+                .unwrap_or(self.eog_token);
+            self.logits.iter_mut().for_each(|l| *l = 0.0);
+            self.logits[next as usize] = 10.0;
+        }
+    }
+
+    impl InferenceContext for MockContext {
+        fn decode(&mut self, tokens: &[i32], pos: i32) -> llama::Result<()> {
+            // Like LlamaContext, an empty batch is a no-op
+            if tokens.is_empty() {
+                return Ok(());
+            }
+            self.step += 1;
+            if self.fail_decode_at == Some(self.step) {
+                return Err(LlamaError::Decode(
+                    "llama_decode returned error code 1".to_string(),
+                ));
+            }
+            self.decoded_tokens += tokens.len();
+            self.last_batch = tokens.to_vec();
+            self.checkpoints
+                .push((pos + tokens.len() as i32, self.step));
+            self.refresh_logits();
+            Ok(())
+        }
+
+        fn get_logits(&self, _idx: i32) -> &[f32] {
+            &self.logits
+        }
+
+        fn is_eog(&self, token: i32) -> bool {
+            token == self.eog_token
+        }
+
+        fn eos_token(&self) -> i32 {
+            self.eog_token
+        }
+
+        fn bos_token(&self) -> i32 {
+            Self::BOS_TOKEN
+        }
+
+        fn detokenize(&self, tokens: &[i32]) -> llama::Result<String> {
+            Ok(tokens.iter().map(|t| format!("<{}>", t)).collect())
+        }
+
+        fn token_bytes(&self, token: i32) -> llama::Result<Vec<u8>> {
+            Ok(format!("<{}>", token).into_bytes())
+        }
+
+        fn clear_kv_cache(&mut self) {
+            self.kv_clears += 1;
+        }
+
+        fn truncate_kv_cache(&mut self, pos: i32) -> bool {
+            self.checkpoints.retain(|&(end, _)| end <= pos);
+            if !self.branches.is_empty() {
+                self.script = self.branches.remove(0);
+            }
+            self.step = self.checkpoints.last().map_or(0, |&(_, step)| step);
+            self.refresh_logits();
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::MockContext;
+    use super::*;
+
+    fn config(max_tokens: usize) -> GenerationConfig {
+        GenerationConfig {
+            max_tokens,
+            timeout: Duration::from_secs(60),
+            length_penalty: 0.0,
+            logit_bias: Vec::new(),
+            repetition_penalty: 1.0,
+        }
+    }
+
+    fn mock(script: Vec<i32>) -> MockContext {
+        MockContext::new(script, 63)
+    }
+
+    #[test]
+    fn test_generate_samples_branch_from_the_decoded_prompt() {
+        let mut ctx = mock(vec![9, 5, 6]);
+        let prompt = [1, 2];
+        ctx.decode(&prompt, 0).unwrap();
+
+        let samples = generate_samples(
+            &mut ctx,
+            &Sampler::greedy(),
+            &prompt,
+            3,
+            &config(10),
+            |_, _| true,
+        );
+        let tokens: Vec<_> = samples.iter().map(|s| s.tokens.clone()).collect();
+        assert_eq!(tokens, vec![vec![5, 6]; 3]);
+        // The prompt once, two tokens per sample, and the last prompt token
+        // again for each extra sample
+        assert_eq!(ctx.decoded_tokens, 2 + 3 * 2 + 2);
+        assert_eq!(ctx.kv_clears, 0);
+
+        let samples = generate_samples(
+            &mut ctx,
+            &Sampler::with_temperature(0.8),
+            &prompt,
+            3,
+            &config(10),
+            |_, _| true,
+        );
+        assert_eq!(samples.len(), 3);
+    }
+
+    #[test]
+    fn test_generate_stops_at_eog() {
+        let mut ctx = mock(vec![5, 6, 7]);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &config(10), |_, _| true);
+        assert_eq!(out.tokens, vec![5, 6, 7]);
+        assert_eq!(out.stop, StopCause::Eog);
+    }
+
+    #[test]
+    fn test_logit_bias_bans_and_forces_tokens() {
+        // Banned: the scripted token can't be chosen, so the greedy pick
+        // falls to the lowest of the tied rest
+        let banned = GenerationConfig {
+            logit_bias: vec![(5, -1e9), (1000, 50.0), (-1, 50.0)],
+            ..config(10)
+        };
+        let mut ctx = mock(vec![5, 6]);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &banned, |_, _| true);
+        assert_eq!(out.tokens, vec![0, 6]);
+
+        // Forced: chosen over the scripted tokens and EOG alike
+        let forced = GenerationConfig {
+            logit_bias: vec![(7, 100.0)],
+            ..config(3)
+        };
+        let mut ctx = mock(vec![5, 6]);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &forced, |_, _| true);
+        assert_eq!(out.tokens, vec![7, 7, 7]);
+        assert_eq!(out.stop, StopCause::MaxTokens);
+    }
+
+    #[test]
+    fn test_generate_stops_at_max_tokens() {
+        let mut ctx = mock(vec![5, 6, 7]);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &config(2), |_, _| true);
+        assert_eq!(out.tokens, vec![5, 6]);
+        assert_eq!(out.stop, StopCause::MaxTokens);
+    }
+
+    #[test]
+    fn test_generate_reports_decode_error() {
+        let mut ctx = mock(vec![5, 6, 7]);
+        ctx.fail_decode_at = Some(2);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &config(10), |_, _| true);
+        assert_eq!(out.tokens, vec![5, 6]);
+        assert!(matches!(out.stop, StopCause::DecodeError(_)));
+    }
+
+    #[test]
+    fn test_generate_cancelled_by_consumer() {
+        let mut ctx = mock(vec![5, 6, 7]);
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &config(10), |_, token| {
+            token != 6
+        });
+        assert_eq!(out.tokens, vec![5, 6]);
+        assert_eq!(out.stop, StopCause::Cancelled);
+    }
+
+    #[test]
+    fn test_generate_length_penalty_ends_early() {
+        // The scripted token has logit 10; a penalty of 4 per token lets EOS
+        // overtake it once three tokens have been generated.
+        let mut ctx = mock(vec![5, 6, 7, 8, 9, 10]);
+        let config = GenerationConfig {
+            length_penalty: 4.0,
+            ..config(10)
+        };
+        let out = generate(&mut ctx, &Sampler::greedy(), 1, &config, |_, _| true);
+        assert_eq!(out.tokens, vec![5, 6, 7]);
+        assert_eq!(out.stop, StopCause::Eog);
+    }
+
+    #[test]
+    fn test_streaming_detokenizer_joins_split_character() {
+        // "日本" is e6 97 a5 e6 9c ac; split the second character across tokens
+        let mut detok = StreamingDetokenizer::new();
+        let pieces: [&[u8]; 3] = [b"\xe6\x97\xa5\xe6", b"\x9c", b"\xac!"];
+
+        let out: Vec<String> = pieces.iter().map(|p| detok.push(p)).collect();
+        assert_eq!(out, vec!["日", "", "本!"]);
+        assert!(!out.concat().contains(char::REPLACEMENT_CHARACTER));
+        assert_eq!(detok.finish(), "");
+    }
+
+    #[test]
+    fn test_streaming_detokenizer_replaces_invalid_bytes() {
+        let mut detok = StreamingDetokenizer::new();
+        assert_eq!(detok.push(b"a\xffb"), "a\u{fffd}b");
+        // An incomplete character left at the end is flushed lossily
+        assert_eq!(detok.push(b"\xe6\x97"), "");
+        assert_eq!(detok.finish(), "\u{fffd}");
+    }
+
+    #[test]
+    fn test_prefix_cache_reuses_shared_prefix() {
+        let mut ctx = mock(vec![]);
+        let mut cache = PrefixCache::default();
+        let system = vec![1; 100];
+
+        cache.decode_prompt(&mut ctx, &system, &[2, 3]).unwrap();
+        assert_eq!(ctx.decoded_tokens, 102);
+
+        // Same system prompt: only the new messages are decoded
+        cache.decode_prompt(&mut ctx, &system, &[4, 5, 6]).unwrap();
+        assert_eq!(ctx.decoded_tokens, 105);
+        assert_eq!(ctx.kv_clears, 1);
+    }
+
+    #[test]
+    fn test_prefix_cache_invalidated_by_new_prefix() {
+        let mut ctx = mock(vec![]);
+        let mut cache = PrefixCache::default();
+
+        cache.decode_prompt(&mut ctx, &[1; 100], &[2]).unwrap();
+        let mut tools_added = vec![1; 100];
+        tools_added.push(9);
+        cache.decode_prompt(&mut ctx, &tools_added, &[2]).unwrap();
+
+        assert_eq!(ctx.decoded_tokens, 101 + 102);
+        assert_eq!(ctx.kv_clears, 2);
+    }
+
+    #[test]
+    fn test_prefix_cache_not_kept_after_failed_decode() {
+        let mut ctx = mock(vec![]);
+        ctx.fail_decode_at = Some(1);
+        let mut cache = PrefixCache::default();
+
+        assert!(cache.decode_prompt(&mut ctx, &[1; 10], &[2]).is_err());
+        cache.decode_prompt(&mut ctx, &[1; 10], &[2]).unwrap();
+        assert_eq!(ctx.kv_clears, 2);
+    }
+}
@@ -0,0 +1,293 @@
+//! Minimal GGUF metadata reader.
+//!
+//! Reads the key/value metadata section at the start of a GGUF file without
+//! loading tensors or going through llama.cpp. Only the header is parsed, so
+//! this is cheap even for multi-gigabyte models.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use thiserror::Error;
+
+/// "GGUF" in little-endian byte order.
+const GGUF_MAGIC: u32 = 0x4655_4747;
+
+/// Errors while reading GGUF metadata.
+#[derive(Error, Debug)]
+pub enum GgufError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("not a GGUF file (bad magic {0:#010x})")]
+    BadMagic(u32),
+
+    #[error("unsupported GGUF version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("unknown metadata value type {0}")]
+    UnknownValueType(u32),
+
+    #[error("metadata string is not valid UTF-8")]
+    InvalidString,
+}
+
+/// A metadata value. Arrays are skipped and only their length is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    UInt(u64),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    Array(u64),
+}
+
+/// Metadata key/value pairs from a GGUF header.
+#[derive(Debug, Clone, Default)]
+pub struct GgufMetadata {
+    values: HashMap<String, MetadataValue>,
+}
+
+impl GgufMetadata {
+    /// Look up a raw metadata value.
+    pub fn get(&self, key: &str) -> Option<&MetadataValue> {
+        self.values.get(key)
+    }
+
+    /// Get a string value.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            MetadataValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get an unsigned integer value.
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            MetadataValue::UInt(v) => Some(*v),
+            MetadataValue::Int(v) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Model architecture (e.g., "qwen2", "llama").
+    pub fn architecture(&self) -> Option<&str> {
+        self.get_str("general.architecture")
+    }
+
+    /// Quantization name derived from `general.file_type` (e.g., "q4_k_m").
+    pub fn quantization(&self) -> Option<&'static str> {
+        file_type_name(self.get_u64("general.file_type")?)
+    }
+
+    /// The model's chat template (Jinja source), if it embeds one.
+    pub fn chat_template(&self) -> Option<&str> {
+        self.get_str("tokenizer.chat_template")
+    }
+
+    /// Number of files the model is split across (1 when not split).
+    pub fn split_count(&self) -> u32 {
+        self.get_u64("split.count")
+            .map(|n| n as u32)
+            .unwrap_or(1)
+            .max(1)
+    }
+}
+
+/// Read the metadata section of a GGUF file.
+pub fn read_metadata(path: &Path) -> Result<GgufMetadata, GgufError> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let magic = read_u32(&mut reader)?;
+    if magic != GGUF_MAGIC {
+        return Err(GgufError::BadMagic(magic));
+    }
+
+    // Version 1 used 32-bit counts and lengths; it predates every model we ship.
+    let version = read_u32(&mut reader)?;
+    if version < 2 {
+        return Err(GgufError::UnsupportedVersion(version));
+    }
+
+    let _tensor_count = read_u64(&mut reader)?;
+    let kv_count = read_u64(&mut reader)?;
+
+    let mut values = HashMap::new();
+    for _ in 0..kv_count {
+        let key = read_string(&mut reader)?;
+        let value_type = read_u32(&mut reader)?;
+        let value = read_value(&mut reader, value_type)?;
+        values.insert(key, value);
+    }
+
+    Ok(GgufMetadata { values })
+}
+
+/// Map a llama.cpp `llama_ftype` to its conventional quantization name.
+fn file_type_name(file_type: u64) -> Option<&'static str> {
+    let name = match file_type {
+        0 => "f32",
+        1 => "f16",
+        2 => "q4_0",
+        3 => "q4_1",
+        7 => "q8_0",
+        8 => "q5_0",
+        9 => "q5_1",
+        10 => "q2_k",
+        11 => "q3_k_s",
+        12 => "q3_k_m",
+        13 => "q3_k_l",
+        14 => "q4_k_s",
+        15 => "q4_k_m",
+        16 => "q5_k_s",
+        17 => "q5_k_m",
+        18 => "q6_k",
+        32 => "bf16",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn read_value<R: Read>(reader: &mut R, value_type: u32) -> Result<MetadataValue, GgufError> {
+    let value = match value_type {
+        0 => MetadataValue::UInt(read_bytes::<R, 1>(reader)?[0] as u64),
+        1 => MetadataValue::Int(read_bytes::<R, 1>(reader)?[0] as i8 as i64),
+        2 => MetadataValue::UInt(u16::from_le_bytes(read_bytes(reader)?) as u64),
+        3 => MetadataValue::Int(i16::from_le_bytes(read_bytes(reader)?) as i64),
+        4 => MetadataValue::UInt(read_u32(reader)? as u64),
+        5 => MetadataValue::Int(i32::from_le_bytes(read_bytes(reader)?) as i64),
+        6 => MetadataValue::Float(f32::from_le_bytes(read_bytes(reader)?) as f64),
+        7 => MetadataValue::Bool(read_bytes::<R, 1>(reader)?[0] != 0),
+        8 => MetadataValue::String(read_string(reader)?),
+        9 => {
+            let element_type = read_u32(reader)?;
+            let len = read_u64(reader)?;
+            for _ in 0..len {
+                read_value(reader, element_type)?;
+            }
+            MetadataValue::Array(len)
+        }
+        10 => MetadataValue::UInt(read_u64(reader)?),
+        11 => MetadataValue::Int(i64::from_le_bytes(read_bytes(reader)?)),
+        12 => MetadataValue::Float(f64::from_le_bytes(read_bytes(reader)?)),
+        other => return Err(GgufError::UnknownValueType(other)),
+    };
+    Ok(value)
+}
+
+fn read_bytes<R: Read, const N: usize>(reader: &mut R) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(reader)?))
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, GgufError> {
+    let len = read_u64(reader)?;
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    String::from_utf8(buf).map_err(|_| GgufError::InvalidString)
+}
+
+/// Builders for small GGUF fixtures used in tests.
+#[cfg(test)]
+pub mod testing {
+    /// Build a GGUF v3 header with the given string and u32 metadata and no tensors.
+    pub fn build_gguf(strings: &[(&str, &str)], u32s: &[(&str, u32)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"GGUF");
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes());
+        out.extend_from_slice(&((strings.len() + u32s.len()) as u64).to_le_bytes());
+
+        let push_str = |out: &mut Vec<u8>, s: &str| {
+            out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        };
+        for (key, value) in strings {
+            push_str(&mut out, key);
+            out.extend_from_slice(&8u32.to_le_bytes());
+            push_str(&mut out, value);
+        }
+        for (key, value) in u32s {
+            push_str(&mut out, key);
+            out.extend_from_slice(&4u32.to_le_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::build_gguf;
+    use super::*;
+
+    #[test]
+    fn test_read_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.gguf");
+        let bytes = build_gguf(
+            &[("general.architecture", "qwen2")],
+            &[("general.file_type", 15)],
+        );
+        std::fs::write(&path, bytes).unwrap();
+
+        let meta = read_metadata(&path).unwrap();
+        assert_eq!(meta.architecture(), Some("qwen2"));
+        assert_eq!(meta.quantization(), Some("q4_k_m"));
+        assert_eq!(meta.split_count(), 1);
+    }
+
+    #[test]
+    fn test_read_metadata_skips_arrays() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.gguf");
+        let mut bytes = build_gguf(&[], &[]);
+        // Patch kv_count to 2 and append an array of strings followed by a string
+        bytes[16..24].copy_from_slice(&2u64.to_le_bytes());
+        let push_str = |out: &mut Vec<u8>, s: &str| {
+            out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        };
+        push_str(&mut bytes, "tokenizer.ggml.tokens");
+        bytes.extend_from_slice(&9u32.to_le_bytes());
+        bytes.extend_from_slice(&8u32.to_le_bytes());
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        push_str(&mut bytes, "<s>");
+        push_str(&mut bytes, "</s>");
+        push_str(&mut bytes, "general.architecture");
+        bytes.extend_from_slice(&8u32.to_le_bytes());
+        push_str(&mut bytes, "llama");
+        std::fs::write(&path, bytes).unwrap();
+
+        let meta = read_metadata(&path).unwrap();
+        assert_eq!(
+            meta.get("tokenizer.ggml.tokens"),
+            Some(&MetadataValue::Array(2))
+        );
+        assert_eq!(meta.architecture(), Some("llama"));
+    }
+
+    #[test]
+    fn test_read_metadata_rejects_non_gguf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        std::fs::write(&path, b"not a gguf file at all").unwrap();
+
+        assert!(matches!(read_metadata(&path), Err(GgufError::BadMagic(_))));
+    }
+}
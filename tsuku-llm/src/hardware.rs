@@ -11,6 +11,8 @@ use tracing::{debug, info, warn};
 pub enum GpuBackend {
     /// NVIDIA CUDA (highest priority on supported systems)
     Cuda,
+    /// AMD ROCm/HIP
+    Rocm,
     /// Apple Metal (macOS ARM)
     Metal,
     /// Vulkan (AMD, Intel, or NVIDIA fallback)
@@ -23,6 +25,7 @@ impl std::fmt::Display for GpuBackend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             GpuBackend::Cuda => write!(f, "cuda"),
+            GpuBackend::Rocm => write!(f, "rocm"),
             GpuBackend::Metal => write!(f, "metal"),
             GpuBackend::Vulkan => write!(f, "vulkan"),
             GpuBackend::None => write!(f, "cpu"),
@@ -46,6 +49,9 @@ pub struct HardwareProfile {
     pub gpu_backend: GpuBackend,
     /// Available video memory in bytes (0 for CPU-only)
     pub vram_bytes: u64,
+    /// GPU device name (e.g. "NVIDIA GeForce RTX 4090"); None for CPU-only
+    /// or when it can't be queried
+    pub gpu_name: Option<String>,
     /// System RAM in bytes
     pub ram_bytes: u64,
     /// CPU instruction set features
@@ -57,6 +63,7 @@ impl Default for HardwareProfile {
         Self {
             gpu_backend: GpuBackend::None,
             vram_bytes: 0,
+            gpu_name: None,
             ram_bytes: 0,
             cpu_features: CpuFeatures::default(),
         }
@@ -72,6 +79,10 @@ pub fn compiled_backend() -> GpuBackend {
     {
         return GpuBackend::Cuda;
     }
+    #[cfg(feature = "hipblas")]
+    {
+        return GpuBackend::Rocm;
+    }
     #[cfg(feature = "metal")]
     {
         return GpuBackend::Metal;
@@ -87,10 +98,11 @@ pub fn compiled_backend() -> GpuBackend {
 /// Returns a human-readable description of the hardware detected at runtime.
 ///
 /// This is used in error messages when the compiled backend doesn't match
-/// available hardware. Examples: "Vulkan", "CUDA", "Metal", "None".
+/// available hardware. Examples: "Vulkan", "CUDA", "ROCm", "Metal", "None".
 pub fn describe_detected_hardware(profile: &HardwareProfile) -> String {
     match profile.gpu_backend {
         GpuBackend::Cuda => "CUDA".to_string(),
+        GpuBackend::Rocm => "ROCm".to_string(),
         GpuBackend::Metal => "Metal".to_string(),
         GpuBackend::Vulkan => "Vulkan".to_string(),
         GpuBackend::None => "None".to_string(),
@@ -138,16 +150,21 @@ impl HardwareDetector {
             vram_bytes as f64 / 1e9
         );
 
+        let gpu_name = Self::detect_gpu_name(gpu_backend);
+        debug!("GPU name: {:?}", gpu_name);
+
         let profile = HardwareProfile {
             gpu_backend,
             vram_bytes,
+            gpu_name,
             ram_bytes,
             cpu_features,
         };
 
         info!(
-            "Hardware profile: backend={}, vram={:.1}GB, ram={:.1}GB, avx2={}, avx512={}",
+            "Hardware profile: backend={}, gpu={}, vram={:.1}GB, ram={:.1}GB, avx2={}, avx512={}",
             profile.gpu_backend,
+            profile.gpu_name.as_deref().unwrap_or("unknown"),
             profile.vram_bytes as f64 / 1e9,
             profile.ram_bytes as f64 / 1e9,
             profile.cpu_features.avx2,
@@ -158,13 +175,18 @@ impl HardwareDetector {
     }
 
     /// Detect GPU backend and VRAM.
-    /// Priority: CUDA > Metal > Vulkan > None
+    /// Priority: CUDA > ROCm > Metal > Vulkan > None
     fn detect_gpu() -> (GpuBackend, u64) {
         // Try CUDA first (NVIDIA)
         if let Some(vram) = Self::detect_cuda() {
             return (GpuBackend::Cuda, vram);
         }
 
+        // Try ROCm (AMD)
+        if let Some(vram) = Self::detect_rocm() {
+            return (GpuBackend::Rocm, vram);
+        }
+
         // Try Metal (macOS ARM)
         if let Some(vram) = Self::detect_metal() {
             return (GpuBackend::Metal, vram);
@@ -179,8 +201,24 @@ impl HardwareDetector {
         (GpuBackend::None, 0)
     }
 
+    /// Detect the name of the GPU `backend` runs on.
+    fn detect_gpu_name(backend: GpuBackend) -> Option<String> {
+        match backend {
+            GpuBackend::Cuda => Self::get_nvidia_name(),
+            GpuBackend::Metal => Self::get_metal_name(),
+            GpuBackend::Vulkan => Self::get_vulkan_name(),
+            GpuBackend::Rocm | GpuBackend::None => None,
+        }
+    }
+
     /// Detect NVIDIA CUDA availability by probing for the CUDA library.
     fn detect_cuda() -> Option<u64> {
+        // An empty CUDA_VISIBLE_DEVICES hides every GPU from CUDA
+        if cuda_visible_devices().is_some_and(|devices| devices.is_empty()) {
+            debug!("CUDA_VISIBLE_DEVICES is empty, no CUDA devices visible");
+            return None;
+        }
+
         #[cfg(target_os = "linux")]
         {
             // Check for CUDA driver library
@@ -220,9 +258,30 @@ impl HardwareDetector {
     }
 
     /// Get NVIDIA VRAM via nvidia-smi command.
+    ///
+    /// nvidia-smi lists every GPU in the machine, so the result is filtered
+    /// through CUDA_VISIBLE_DEVICES when it is set.
     fn get_nvidia_vram() -> Option<u64> {
         let output = std::process::Command::new("nvidia-smi")
-            .args(["--query-gpu=memory.total", "--format=csv,noheader,nounits"])
+            .args([
+                "--query-gpu=index,uuid,memory.total",
+                "--format=csv,noheader,nounits",
+            ])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_nvidia_smi_vram(&stdout, cuda_visible_devices().as_deref())
+    }
+
+    /// Query the name of the GPU CUDA will use via nvidia-smi.
+    fn get_nvidia_name() -> Option<String> {
+        let output = std::process::Command::new("nvidia-smi")
+            .args(["--query-gpu=index,uuid,name", "--format=csv,noheader"])
             .output()
             .ok()?;
 
@@ -231,9 +290,84 @@ impl HardwareDetector {
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        // nvidia-smi reports in MiB, take the first GPU's memory
-        let mib: u64 = stdout.lines().next()?.trim().parse().ok()?;
-        Some(mib * 1024 * 1024)
+        parse_nvidia_smi_name(&stdout, cuda_visible_devices().as_deref())
+    }
+
+    /// Metal runs on the SoC's GPU, which takes the chip's name (e.g.
+    /// "Apple M2 Pro").
+    fn get_metal_name() -> Option<String> {
+        #[cfg(target_os = "macos")]
+        {
+            let output = std::process::Command::new("sysctl")
+                .args(["-n", "machdep.cpu.brand_string"])
+                .output()
+                .ok()?;
+            if output.status.success() {
+                let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                return Some(name).filter(|name| !name.is_empty());
+            }
+        }
+
+        None
+    }
+
+    /// Query the first Vulkan device's name via vulkaninfo.
+    fn get_vulkan_name() -> Option<String> {
+        let output = std::process::Command::new("vulkaninfo")
+            .arg("--summary")
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        parse_vulkaninfo_device_name(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Detect AMD ROCm availability by probing for the HIP runtime or ROCm SMI library.
+    ///
+    /// Returns `None` when the VRAM can't be read, so detection falls through
+    /// to Vulkan instead of reporting a GPU with no memory.
+    fn detect_rocm() -> Option<u64> {
+        #[cfg(target_os = "linux")]
+        {
+            let rocm_paths = [
+                "/opt/rocm/lib/libamdhip64.so",
+                "/opt/rocm/lib/librocm_smi64.so",
+                "/usr/lib/x86_64-linux-gnu/libamdhip64.so",
+                "/usr/lib/x86_64-linux-gnu/librocm_smi64.so",
+                "/usr/lib64/libamdhip64.so",
+                "/usr/lib64/librocm_smi64.so",
+            ];
+
+            for path in &rocm_paths {
+                if Path::new(path).exists() {
+                    debug!("Found ROCm library at {}", path);
+                    let vram = Self::get_rocm_vram();
+                    if vram.is_none() {
+                        debug!("Couldn't read VRAM via rocm-smi, skipping ROCm");
+                    }
+                    return vram;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Get AMD VRAM via rocm-smi command.
+    fn get_rocm_vram() -> Option<u64> {
+        let output = std::process::Command::new("rocm-smi")
+            .args(["--showmeminfo", "vram", "--csv"])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        parse_rocm_smi_vram(&String::from_utf8_lossy(&output.stdout))
     }
 
     /// Detect Apple Metal availability (macOS ARM only).
@@ -367,8 +501,30 @@ impl HardwareDetector {
 
     #[cfg(target_os = "windows")]
     fn detect_system_ram() -> u64 {
-        // Use GetPhysicallyInstalledSystemMemory or GlobalMemoryStatusEx
-        // For simplicity, shell out to wmic
+        if let Some(bytes) = Self::detect_system_ram_api() {
+            return bytes;
+        }
+
+        // Fallback to wmic (deprecated, and missing on newer Windows builds)
+        Self::detect_system_ram_wmic()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn detect_system_ram_api() -> Option<u64> {
+        use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+        let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+        status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+        if unsafe { GlobalMemoryStatusEx(&mut status) } != 0 && status.ullTotalPhys > 0 {
+            Some(status.ullTotalPhys)
+        } else {
+            warn!("GlobalMemoryStatusEx() failed, falling back to wmic");
+            None
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn detect_system_ram_wmic() -> u64 {
         let output = std::process::Command::new("wmic")
             .args(["computersystem", "get", "TotalPhysicalMemory", "/value"])
             .output();
@@ -426,6 +582,84 @@ impl HardwareDetector {
     }
 }
 
+/// Devices listed in CUDA_VISIBLE_DEVICES, or `None` when it is unset.
+///
+/// Entries are device indices or UUIDs. An empty list means no GPU is visible.
+fn cuda_visible_devices() -> Option<Vec<String>> {
+    let value = std::env::var("CUDA_VISIBLE_DEVICES").ok()?;
+    Some(parse_device_list(&value))
+}
+
+fn parse_device_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse the VRAM of the first usable GPU from
+/// `nvidia-smi --query-gpu=index,uuid,memory.total --format=csv,noheader,nounits`.
+///
+/// With `visible` set, only the listed devices are considered, in the listed
+/// order (CUDA renumbers them from 0 that way). nvidia-smi reports MiB.
+fn parse_nvidia_smi_vram(csv: &str, visible: Option<&[String]>) -> Option<u64> {
+    let mib: u64 = nvidia_smi_device_field(csv, visible)?.parse().ok()?;
+    Some(mib * 1024 * 1024)
+}
+
+/// Parse the name of the first usable GPU from
+/// `nvidia-smi --query-gpu=index,uuid,name --format=csv,noheader`.
+fn parse_nvidia_smi_name(csv: &str, visible: Option<&[String]>) -> Option<String> {
+    let name = nvidia_smi_device_field(csv, visible)?;
+    Some(name.to_string()).filter(|name| !name.is_empty())
+}
+
+/// The third field of the first usable GPU's row in `index, uuid, <field>`
+/// nvidia-smi output, chosen as `parse_nvidia_smi_vram` describes.
+fn nvidia_smi_device_field<'a>(csv: &'a str, visible: Option<&[String]>) -> Option<&'a str> {
+    let rows: Vec<Vec<&str>> = csv
+        .lines()
+        .map(|line| line.splitn(3, ',').map(str::trim).collect::<Vec<_>>())
+        .filter(|fields| fields.len() == 3)
+        .collect();
+
+    let row = match visible {
+        None => rows.first()?,
+        Some(devices) => devices.iter().find_map(|device| {
+            rows.iter()
+                .find(|fields| fields[0] == device || fields[1] == device)
+        })?,
+    };
+    Some(row[2])
+}
+
+/// Parse the first device's name from `vulkaninfo --summary`, which lists
+/// each device with a `deviceName = ...` line.
+fn parse_vulkaninfo_device_name(summary: &str) -> Option<String> {
+    summary.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "deviceName")
+            .then(|| value.trim().to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// Parse the total VRAM of the first GPU from `rocm-smi --showmeminfo vram --csv`.
+///
+/// The output is a header row followed by one row per device, e.g.
+/// `card0,17163091968,1234567`. rocm-smi reports bytes.
+fn parse_rocm_smi_vram(csv: &str) -> Option<u64> {
+    let mut lines = csv.lines().map(str::trim).filter(|l| !l.is_empty());
+    let header: Vec<&str> = lines.next()?.split(',').collect();
+    let column = header
+        .iter()
+        .position(|h| h.contains("Total Memory") && !h.contains("Used"))?;
+    let row = lines.next()?;
+    row.split(',').nth(column)?.trim().parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,6 +667,7 @@ mod tests {
     #[test]
     fn test_gpu_backend_display() {
         assert_eq!(format!("{}", GpuBackend::Cuda), "cuda");
+        assert_eq!(format!("{}", GpuBackend::Rocm), "rocm");
         assert_eq!(format!("{}", GpuBackend::Metal), "metal");
         assert_eq!(format!("{}", GpuBackend::Vulkan), "vulkan");
         assert_eq!(format!("{}", GpuBackend::None), "cpu");
@@ -484,13 +719,27 @@ mod tests {
         assert!(ram > 0, "Expected RAM to be detected on Linux/macOS");
     }
 
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_system_ram_detection_windows_api() {
+        let ram = HardwareDetector::detect_system_ram_api();
+        assert!(
+            ram.is_some_and(|ram| ram > 0),
+            "Expected GlobalMemoryStatusEx to report RAM"
+        );
+    }
+
     #[test]
     fn test_compiled_backend_returns_valid_variant() {
         let backend = compiled_backend();
         // The compiled backend should be one of the known variants.
         // Which one depends on cargo features, but it should never panic.
         match backend {
-            GpuBackend::Cuda | GpuBackend::Metal | GpuBackend::Vulkan | GpuBackend::None => {}
+            GpuBackend::Cuda
+            | GpuBackend::Rocm
+            | GpuBackend::Metal
+            | GpuBackend::Vulkan
+            | GpuBackend::None => {}
         }
     }
 
@@ -502,6 +751,12 @@ mod tests {
         };
         assert_eq!(describe_detected_hardware(&profile_cuda), "CUDA");
 
+        let profile_rocm = HardwareProfile {
+            gpu_backend: GpuBackend::Rocm,
+            ..HardwareProfile::default()
+        };
+        assert_eq!(describe_detected_hardware(&profile_rocm), "ROCm");
+
         let profile_metal = HardwareProfile {
             gpu_backend: GpuBackend::Metal,
             ..HardwareProfile::default()
@@ -550,4 +805,84 @@ mod tests {
         assert!(msg.contains("Detected hardware supports: None"));
         assert!(msg.contains("GPU acceleration is required"));
     }
+
+    #[test]
+    fn test_parse_rocm_smi_vram() {
+        let csv = "device,VRAM Total Memory (B),VRAM Total Used Memory (B)\n\
+                   card0,17163091968,1234567\n\
+                   card1,8573157376,0\n";
+        assert_eq!(parse_rocm_smi_vram(csv), Some(17_163_091_968));
+        assert_eq!(parse_rocm_smi_vram(""), None);
+        assert_eq!(parse_rocm_smi_vram("device,foo\ncard0,1\n"), None);
+    }
+
+    const NVIDIA_SMI_TWO_GPUS: &str = "0, GPU-aaaa-1111, 24576\n1, GPU-bbbb-2222, 8192\n";
+
+    #[test]
+    fn test_parse_nvidia_smi_vram_first_gpu() {
+        assert_eq!(
+            parse_nvidia_smi_vram(NVIDIA_SMI_TWO_GPUS, None),
+            Some(24576 * 1024 * 1024)
+        );
+        assert_eq!(parse_nvidia_smi_vram("", None), None);
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_vram_respects_visible_devices() {
+        let visible = parse_device_list("1");
+        assert_eq!(
+            parse_nvidia_smi_vram(NVIDIA_SMI_TWO_GPUS, Some(&visible)),
+            Some(8192 * 1024 * 1024)
+        );
+
+        let visible = parse_device_list("GPU-bbbb-2222");
+        assert_eq!(
+            parse_nvidia_smi_vram(NVIDIA_SMI_TWO_GPUS, Some(&visible)),
+            Some(8192 * 1024 * 1024)
+        );
+
+        let visible = parse_device_list("5");
+        assert_eq!(
+            parse_nvidia_smi_vram(NVIDIA_SMI_TWO_GPUS, Some(&visible)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_name() {
+        let csv = "0, GPU-aaaa-1111, NVIDIA GeForce RTX 4090\n\
+                   1, GPU-bbbb-2222, Tesla T4\n";
+        assert_eq!(
+            parse_nvidia_smi_name(csv, None).as_deref(),
+            Some("NVIDIA GeForce RTX 4090")
+        );
+
+        let visible = parse_device_list("GPU-bbbb-2222");
+        assert_eq!(
+            parse_nvidia_smi_name(csv, Some(&visible)).as_deref(),
+            Some("Tesla T4")
+        );
+        assert_eq!(parse_nvidia_smi_name("", None), None);
+    }
+
+    #[test]
+    fn test_parse_vulkaninfo_device_name() {
+        let summary = "Devices:\n========\nGPU0:\n\
+                       \tapiVersion         = 1.3.277\n\
+                       \tdeviceType         = PHYSICAL_DEVICE_TYPE_DISCRETE_GPU\n\
+                       \tdeviceName         = AMD Radeon RX 7900 XTX (RADV NAVI31)\n\
+                       GPU1:\n\
+                       \tdeviceName         = llvmpipe (LLVM 17.0.6, 256 bits)\n";
+        assert_eq!(
+            parse_vulkaninfo_device_name(summary).as_deref(),
+            Some("AMD Radeon RX 7900 XTX (RADV NAVI31)")
+        );
+        assert_eq!(parse_vulkaninfo_device_name("Devices:\n"), None);
+    }
+
+    #[test]
+    fn test_parse_device_list() {
+        assert_eq!(parse_device_list("0, 2"), vec!["0", "2"]);
+        assert!(parse_device_list("").is_empty());
+    }
 }
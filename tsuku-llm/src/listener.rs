@@ -0,0 +1,231 @@
+//! Incoming connections for the gRPC server.
+//!
+//! The daemon always listens on its Unix socket, which the co-located Go
+//! client uses. A TCP listener can be added alongside it (e.g. for remote
+//! debugging); connections from both feed the same server. Calls over TCP
+//! can be required to carry a bearer token, since anyone who can reach the
+//! port could otherwise use the model, and must be when the port is
+//! reachable from other hosts. The socket is protected by its file
+//! permissions and stays open.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio_stream::wrappers::{TcpListenerStream, UnixListenerStream};
+use tonic::service::Interceptor;
+use tonic::transport::server::Connected;
+use tonic::{Request, Status};
+
+/// A client connection accepted on either listener.
+pub enum Connection {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+/// Merge the Unix listener and an optional TCP listener into one stream of
+/// connections for `serve_with_incoming`.
+pub fn incoming(
+    unix: UnixListener,
+    tcp: Option<TcpListener>,
+) -> impl Stream<Item = io::Result<Connection>> {
+    let unix = UnixListenerStream::new(unix).map(|conn| conn.map(Connection::Unix));
+    // With no TCP listener this side is simply an empty stream
+    let tcp = stream::iter(tcp)
+        .flat_map(TcpListenerStream::new)
+        .map(|conn| conn.map(Connection::Tcp));
+    stream::select(unix, tcp)
+}
+
+/// Which listener a request arrived on, available in request extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Unix,
+    Tcp,
+}
+
+impl Connected for Connection {
+    type ConnectInfo = Transport;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        match self {
+            Connection::Unix(_) => Transport::Unix,
+            Connection::Tcp(_) => Transport::Tcp,
+        }
+    }
+}
+
+/// Interceptor requiring `authorization: Bearer <token>` on TCP calls.
+///
+/// With no token configured every call is let through. With one, only calls
+/// known to have arrived on the Unix socket go without it.
+#[derive(Clone)]
+pub struct TcpAuth {
+    token: Option<String>,
+}
+
+impl TcpAuth {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+}
+
+impl Interceptor for TcpAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(token) = &self.token else {
+            return Ok(request);
+        };
+        if request.extensions().get::<Transport>() == Some(&Transport::Unix) {
+            return Ok(request);
+        }
+
+        let provided = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        match provided {
+            Some(provided) if constant_time_eq(provided.as_bytes(), token.as_bytes()) => {
+                Ok(request)
+            }
+            _ => Err(Status::unauthenticated("missing or invalid bearer token")),
+        }
+    }
+}
+
+/// Compare without exiting early, so timing doesn't reveal the matching prefix.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(s) => Pin::new(s).poll_read(cx, buf),
+            Connection::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Connection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Connection::Unix(s) => Pin::new(s).poll_write(cx, buf),
+            Connection::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(s) => Pin::new(s).poll_flush(cx),
+            Connection::Tcp(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Connection::Unix(s) => Pin::new(s).poll_shutdown(cx),
+            Connection::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_incoming_accepts_both_listeners() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("llm.sock");
+        let unix = UnixListener::bind(&socket).unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tcp_addr = tcp.local_addr().unwrap();
+        let mut incoming = Box::pin(incoming(unix, Some(tcp)));
+
+        let mut unix_client = UnixStream::connect(&socket).await.unwrap();
+        let mut tcp_client = TcpStream::connect(tcp_addr).await.unwrap();
+
+        let (mut unix_seen, mut tcp_seen) = (false, false);
+        for _ in 0..2 {
+            let mut conn = incoming.next().await.unwrap().unwrap();
+            match conn {
+                Connection::Unix(_) => unix_seen = true,
+                Connection::Tcp(_) => tcp_seen = true,
+            }
+            conn.write_all(b"ok").await.unwrap();
+        }
+        assert!(unix_seen && tcp_seen);
+
+        let mut buf = [0u8; 2];
+        unix_client.read_exact(&mut buf).await.unwrap();
+        tcp_client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ok");
+    }
+
+    #[tokio::test]
+    async fn test_incoming_unix_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("llm.sock");
+        let mut incoming = Box::pin(incoming(UnixListener::bind(&socket).unwrap(), None));
+
+        let _client = UnixStream::connect(&socket).await.unwrap();
+        let conn = incoming.next().await.unwrap().unwrap();
+        assert!(matches!(conn, Connection::Unix(_)));
+    }
+
+    fn request(transport: Transport, authorization: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        request.extensions_mut().insert(transport);
+        if let Some(value) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", value.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn test_tcp_token_required() {
+        let mut auth = TcpAuth::new(Some("s3cret".to_string()));
+
+        let status = auth.call(request(Transport::Tcp, None)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = auth
+            .call(request(Transport::Tcp, Some("Bearer wrong")))
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        assert!(auth
+            .call(request(Transport::Tcp, Some("Bearer s3cret")))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unix_socket_needs_no_token() {
+        let mut auth = TcpAuth::new(Some("s3cret".to_string()));
+        assert!(auth.call(request(Transport::Unix, None)).is_ok());
+
+        let mut open = TcpAuth::new(None);
+        assert!(open.call(request(Transport::Tcp, None)).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_transport_needs_token() {
+        let mut auth = TcpAuth::new(Some("s3cret".to_string()));
+        let status = auth.call(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+}
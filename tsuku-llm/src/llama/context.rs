@@ -5,8 +5,9 @@ use std::sync::Arc;
 
 use super::bindings::{
     llama_batch_free, llama_batch_init, llama_context, llama_decode, llama_free,
-    llama_get_logits_ith, llama_get_memory, llama_memory_clear, llama_n_ctx,
-    llama_new_context_with_model, llama_token_to_piece, llama_tokenize,
+    llama_get_embeddings_seq, llama_get_logits_ith, llama_get_memory, llama_memory_clear,
+    llama_memory_seq_pos_max, llama_memory_seq_pos_min, llama_memory_seq_rm, llama_n_ctx,
+    llama_new_context_with_model,
 };
 use super::error::{LlamaError, Result};
 use super::model::LlamaModel;
@@ -54,6 +55,12 @@ impl LlamaContext {
         unsafe { llama_n_ctx(self.ptr.as_ptr()) }
     }
 
+    /// Check that `token_count` tokens plus `reserve` more (e.g. room for
+    /// the output) fit in the context, before any of them are decoded.
+    pub fn check_fits(&self, token_count: usize, reserve: usize) -> Result<()> {
+        check_window(token_count.saturating_add(reserve), self.n_ctx() as usize)
+    }
+
     /// Clear the KV cache.
     ///
     /// Call this between independent generations to reset the context.
@@ -62,77 +69,56 @@ impl LlamaContext {
             let memory = llama_get_memory(self.ptr.as_ptr());
             llama_memory_clear(memory, true);
         }
+        // A stale sequence would leak one request's tokens into the next
+        let remaining = self.kv_cache_used_cells();
+        if remaining > 0 {
+            tracing::warn!(
+                "KV cache still holds {} cells after clear, removing",
+                remaining
+            );
+            unsafe {
+                let memory = llama_get_memory(self.ptr.as_ptr());
+                llama_memory_seq_rm(memory, -1, -1, -1);
+            }
+        }
         tracing::debug!("Cleared KV cache");
     }
 
-    /// Tokenize a string.
-    ///
-    /// # Arguments
-    ///
-    /// * `text` - The text to tokenize
-    /// * `add_special` - Whether to add special tokens (BOS/EOS)
-    /// * `parse_special` - Whether to parse special tokens in the text
-    ///
-    /// # Returns
-    ///
-    /// A vector of token IDs.
-    pub fn tokenize(&self, text: &str, add_special: bool, parse_special: bool) -> Result<Vec<i32>> {
-        let vocab = self._model.vocab();
-
-        // First call with null buffer to get the required token count.
-        // llama_tokenize returns -n_tokens when buffer is too small (or null/0).
-        // We negate this to get the actual count needed.
-        // Special case: i32::MIN indicates tokenization overflow.
-        let n_tokens_result = unsafe {
-            llama_tokenize(
-                vocab,
-                text.as_ptr() as *const core::ffi::c_char,
-                text.len() as i32,
-                std::ptr::null_mut(),
-                0,
-                add_special,
-                parse_special,
+    /// Number of positions sequence 0 occupies in the KV cache.
+    pub fn kv_cache_used_cells(&self) -> usize {
+        let (min, max) = unsafe {
+            let memory = llama_get_memory(self.ptr.as_ptr());
+            (
+                llama_memory_seq_pos_min(memory, 0),
+                llama_memory_seq_pos_max(memory, 0),
             )
         };
-
-        // Check for overflow error (result too large for int32)
-        if n_tokens_result == i32::MIN {
-            return Err(LlamaError::Tokenization(
-                "tokenization result exceeds int32 limit".to_string(),
-            ));
-        }
-
-        // The return value is negative (the negation of the required size).
-        // Negate it to get the actual required buffer size.
-        let n_tokens = -n_tokens_result;
-
-        if n_tokens <= 0 {
-            // Empty tokenization result
-            return Ok(Vec::new());
+        if max < 0 {
+            0
+        } else {
+            (max - min + 1) as usize
         }
+    }
 
-        // Allocate buffer and tokenize
-        let mut tokens = vec![0i32; n_tokens as usize];
-        let actual = unsafe {
-            llama_tokenize(
-                vocab,
-                text.as_ptr() as *const core::ffi::c_char,
-                text.len() as i32,
-                tokens.as_mut_ptr(),
-                tokens.len() as i32,
-                add_special,
-                parse_special,
-            )
+    /// Drop every cached position from `pos` onwards, keeping the tokens
+    /// before it for the next decode.
+    ///
+    /// Returns false if the model's memory can't be partially removed (e.g.
+    /// recurrent models); the cache is cleared entirely in that case.
+    pub fn truncate_kv_cache(&mut self, pos: i32) -> bool {
+        let removed = unsafe {
+            let memory = llama_get_memory(self.ptr.as_ptr());
+            llama_memory_seq_rm(memory, 0, pos, -1)
         };
-
-        if actual < 0 {
-            return Err(LlamaError::Tokenization(
-                "llama_tokenize failed on second call".to_string(),
-            ));
+        if !removed {
+            self.clear_kv_cache();
         }
+        removed
+    }
 
-        tokens.truncate(actual as usize);
-        Ok(tokens)
+    /// Tokenize a string; see `LlamaModel::tokenize`.
+    pub fn tokenize(&self, text: &str, add_special: bool, parse_special: bool) -> Result<Vec<i32>> {
+        self._model.tokenize(text, add_special, parse_special)
     }
 
     /// Decode a batch of tokens.
@@ -151,14 +137,7 @@ impl LlamaContext {
         }
 
         // Check context window
-        let n_ctx = self.n_ctx() as usize;
-        let end_pos = pos as usize + tokens.len();
-        if end_pos > n_ctx {
-            return Err(LlamaError::ContextWindowExceeded {
-                used: end_pos,
-                max: n_ctx,
-            });
-        }
+        check_window(pos as usize + tokens.len(), self.n_ctx() as usize)?;
 
         // Create and fill batch
         let n_tokens = tokens.len() as i32;
@@ -210,67 +189,36 @@ impl LlamaContext {
         unsafe { std::slice::from_raw_parts(ptr, n_vocab) }
     }
 
+    /// Get the pooled embedding of the last decoded sequence.
+    ///
+    /// The context must have been created with `embeddings: true` and a
+    /// pooling method other than `Pooling::None`. The slice has `n_embd`
+    /// elements.
+    pub fn embeddings(&self) -> Result<&[f32]> {
+        let n_embd = self._model.n_embd() as usize;
+        let ptr = unsafe { llama_get_embeddings_seq(self.ptr.as_ptr(), 0) };
+        if ptr.is_null() {
+            return Err(LlamaError::Embeddings(
+                "no pooled embeddings (context needs embeddings and pooling enabled)".to_string(),
+            ));
+        }
+        Ok(unsafe { std::slice::from_raw_parts(ptr, n_embd) })
+    }
+
     /// Get the model this context was created from.
     pub fn model(&self) -> &Arc<LlamaModel> {
         &self._model
     }
 
-    /// Convert tokens back to text (detokenization).
-    ///
-    /// # Arguments
-    ///
-    /// * `tokens` - Token IDs to convert to text
-    ///
-    /// # Returns
-    ///
-    /// The text representation of the tokens.
+    /// Convert tokens back to text; see `LlamaModel::detokenize`.
     pub fn detokenize(&self, tokens: &[i32]) -> Result<String> {
-        let vocab = self._model.vocab();
-        let mut output = String::new();
-
-        for &token in tokens {
-            // Start with a reasonable buffer size
-            let mut buf = vec![0u8; 256];
-            let len = unsafe {
-                llama_token_to_piece(
-                    vocab,
-                    token,
-                    buf.as_mut_ptr() as *mut core::ffi::c_char,
-                    buf.len() as i32,
-                    0,     // lstrip
-                    false, // special - don't render special tokens
-                )
-            };
-
-            if len < 0 {
-                // Negative means buffer too small, resize and retry
-                let needed = (-len) as usize;
-                buf.resize(needed, 0);
-                let len = unsafe {
-                    llama_token_to_piece(
-                        vocab,
-                        token,
-                        buf.as_mut_ptr() as *mut core::ffi::c_char,
-                        buf.len() as i32,
-                        0,
-                        false,
-                    )
-                };
-                if len < 0 {
-                    return Err(LlamaError::Detokenization(format!(
-                        "failed to detokenize token {}: buffer still too small",
-                        token
-                    )));
-                }
-                buf.truncate(len as usize);
-            } else {
-                buf.truncate(len as usize);
-            }
-
-            output.push_str(&String::from_utf8_lossy(&buf));
-        }
+        self._model.detokenize(tokens)
+    }
 
-        Ok(output)
+    /// Get the raw bytes of a single token's text; see
+    /// `LlamaModel::token_to_bytes`.
+    pub fn token_to_bytes(&self, token: i32) -> Result<Vec<u8>> {
+        self._model.token_to_bytes(token)
     }
 
     /// Get the raw context pointer for use with samplers.
@@ -294,9 +242,18 @@ impl Drop for LlamaContext {
     }
 }
 
+/// Error if `used` positions don't fit in a context of `n_ctx`.
+fn check_window(used: usize, n_ctx: usize) -> Result<()> {
+    if used > n_ctx {
+        return Err(LlamaError::ContextWindowExceeded { used, max: n_ctx });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llama::{test_model_path, ModelParams, ParamOverrides, RopeScaling};
 
     #[test]
     fn test_context_params_default() {
@@ -310,4 +267,136 @@ mod tests {
         let params = ContextParams::with_context_size(4096);
         assert_eq!(params.n_ctx, 4096);
     }
+
+    #[test]
+    fn test_context_params_rope_fields_mapped() {
+        let params = ContextParams {
+            rope_scaling: RopeScaling::Linear,
+            rope_freq_base: 1_000_000.0,
+            rope_freq_scale: 0.5,
+            yarn_ext_factor: 1.0,
+            yarn_attn_factor: 0.9,
+            yarn_beta_fast: 16.0,
+            yarn_beta_slow: 2.0,
+            yarn_orig_ctx: 32768,
+            ..Default::default()
+        };
+        let raw = params.into_raw();
+        assert_eq!(raw.rope_scaling_type, RopeScaling::Linear as i32);
+        assert_eq!(raw.rope_freq_base, 1_000_000.0);
+        assert_eq!(raw.rope_freq_scale, 0.5);
+        assert_eq!(raw.yarn_ext_factor, 1.0);
+        assert_eq!(raw.yarn_attn_factor, 0.9);
+        assert_eq!(raw.yarn_beta_fast, 16.0);
+        assert_eq!(raw.yarn_beta_slow, 2.0);
+        assert_eq!(raw.yarn_orig_ctx, 32768);
+    }
+
+    #[test]
+    fn test_context_params_yarn_scaling() {
+        let params = ContextParams::with_context_size(65536).with_yarn_scaling(32768);
+        assert_eq!(params.rope_scaling, RopeScaling::Yarn);
+        assert_eq!(params.rope_freq_scale, 0.5);
+        assert_eq!(params.yarn_orig_ctx, 32768);
+
+        // Within the trained context, nothing changes
+        let params = ContextParams::with_context_size(8192).with_yarn_scaling(32768);
+        assert_eq!(params.rope_scaling, ContextParams::default().rope_scaling);
+    }
+
+    #[test]
+    fn test_context_params_override_applied() {
+        let params = ContextParams {
+            overrides: ParamOverrides::parse(&["rope_freq_base=500000", "yarn_orig_ctx=32768"])
+                .unwrap(),
+            ..Default::default()
+        };
+        let raw = params.into_raw();
+        assert_eq!(raw.rope_freq_base, 500000.0);
+        assert_eq!(raw.yarn_orig_ctx, 32768);
+    }
+
+    #[test]
+    fn test_param_overrides_unknown_key_ignored() {
+        let overrides = ParamOverrides::parse(&["no_such_param=1", "yarn_ext_factor=1.0"]).unwrap();
+        assert_eq!(overrides.len(), 1);
+    }
+
+    #[test]
+    fn test_param_overrides_invalid_value_rejected() {
+        assert!(ParamOverrides::parse(&["rope_freq_base=fast"]).is_err());
+        assert!(ParamOverrides::parse(&["rope_freq_base"]).is_err());
+    }
+
+    #[test]
+    fn test_check_window() {
+        assert!(check_window(512, 512).is_ok());
+        assert!(matches!(
+            check_window(513, 512),
+            Err(LlamaError::ContextWindowExceeded {
+                used: 513,
+                max: 512
+            })
+        ));
+    }
+
+    #[test]
+    fn test_check_window_with_huge_reserve() {
+        // A request's max_tokens is reserved in full, however large
+        let used = 200usize.saturating_add(usize::MAX);
+        assert!(matches!(
+            check_window(used, 256),
+            Err(LlamaError::ContextWindowExceeded { max: 256, .. })
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_check_fits_small_context() {
+        let path = test_model_path();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let params = ContextParams {
+            n_ctx: 256,
+            n_batch: 256,
+            ..Default::default()
+        };
+        let ctx = LlamaContext::new(model, params).unwrap();
+        assert_eq!(ctx.n_ctx(), 256);
+
+        assert!(ctx.check_fits(200, 56).is_ok());
+        let err = ctx.check_fits(200, 100).unwrap_err();
+        assert!(matches!(
+            err,
+            LlamaError::ContextWindowExceeded {
+                used: 300,
+                max: 256
+            }
+        ));
+        // Nothing was decoded
+        assert_eq!(ctx.kv_cache_used_cells(), 0);
+    }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_clear_kv_cache_frees_cells() {
+        let path = test_model_path();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let params = ContextParams {
+            n_ctx: 512,
+            n_batch: 512,
+            ..Default::default()
+        };
+        let mut ctx = LlamaContext::new(model, params).unwrap();
+
+        let tokens = ctx.tokenize("Hello, world", true, false).unwrap();
+        ctx.decode(&tokens, 0).unwrap();
+        assert_eq!(ctx.kv_cache_used_cells(), tokens.len());
+
+        ctx.clear_kv_cache();
+        assert_eq!(ctx.kv_cache_used_cells(), 0);
+    }
 }
@@ -53,6 +53,10 @@ pub enum LlamaError {
     /// Grammar error.
     #[error("grammar error: {0}")]
     Grammar(String),
+
+    /// Embeddings were not produced by the last decode.
+    #[error("embeddings unavailable: {0}")]
+    Embeddings(String),
 }
 
 /// Result type alias for llama operations.
@@ -4,6 +4,10 @@
 //! - Memory safety via RAII (Drop implementations)
 //! - Proper lifetime management (Context holds Arc<Model>)
 //! - Clear error handling via Result types
+//!
+//! Tests that need a real model load the GGUF file named by
+//! `TSUKU_LLM_TEST_MODEL` (see `test_model_path`). CI has no model, so they
+//! are `#[ignore]`d; set the variable and pass `--ignored` to run them.
 
 mod context;
 mod error;
@@ -13,8 +17,12 @@ mod params;
 mod sampler;
 
 pub use context::LlamaContext;
+pub use error::LlamaError;
+pub use error::Result;
 pub use model::LlamaModel;
-pub use params::{ContextParams, ModelParams};
+#[cfg(test)]
+pub use params::RopeScaling;
+pub use params::{ContextParams, KvCacheType, ModelParams, ParamOverrides, Pooling};
 pub use sampler::Sampler;
 
 // Re-export bindings for internal use
@@ -57,3 +65,11 @@ pub unsafe fn backend_free() {
     llama_backend_free();
     tracing::debug!("llama.cpp backend freed");
 }
+
+/// The model for model-backed tests, from `TSUKU_LLM_TEST_MODEL`.
+#[cfg(test)]
+pub fn test_model_path() -> std::path::PathBuf {
+    std::env::var_os("TSUKU_LLM_TEST_MODEL")
+        .expect("TSUKU_LLM_TEST_MODEL is unset")
+        .into()
+}
@@ -6,7 +6,8 @@ use std::ptr::NonNull;
 
 use super::bindings::{
     llama_model, llama_model_free, llama_model_load_from_file, llama_model_n_ctx_train,
-    llama_vocab, llama_vocab_n_tokens,
+    llama_model_n_embd, llama_model_n_head, llama_model_n_head_kv, llama_model_n_layer,
+    llama_model_size, llama_token_to_piece, llama_tokenize, llama_vocab, llama_vocab_n_tokens,
 };
 use super::error::{LlamaError, Result};
 use super::params::ModelParams;
@@ -16,6 +17,7 @@ use super::{backend_init, bindings};
 ///
 /// This struct owns the model and will free it when dropped.
 /// Create contexts from this model using `LlamaContext::new()`.
+/// Tokenizing only reads the vocabulary, so it needs no context.
 pub struct LlamaModel {
     ptr: NonNull<llama_model>,
 }
@@ -75,6 +77,31 @@ impl LlamaModel {
         unsafe { llama_model_n_ctx_train(self.ptr.as_ptr()) as u32 }
     }
 
+    /// Get the number of transformer layers.
+    pub fn n_layer(&self) -> u32 {
+        unsafe { llama_model_n_layer(self.ptr.as_ptr()) as u32 }
+    }
+
+    /// Get the embedding dimension.
+    pub fn n_embd(&self) -> u32 {
+        unsafe { llama_model_n_embd(self.ptr.as_ptr()) as u32 }
+    }
+
+    /// Get the number of attention heads.
+    pub fn n_head(&self) -> u32 {
+        unsafe { llama_model_n_head(self.ptr.as_ptr()) as u32 }
+    }
+
+    /// Get the number of key/value heads (smaller than `n_head` with GQA).
+    pub fn n_head_kv(&self) -> u32 {
+        unsafe { llama_model_n_head_kv(self.ptr.as_ptr()) as u32 }
+    }
+
+    /// Get the total size of the model's tensors in bytes.
+    pub fn size_bytes(&self) -> u64 {
+        unsafe { llama_model_size(self.ptr.as_ptr()) }
+    }
+
     /// Get the vocabulary size of this model.
     pub fn n_vocab(&self) -> u32 {
         let vocab = self.vocab();
@@ -88,6 +115,143 @@ impl LlamaModel {
         unsafe { bindings::llama_model_get_vocab(self.ptr.as_ptr()) }
     }
 
+    /// Tokenize a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to tokenize
+    /// * `add_special` - Whether to add special tokens (BOS/EOS)
+    /// * `parse_special` - Whether to parse special tokens in the text
+    ///
+    /// # Returns
+    ///
+    /// A vector of token IDs.
+    pub fn tokenize(&self, text: &str, add_special: bool, parse_special: bool) -> Result<Vec<i32>> {
+        let vocab = self.vocab();
+
+        // First call with null buffer to get the required token count.
+        // llama_tokenize returns -n_tokens when buffer is too small (or null/0).
+        // We negate this to get the actual count needed.
+        // Special case: i32::MIN indicates tokenization overflow.
+        let n_tokens_result = unsafe {
+            llama_tokenize(
+                vocab,
+                text.as_ptr() as *const core::ffi::c_char,
+                text.len() as i32,
+                std::ptr::null_mut(),
+                0,
+                add_special,
+                parse_special,
+            )
+        };
+
+        // Check for overflow error (result too large for int32)
+        if n_tokens_result == i32::MIN {
+            return Err(LlamaError::Tokenization(
+                "tokenization result exceeds int32 limit".to_string(),
+            ));
+        }
+
+        // The return value is negative (the negation of the required size).
+        // Negate it to get the actual required buffer size.
+        let n_tokens = -n_tokens_result;
+
+        if n_tokens <= 0 {
+            // Empty tokenization result
+            return Ok(Vec::new());
+        }
+
+        // Allocate buffer and tokenize
+        let mut tokens = vec![0i32; n_tokens as usize];
+        let actual = unsafe {
+            llama_tokenize(
+                vocab,
+                text.as_ptr() as *const core::ffi::c_char,
+                text.len() as i32,
+                tokens.as_mut_ptr(),
+                tokens.len() as i32,
+                add_special,
+                parse_special,
+            )
+        };
+
+        if actual < 0 {
+            return Err(LlamaError::Tokenization(
+                "llama_tokenize failed on second call".to_string(),
+            ));
+        }
+
+        tokens.truncate(actual as usize);
+        Ok(tokens)
+    }
+
+    /// Convert tokens back to text (detokenization).
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Token IDs to convert to text
+    ///
+    /// # Returns
+    ///
+    /// The text representation of the tokens.
+    pub fn detokenize(&self, tokens: &[i32]) -> Result<String> {
+        let mut bytes = Vec::new();
+        for &token in tokens {
+            bytes.extend_from_slice(&self.token_to_bytes(token)?);
+        }
+
+        // Decode once at the end: a multibyte character may span tokens
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Get the raw bytes of a single token's text.
+    ///
+    /// A token may hold only part of a multibyte UTF-8 character, so the
+    /// result is not necessarily valid UTF-8 on its own.
+    pub fn token_to_bytes(&self, token: i32) -> Result<Vec<u8>> {
+        let vocab = self.vocab();
+
+        // Start with a reasonable buffer size
+        let mut buf = vec![0u8; 256];
+        let len = unsafe {
+            llama_token_to_piece(
+                vocab,
+                token,
+                buf.as_mut_ptr() as *mut core::ffi::c_char,
+                buf.len() as i32,
+                0,     // lstrip
+                false, // special - don't render special tokens
+            )
+        };
+
+        if len < 0 {
+            // Negative means buffer too small, resize and retry
+            let needed = (-len) as usize;
+            buf.resize(needed, 0);
+            let len = unsafe {
+                llama_token_to_piece(
+                    vocab,
+                    token,
+                    buf.as_mut_ptr() as *mut core::ffi::c_char,
+                    buf.len() as i32,
+                    0,
+                    false,
+                )
+            };
+            if len < 0 {
+                return Err(LlamaError::Detokenization(format!(
+                    "failed to detokenize token {}: buffer still too small",
+                    token
+                )));
+            }
+            buf.truncate(len as usize);
+        } else {
+            buf.truncate(len as usize);
+        }
+
+        Ok(buf)
+    }
+
     /// Check if a token is an end-of-generation token.
     ///
     /// For Qwen 2.5, EOG tokens include `<|im_end|>` (151645),
@@ -96,6 +260,16 @@ impl LlamaModel {
         unsafe { bindings::llama_vocab_is_eog(self.vocab(), token) }
     }
 
+    /// Get the end-of-sequence token from the model's vocabulary.
+    pub fn eos_token(&self) -> i32 {
+        unsafe { bindings::llama_vocab_eos(self.vocab()) }
+    }
+
+    /// Get the beginning-of-sequence token from the model's vocabulary.
+    pub fn bos_token(&self) -> i32 {
+        unsafe { bindings::llama_vocab_bos(self.vocab()) }
+    }
+
     /// Get the raw model pointer.
     ///
     /// # Safety
@@ -118,6 +292,7 @@ impl Drop for LlamaModel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llama::ParamOverrides;
 
     #[test]
     fn test_model_params_default() {
@@ -132,6 +307,16 @@ mod tests {
         assert_eq!(params.n_gpu_layers, -1);
     }
 
+    #[test]
+    fn test_model_params_override_applied() {
+        let params = ModelParams {
+            overrides: ParamOverrides::parse(&["n_gpu_layers=20", "rope_freq_base=1e6"]).unwrap(),
+            ..ModelParams::for_gpu()
+        };
+        // Context keys in the same list are skipped for model params
+        assert_eq!(params.into_raw().n_gpu_layers, 20);
+    }
+
     #[test]
     fn test_load_nonexistent_file() {
         let result = LlamaModel::load_from_file(
@@ -140,4 +325,20 @@ mod tests {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_special_tokens_match_metadata() {
+        let path = crate::llama::test_model_path();
+        let meta = crate::gguf::read_metadata(&path).unwrap();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default()).unwrap();
+
+        if let Some(eos) = meta.get_u64("tokenizer.ggml.eos_token_id") {
+            assert_eq!(model.eos_token() as u64, eos);
+            assert!(model.is_eog(model.eos_token()));
+        }
+        if let Some(bos) = meta.get_u64("tokenizer.ggml.bos_token_id") {
+            assert_eq!(model.bos_token() as u64, bos);
+        }
+    }
 }
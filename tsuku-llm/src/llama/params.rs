@@ -4,6 +4,7 @@ use super::bindings::{
     llama_context_default_params, llama_context_params, llama_model_default_params,
     llama_model_params,
 };
+use super::error::{LlamaError, Result};
 
 /// Parameters for loading a model.
 #[derive(Debug, Clone)]
@@ -16,6 +17,9 @@ pub struct ModelParams {
 
     /// Use memory locking to prevent swapping.
     pub use_mlock: bool,
+
+    /// Raw llama.cpp overrides, applied last.
+    pub overrides: ParamOverrides,
 }
 
 impl Default for ModelParams {
@@ -25,6 +29,7 @@ impl Default for ModelParams {
             n_gpu_layers: defaults.n_gpu_layers,
             use_mmap: defaults.use_mmap,
             use_mlock: defaults.use_mlock,
+            overrides: ParamOverrides::default(),
         }
     }
 }
@@ -36,6 +41,7 @@ impl ModelParams {
             n_gpu_layers: -1, // Offload all layers
             use_mmap: true,
             use_mlock: false,
+            overrides: ParamOverrides::default(),
         }
     }
 
@@ -45,10 +51,14 @@ impl ModelParams {
         params.n_gpu_layers = self.n_gpu_layers;
         params.use_mmap = self.use_mmap;
         params.use_mlock = self.use_mlock;
+        self.overrides.apply_to_model(&mut params);
         params
     }
 }
 
+/// `LLAMA_FLASH_ATTN_TYPE_ENABLED`; the default is -1 (auto).
+const FLASH_ATTN_ENABLED: i32 = 1;
+
 /// Parameters for creating a context.
 #[derive(Debug, Clone)]
 pub struct ContextParams {
@@ -66,6 +76,42 @@ pub struct ContextParams {
 
     /// Enable embeddings mode.
     pub embeddings: bool,
+
+    /// How token embeddings are pooled into a sequence embedding.
+    pub pooling: Pooling,
+
+    /// RoPE scaling method.
+    pub rope_scaling: RopeScaling,
+
+    /// RoPE base frequency (0 = from model).
+    pub rope_freq_base: f32,
+
+    /// RoPE frequency scaling factor (0 = from model).
+    pub rope_freq_scale: f32,
+
+    /// YaRN extrapolation mix factor (negative = from model).
+    pub yarn_ext_factor: f32,
+
+    /// YaRN magnitude scaling factor.
+    pub yarn_attn_factor: f32,
+
+    /// YaRN low correction dim.
+    pub yarn_beta_fast: f32,
+
+    /// YaRN high correction dim.
+    pub yarn_beta_slow: f32,
+
+    /// YaRN original context size (0 = from model).
+    pub yarn_orig_ctx: u32,
+
+    /// Force flash attention on. When off, llama.cpp decides per device.
+    pub flash_attn: bool,
+
+    /// Element type of the K and V caches.
+    pub kv_cache_type: KvCacheType,
+
+    /// Raw llama.cpp overrides, applied last.
+    pub overrides: ParamOverrides,
 }
 
 impl Default for ContextParams {
@@ -77,6 +123,18 @@ impl Default for ContextParams {
             n_threads: defaults.n_threads,
             n_threads_batch: defaults.n_threads_batch,
             embeddings: defaults.embeddings,
+            pooling: Pooling::from_raw(defaults.pooling_type),
+            rope_scaling: RopeScaling::from_raw(defaults.rope_scaling_type),
+            rope_freq_base: defaults.rope_freq_base,
+            rope_freq_scale: defaults.rope_freq_scale,
+            yarn_ext_factor: defaults.yarn_ext_factor,
+            yarn_attn_factor: defaults.yarn_attn_factor,
+            yarn_beta_fast: defaults.yarn_beta_fast,
+            yarn_beta_slow: defaults.yarn_beta_slow,
+            yarn_orig_ctx: defaults.yarn_orig_ctx,
+            flash_attn: defaults.flash_attn_type == FLASH_ATTN_ENABLED,
+            kv_cache_type: KvCacheType::from_raw(defaults.type_k),
+            overrides: ParamOverrides::default(),
         }
     }
 }
@@ -90,6 +148,19 @@ impl ContextParams {
         }
     }
 
+    /// Stretch the model's trained context to `n_ctx` with YaRN RoPE scaling.
+    ///
+    /// Has no effect when `n_ctx` already fits in the trained context.
+    pub fn with_yarn_scaling(mut self, n_ctx_train: u32) -> Self {
+        if n_ctx_train == 0 || self.n_ctx <= n_ctx_train {
+            return self;
+        }
+        self.rope_scaling = RopeScaling::Yarn;
+        self.rope_freq_scale = n_ctx_train as f32 / self.n_ctx as f32;
+        self.yarn_orig_ctx = n_ctx_train;
+        self
+    }
+
     /// Convert to raw llama.cpp params.
     pub(crate) fn into_raw(self) -> llama_context_params {
         let mut params = unsafe { llama_context_default_params() };
@@ -98,6 +169,244 @@ impl ContextParams {
         params.n_threads = self.n_threads;
         params.n_threads_batch = self.n_threads_batch;
         params.embeddings = self.embeddings;
+        params.pooling_type = self.pooling as _;
+        params.rope_scaling_type = self.rope_scaling as _;
+        params.rope_freq_base = self.rope_freq_base;
+        params.rope_freq_scale = self.rope_freq_scale;
+        params.yarn_ext_factor = self.yarn_ext_factor;
+        params.yarn_attn_factor = self.yarn_attn_factor;
+        params.yarn_beta_fast = self.yarn_beta_fast;
+        params.yarn_beta_slow = self.yarn_beta_slow;
+        params.yarn_orig_ctx = self.yarn_orig_ctx;
+        if self.flash_attn {
+            params.flash_attn_type = FLASH_ATTN_ENABLED;
+        }
+        params.type_k = self.kv_cache_type as _;
+        params.type_v = self.kv_cache_type as _;
+        self.overrides.apply_to_context(&mut params);
         params
     }
 }
+
+/// RoPE scaling method (mirrors `enum llama_rope_scaling_type`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RopeScaling {
+    /// Use the method recorded in the model.
+    #[default]
+    FromModel = -1,
+    /// No scaling.
+    None = 0,
+    /// Linear position interpolation.
+    Linear = 1,
+    /// YaRN (Yet another RoPE extensioN).
+    Yarn = 2,
+}
+
+impl RopeScaling {
+    fn from_raw(value: i32) -> Self {
+        match value {
+            0 => Self::None,
+            1 => Self::Linear,
+            2 => Self::Yarn,
+            _ => Self::FromModel,
+        }
+    }
+}
+
+/// Embedding pooling method (mirrors `enum llama_pooling_type`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pooling {
+    /// Use the method recorded in the model.
+    #[default]
+    FromModel = -1,
+    /// No pooling; only per-token embeddings are available.
+    None = 0,
+    /// Average of all token embeddings.
+    Mean = 1,
+    /// Embedding of the first (CLS) token.
+    Cls = 2,
+    /// Embedding of the last token.
+    Last = 3,
+}
+
+impl Pooling {
+    fn from_raw(value: i32) -> Self {
+        match value {
+            0 => Self::None,
+            1 => Self::Mean,
+            2 => Self::Cls,
+            3 => Self::Last,
+            _ => Self::FromModel,
+        }
+    }
+}
+
+/// KV cache element type (mirrors the `enum ggml_type` values llama.cpp
+/// accepts for `type_k`/`type_v`).
+///
+/// Quantized caches roughly halve (Q8_0) or quarter (Q4_0) KV memory at a
+/// small quality cost. llama.cpp only supports a quantized V cache with
+/// flash attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum KvCacheType {
+    /// 16-bit floats, llama.cpp's default.
+    #[default]
+    #[value(name = "f16")]
+    F16 = 1,
+    /// 8-bit quantization.
+    #[value(name = "q8_0")]
+    Q8_0 = 8,
+    /// 4-bit quantization.
+    #[value(name = "q4_0")]
+    Q4_0 = 2,
+}
+
+impl KvCacheType {
+    fn from_raw(value: i32) -> Self {
+        match value {
+            8 => Self::Q8_0,
+            2 => Self::Q4_0,
+            _ => Self::F16,
+        }
+    }
+}
+
+/// A single raw llama.cpp parameter override.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamOverride {
+    // Context parameters
+    RopeFreqBase(f32),
+    RopeFreqScale(f32),
+    YarnExtFactor(f32),
+    YarnAttnFactor(f32),
+    YarnBetaFast(f32),
+    YarnBetaSlow(f32),
+    YarnOrigCtx(u32),
+    NUbatch(u32),
+    OffloadKqv(bool),
+    // Model parameters
+    NGpuLayers(i32),
+    MainGpu(i32),
+    CheckTensors(bool),
+}
+
+impl ParamOverride {
+    /// Keys accepted by `parse`, matching the llama.cpp struct field names.
+    pub const KEYS: &'static [&'static str] = &[
+        "rope_freq_base",
+        "rope_freq_scale",
+        "yarn_ext_factor",
+        "yarn_attn_factor",
+        "yarn_beta_fast",
+        "yarn_beta_slow",
+        "yarn_orig_ctx",
+        "n_ubatch",
+        "offload_kqv",
+        "n_gpu_layers",
+        "main_gpu",
+        "check_tensors",
+    ];
+
+    /// Parse a `key`/`value` pair.
+    ///
+    /// Returns `Ok(None)` for keys we don't support, and an error when a
+    /// supported key has a value of the wrong type.
+    pub fn parse(key: &str, value: &str) -> Result<Option<Self>> {
+        fn num<T: std::str::FromStr>(key: &str, value: &str) -> Result<T> {
+            value.trim().parse().map_err(|_| {
+                LlamaError::InvalidParam(format!("invalid value '{}' for {}", value, key))
+            })
+        }
+
+        let parsed = match key {
+            "rope_freq_base" => Self::RopeFreqBase(num(key, value)?),
+            "rope_freq_scale" => Self::RopeFreqScale(num(key, value)?),
+            "yarn_ext_factor" => Self::YarnExtFactor(num(key, value)?),
+            "yarn_attn_factor" => Self::YarnAttnFactor(num(key, value)?),
+            "yarn_beta_fast" => Self::YarnBetaFast(num(key, value)?),
+            "yarn_beta_slow" => Self::YarnBetaSlow(num(key, value)?),
+            "yarn_orig_ctx" => Self::YarnOrigCtx(num(key, value)?),
+            "n_ubatch" => Self::NUbatch(num(key, value)?),
+            "offload_kqv" => Self::OffloadKqv(num(key, value)?),
+            "n_gpu_layers" => Self::NGpuLayers(num(key, value)?),
+            "main_gpu" => Self::MainGpu(num(key, value)?),
+            "check_tensors" => Self::CheckTensors(num(key, value)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(parsed))
+    }
+}
+
+/// Validated raw parameter overrides.
+///
+/// An escape hatch for tuning llama.cpp without a dedicated flag for every
+/// option. Each override is applied to the raw params struct after the typed
+/// fields, so it wins over them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParamOverrides {
+    values: Vec<ParamOverride>,
+}
+
+impl ParamOverrides {
+    /// Parse `key=value` strings.
+    ///
+    /// Unknown keys are logged and skipped so that a config written for a
+    /// newer version still starts; malformed entries and bad values fail.
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self> {
+        let mut values = Vec::new();
+        for entry in entries {
+            let entry = entry.as_ref();
+            let (key, value) = entry.split_once('=').ok_or_else(|| {
+                LlamaError::InvalidParam(format!("expected key=value, got '{}'", entry))
+            })?;
+            let key = key.trim();
+            match ParamOverride::parse(key, value)? {
+                Some(value) => values.push(value),
+                None => tracing::warn!(
+                    "Ignoring unknown llama.cpp parameter '{}' (supported: {})",
+                    key,
+                    ParamOverride::KEYS.join(", ")
+                ),
+            }
+        }
+        Ok(Self { values })
+    }
+
+    /// Number of overrides that will be applied.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub(crate) fn apply_to_context(&self, params: &mut llama_context_params) {
+        for value in &self.values {
+            match *value {
+                ParamOverride::RopeFreqBase(v) => params.rope_freq_base = v,
+                ParamOverride::RopeFreqScale(v) => params.rope_freq_scale = v,
+                ParamOverride::YarnExtFactor(v) => params.yarn_ext_factor = v,
+                ParamOverride::YarnAttnFactor(v) => params.yarn_attn_factor = v,
+                ParamOverride::YarnBetaFast(v) => params.yarn_beta_fast = v,
+                ParamOverride::YarnBetaSlow(v) => params.yarn_beta_slow = v,
+                ParamOverride::YarnOrigCtx(v) => params.yarn_orig_ctx = v,
+                ParamOverride::NUbatch(v) => params.n_ubatch = v,
+                ParamOverride::OffloadKqv(v) => params.offload_kqv = v,
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn apply_to_model(&self, params: &mut llama_model_params) {
+        for value in &self.values {
+            match *value {
+                ParamOverride::NGpuLayers(v) => params.n_gpu_layers = v,
+                ParamOverride::MainGpu(v) => params.main_gpu = v,
+                ParamOverride::CheckTensors(v) => params.check_tensors = v,
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,28 +1,59 @@
 //! Token sampling utilities.
 
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
 use super::bindings::llama_token;
 
+/// Temperatures below this sample greedily: the distribution is already
+/// all but one-hot, and dividing by them risks overflow.
+const MIN_TEMPERATURE: f32 = 1e-4;
+
 /// Token sampler for selecting the next token from logits.
+#[derive(Debug, Clone)]
 pub struct Sampler {
     /// Temperature for sampling (0 = greedy, higher = more random).
     pub temperature: f32,
+    /// Sample only among the `top_k` most likely tokens (0 = no limit).
+    pub top_k: usize,
+    /// Sample only among the most likely tokens whose probabilities add up
+    /// to `top_p` (1.0 = no limit).
+    pub top_p: f32,
+    /// Candidate buffer reused across tokens, so sampling from a large
+    /// vocabulary doesn't allocate for every token.
+    scratch: RefCell<Vec<(usize, f32)>>,
 }
 
 impl Default for Sampler {
     fn default() -> Self {
-        Self { temperature: 0.0 } // Greedy by default for deterministic output
+        Self::greedy() // Greedy by default for deterministic output
     }
 }
 
 impl Sampler {
     /// Create a greedy sampler (always picks the highest probability token).
     pub fn greedy() -> Self {
-        Self { temperature: 0.0 }
+        Self::with_temperature(0.0)
     }
 
     /// Create a sampler with the given temperature.
     pub fn with_temperature(temperature: f32) -> Self {
-        Self { temperature }
+        Self::new(temperature, 0, 1.0)
+    }
+
+    /// Create a sampler with the given temperature, top-k and top-p.
+    pub fn new(temperature: f32, top_k: usize, top_p: f32) -> Self {
+        Self {
+            temperature,
+            top_k,
+            top_p,
+            scratch: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Whether sampling always picks the most likely token.
+    pub fn is_greedy(&self) -> bool {
+        self.temperature < MIN_TEMPERATURE
     }
 
     /// Sample the next token from logits.
@@ -35,7 +66,7 @@ impl Sampler {
     ///
     /// The selected token ID.
     pub fn sample(&self, logits: &[f32]) -> llama_token {
-        if self.temperature <= 0.0 {
+        if self.is_greedy() {
             // Greedy: pick the token with highest logit
             self.sample_greedy(logits)
         } else {
@@ -44,38 +75,98 @@ impl Sampler {
         }
     }
 
+    /// Bias logits toward ending generation as output grows.
+    ///
+    /// Raises the EOS logit by `penalty` for every token generated so far,
+    /// which is equivalent to lowering all other tokens relative to it.
+    pub fn apply_length_penalty(
+        logits: &mut [f32],
+        eos: llama_token,
+        generated: usize,
+        penalty: f32,
+    ) {
+        if penalty <= 0.0 {
+            return;
+        }
+        if let Some(logit) = logits.get_mut(eos as usize) {
+            *logit += penalty * generated as f32;
+        }
+    }
+
     /// Greedy sampling: pick the token with highest logit.
+    ///
+    /// Exact ties, common with low-precision quantizations, go to the lowest
+    /// token id, as in llama.cpp. NaN logits are never picked.
     fn sample_greedy(&self, logits: &[f32]) -> llama_token {
-        logits
-            .iter()
-            .enumerate()
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .map(|(idx, _)| idx as llama_token)
-            .unwrap_or(0)
+        let mut best: Option<(usize, f32)> = None;
+        for (idx, &logit) in logits.iter().enumerate() {
+            if !logit.is_nan() && best.is_none_or(|(_, max)| logit > max) {
+                best = Some((idx, logit));
+            }
+        }
+        best.map(|(idx, _)| idx as llama_token).unwrap_or(0)
     }
 
-    /// Temperature sampling with softmax.
+    /// Temperature sampling with softmax, restricted by top-k and top-p.
     fn sample_temperature(&self, logits: &[f32]) -> llama_token {
-        // Apply temperature
-        let scaled: Vec<f32> = logits.iter().map(|x| x / self.temperature).collect();
-
-        // Softmax for probabilities
-        let max_logit = scaled.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
-        let exp_sum: f32 = scaled.iter().map(|x| (x - max_logit).exp()).sum();
-        let probs: Vec<f32> = scaled.iter().map(|x| (x - max_logit).exp() / exp_sum).collect();
+        let mut candidates = self.scratch.borrow_mut();
+        self.candidates(logits, &mut candidates);
 
         // Sample from distribution
-        let random: f32 = rand_simple();
+        let total: f32 = candidates.iter().map(|&(_, prob)| prob).sum();
+        let random: f32 = rand_simple() * total;
         let mut cumulative = 0.0;
-        for (idx, &prob) in probs.iter().enumerate() {
+        for &(idx, prob) in candidates.iter() {
             cumulative += prob;
             if random < cumulative {
                 return idx as llama_token;
             }
         }
 
-        // Fallback to last token
-        (probs.len() - 1) as llama_token
+        // Fallback to the least likely candidate
+        candidates.last().map_or(0, |&(idx, _)| idx as llama_token)
+    }
+
+    /// Fill `probs` with the tokens that may be sampled and their
+    /// probabilities, most likely first.
+    ///
+    /// With top-k set, only the k most likely tokens are sorted and turned
+    /// into probabilities; the rest of the vocabulary is just summed over.
+    fn candidates(&self, logits: &[f32], probs: &mut Vec<(usize, f32)>) {
+        // Softmax of the temperature-scaled logits. The max is subtracted
+        // before scaling, so every exponent is at most 0 and nothing
+        // overflows however large the logits or small the temperature.
+        let max_logit = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let weight = |x: f32| ((x - max_logit) / self.temperature).exp();
+        let exp_sum: f32 = logits.iter().map(|&x| weight(x)).sum();
+
+        // Most likely first; ties in index order
+        let by_likelihood = |(i, a): &(usize, f32), (j, b): &(usize, f32)| {
+            b.partial_cmp(a).unwrap_or(Ordering::Equal).then(i.cmp(j))
+        };
+        probs.clear();
+        probs.extend(logits.iter().copied().enumerate());
+        if self.top_k > 0 && self.top_k < probs.len() {
+            probs.select_nth_unstable_by(self.top_k - 1, by_likelihood);
+            probs.truncate(self.top_k);
+        }
+        probs.sort_unstable_by(by_likelihood);
+        for (_, p) in probs.iter_mut() {
+            *p = weight(*p) / exp_sum;
+        }
+
+        if self.top_p < 1.0 {
+            // Keep the smallest prefix reaching top_p, always at least one token
+            let mut cumulative = 0.0;
+            let keep = probs
+                .iter()
+                .position(|&(_, prob)| {
+                    cumulative += prob;
+                    cumulative >= self.top_p
+                })
+                .map_or(probs.len(), |pos| pos + 1);
+            probs.truncate(keep);
+        }
     }
 }
 
@@ -105,6 +196,12 @@ fn rand_simple() -> f32 {
 mod tests {
     use super::*;
 
+    fn candidates(sampler: &Sampler, logits: &[f32]) -> Vec<(usize, f32)> {
+        let mut probs = Vec::new();
+        sampler.candidates(logits, &mut probs);
+        probs
+    }
+
     #[test]
     fn test_greedy_sampling() {
         let sampler = Sampler::greedy();
@@ -113,6 +210,40 @@ mod tests {
         assert_eq!(token, 1); // Index of highest value (5.0)
     }
 
+    #[test]
+    fn test_greedy_sampling_ties_pick_lowest_token() {
+        let sampler = Sampler::greedy();
+        assert_eq!(sampler.sample(&[1.0, 5.0, 2.0, 5.0]), 1);
+        assert_eq!(sampler.sample(&[f32::NAN, 3.0, 3.0]), 1);
+    }
+
+    #[test]
+    fn test_length_penalty_raises_eos_with_length() {
+        let logits = vec![2.0, 5.0, 1.0, 0.0];
+        let eos = 3;
+        let effective = |generated| {
+            let mut adjusted = logits.clone();
+            Sampler::apply_length_penalty(&mut adjusted, eos, generated, 0.5);
+            adjusted[eos as usize]
+        };
+
+        assert_eq!(effective(0), 0.0);
+        assert!(effective(4) > effective(2));
+        assert!(effective(12) > effective(4));
+
+        // Eventually EOS wins under greedy sampling
+        let mut adjusted = logits.clone();
+        Sampler::apply_length_penalty(&mut adjusted, eos, 12, 0.5);
+        assert_eq!(Sampler::greedy().sample(&adjusted), eos);
+    }
+
+    #[test]
+    fn test_length_penalty_zero_is_noop() {
+        let mut logits = vec![2.0, 5.0, 1.0, 0.0];
+        Sampler::apply_length_penalty(&mut logits, 3, 100, 0.0);
+        assert_eq!(logits, vec![2.0, 5.0, 1.0, 0.0]);
+    }
+
     #[test]
     fn test_greedy_sampling_negative_logits() {
         let sampler = Sampler::greedy();
@@ -121,6 +252,94 @@ mod tests {
         assert_eq!(token, 2); // Index of highest value (-0.5)
     }
 
+    #[test]
+    fn test_top_k_and_top_p_limit_candidates() {
+        let logits = vec![1.0, 4.0, 3.0, 2.0];
+        let tokens = |sampler: Sampler| -> Vec<usize> {
+            candidates(&sampler, &logits)
+                .iter()
+                .map(|&(idx, _)| idx)
+                .collect()
+        };
+
+        let sampler = Sampler::with_temperature(1.0);
+        assert_eq!(tokens(sampler.clone()), vec![1, 2, 3, 0]);
+        assert_eq!(
+            tokens(Sampler {
+                top_k: 2,
+                ..sampler.clone()
+            }),
+            vec![1, 2]
+        );
+        // Token 1 alone has about 64% of the probability mass
+        assert_eq!(
+            tokens(Sampler {
+                top_p: 0.5,
+                ..sampler.clone()
+            }),
+            vec![1]
+        );
+        assert_eq!(
+            tokens(Sampler {
+                top_p: 0.8,
+                ..sampler
+            }),
+            vec![1, 2]
+        );
+
+        let sampler = Sampler {
+            top_k: 1,
+            ..Sampler::with_temperature(1.0)
+        };
+        assert_eq!(sampler.sample(&logits), 1);
+    }
+
+    #[test]
+    fn test_top_k_matches_full_sort_without_reallocating() {
+        // A vocabulary-sized spread of logits with some ties
+        let logits: Vec<f32> = (0..5000)
+            .map(|i| ((i * 7919) % 1000) as f32 / 100.0)
+            .collect();
+        let full = candidates(&Sampler::with_temperature(0.8), &logits);
+
+        let sampler = Sampler {
+            top_k: 40,
+            ..Sampler::with_temperature(0.8)
+        };
+        assert_eq!(candidates(&sampler, &logits), full[..40]);
+
+        // After the first token, the candidate buffer is reused as is
+        sampler.sample(&logits);
+        let buffer = sampler.scratch.borrow().as_ptr();
+        for _ in 0..100 {
+            sampler.sample(&logits);
+        }
+        assert_eq!(sampler.scratch.borrow().as_ptr(), buffer);
+    }
+
+    #[test]
+    fn test_softmax_handles_extreme_logits() {
+        let logits = vec![1e30, -1e30, 0.0, 1e30];
+        for temperature in [1e-3, 1.0, 100.0] {
+            let probs = candidates(&Sampler::with_temperature(temperature), &logits);
+            assert!(probs.iter().all(|&(_, p)| p.is_finite()));
+            let total: f32 = probs.iter().map(|&(_, p)| p).sum();
+            assert!((total - 1.0).abs() < 1e-5);
+            // The two maximal tokens share the mass
+            assert!((probs[0].1 - 0.5).abs() < 1e-5);
+            assert!((probs[1].1 - 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_near_zero_temperature_is_greedy() {
+        let logits = vec![1.0, 1e30, 3.0];
+        let sampler = Sampler::with_temperature(1e-9);
+        for _ in 0..10 {
+            assert_eq!(sampler.sample(&logits), 1);
+        }
+    }
+
     #[test]
     fn test_temperature_sampling_exists() {
         let sampler = Sampler::with_temperature(1.0);
@@ -0,0 +1,179 @@
+//! Logit processors applied before sampling.
+//!
+//! Each adjustment to the model's logits (token bias, repetition penalty,
+//! length penalty) is a `LogitProcessor`. The generation loop runs them in
+//! order on a copy of the logits and hands the result to the `Sampler`.
+
+use std::cell::RefCell;
+
+use crate::generation::GenerationConfig;
+use crate::llama::Sampler;
+
+/// Tokens looked back over by the repetition penalty.
+pub const REPETITION_WINDOW: usize = 64;
+
+/// Adjusts logits for the next token.
+pub trait LogitProcessor {
+    /// Adjust `logits` given the tokens generated so far.
+    fn process(&self, logits: &mut [f32], generated: &[i32]);
+}
+
+/// Adds a fixed bias to chosen tokens' logits, e.g. a large negative one
+/// to ban a token.
+pub struct LogitBias {
+    biases: Vec<(i32, f32)>,
+}
+
+impl LogitBias {
+    pub fn new(biases: Vec<(i32, f32)>) -> Self {
+        Self { biases }
+    }
+}
+
+impl LogitProcessor for LogitBias {
+    fn process(&self, logits: &mut [f32], _generated: &[i32]) {
+        for &(token, bias) in &self.biases {
+            // Tokens outside the vocabulary are ignored
+            if let Some(logit) = usize::try_from(token).ok().and_then(|t| logits.get_mut(t)) {
+                *logit += bias;
+            }
+        }
+    }
+}
+
+/// Makes tokens generated within the last `REPETITION_WINDOW` tokens less
+/// likely, as llama.cpp does: positive logits are divided by the penalty
+/// and negative ones multiplied by it.
+pub struct RepetitionPenalty {
+    penalty: f32,
+    /// Reused for the deduplicated window so each token doesn't allocate
+    scratch: RefCell<Vec<i32>>,
+}
+
+impl RepetitionPenalty {
+    pub fn new(penalty: f32) -> Self {
+        Self {
+            penalty,
+            scratch: RefCell::new(Vec::with_capacity(REPETITION_WINDOW)),
+        }
+    }
+}
+
+impl LogitProcessor for RepetitionPenalty {
+    fn process(&self, logits: &mut [f32], generated: &[i32]) {
+        let window = &generated[generated.len().saturating_sub(REPETITION_WINDOW)..];
+        let mut seen = self.scratch.borrow_mut();
+        seen.clear();
+        seen.extend_from_slice(window);
+        seen.sort_unstable();
+        seen.dedup();
+        for &token in seen.iter() {
+            if let Some(logit) = usize::try_from(token).ok().and_then(|t| logits.get_mut(t)) {
+                if *logit > 0.0 {
+                    *logit /= self.penalty;
+                } else {
+                    *logit *= self.penalty;
+                }
+            }
+        }
+    }
+}
+
+/// Raises the end-of-sequence logit as the output grows.
+pub struct LengthPenalty {
+    eos: i32,
+    penalty: f32,
+}
+
+impl LengthPenalty {
+    pub fn new(eos: i32, penalty: f32) -> Self {
+        Self { eos, penalty }
+    }
+}
+
+impl LogitProcessor for LengthPenalty {
+    fn process(&self, logits: &mut [f32], generated: &[i32]) {
+        Sampler::apply_length_penalty(logits, self.eos, generated.len(), self.penalty);
+    }
+}
+
+/// The processors `config` enables, in the order they apply: bias, then
+/// repetition, then length.
+pub fn pipeline(config: &GenerationConfig, eos: i32) -> Vec<Box<dyn LogitProcessor>> {
+    let mut processors: Vec<Box<dyn LogitProcessor>> = Vec::new();
+    if !config.logit_bias.is_empty() {
+        processors.push(Box::new(LogitBias::new(config.logit_bias.clone())));
+    }
+    if config.repetition_penalty > 1.0 {
+        processors.push(Box::new(RepetitionPenalty::new(config.repetition_penalty)));
+    }
+    if config.length_penalty > 0.0 {
+        processors.push(Box::new(LengthPenalty::new(eos, config.length_penalty)));
+    }
+    processors
+}
+
+/// Run `processors` over `logits` in order.
+pub fn apply(processors: &[Box<dyn LogitProcessor>], logits: &mut [f32], generated: &[i32]) {
+    for processor in processors {
+        processor.process(logits, generated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bias_then_repetition_apply_in_order() {
+        let processors: Vec<Box<dyn LogitProcessor>> = vec![
+            Box::new(LogitBias::new(vec![(1, 2.0), (2, -5.0), (99, 1.0)])),
+            Box::new(RepetitionPenalty::new(2.0)),
+        ];
+        let mut logits = vec![1.0, 2.0, 3.0, 4.0];
+        apply(&processors, &mut logits, &[1, 2, 1]);
+
+        // Token 1: 2 + 2 = 4, then halved. Token 2: 3 - 5 = -2, then doubled
+        // (it went negative before the penalty saw it).
+        assert_eq!(logits, vec![1.0, 2.0, -4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_repetition_penalty_only_looks_at_recent_tokens() {
+        let mut generated = vec![0];
+        generated.extend(std::iter::repeat_n(1, REPETITION_WINDOW));
+        let mut logits = vec![4.0, 4.0];
+        RepetitionPenalty::new(2.0).process(&mut logits, &generated);
+        assert_eq!(logits, vec![4.0, 2.0]);
+    }
+
+    #[test]
+    fn test_repetition_penalty_reuses_its_buffer() {
+        let penalty = RepetitionPenalty::new(2.0);
+        let mut logits = vec![4.0; 4];
+        penalty.process(&mut logits, &[1, 2, 1]);
+        let buffer = penalty.scratch.borrow().as_ptr();
+        penalty.process(&mut logits, &[3, 3, 2]);
+        assert_eq!(penalty.scratch.borrow().as_ptr(), buffer);
+        assert_eq!(logits, vec![4.0, 2.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_pipeline_skips_disabled_processors() {
+        let config = GenerationConfig {
+            max_tokens: 10,
+            timeout: std::time::Duration::from_secs(60),
+            length_penalty: 0.0,
+            logit_bias: Vec::new(),
+            repetition_penalty: 1.0,
+        };
+        assert!(pipeline(&config, 2).is_empty());
+
+        let config = GenerationConfig {
+            length_penalty: 1.0,
+            repetition_penalty: 1.5,
+            ..config
+        };
+        assert_eq!(pipeline(&config, 2).len(), 2);
+    }
+}
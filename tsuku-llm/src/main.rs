@@ -3,27 +3,55 @@
 //! This binary provides local inference capabilities via gRPC over Unix domain sockets.
 //! It bundles llama.cpp and handles hardware detection, model management, and inference.
 
+mod affinity;
+mod generation;
+mod gguf;
 mod hardware;
+mod listener;
 mod llama;
+mod logits;
+mod memory;
+mod metrics;
 mod model;
 mod models;
+mod pool;
+mod reload;
+mod sampling;
+mod schema;
+mod startup;
+mod structured;
+mod template;
+mod trace;
+mod verify;
 
 use std::fs::File;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
 use std::os::unix::io::AsRawFd;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
-use tokio::net::UnixListener;
+use clap::{Args, Parser, Subcommand};
+use tokio::net::{TcpListener, UnixListener};
 use tokio::sync::{mpsc, Mutex};
-use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info, warn};
 
-use llama::{ContextParams, LlamaContext, LlamaModel, ModelParams, Sampler};
+use generation::{Generation, GenerationConfig, InferenceContext, StopCause, StreamingDetokenizer};
+use llama::{
+    ContextParams, KvCacheType, LlamaContext, LlamaModel, ModelParams, ParamOverrides, Pooling,
+    Sampler,
+};
+use metrics::Metrics;
+use pool::{ContextPool, PooledContext};
+use reload::{ModelSlot, ReloadError};
+use sampling::SamplingPreset;
+use template::{ChatTemplate, Prompt};
+use trace::Trace;
 
 // Generated from proto/llm.proto
 pub mod proto {
@@ -32,13 +60,36 @@ pub mod proto {
 
 use proto::inference_service_server::{InferenceService, InferenceServiceServer};
 use proto::{
-    CompletionRequest, CompletionResponse, ShutdownRequest, ShutdownResponse, StatusRequest,
-    StatusResponse, Usage,
+    CancelStartupRequest, CancelStartupResponse, CompletionRequest, CompletionResponse,
+    CountTokensRequest, CountTokensResponse, DetokenizeRequest, DetokenizeResponse, EmbedRequest,
+    EmbedResponse, MetricsRequest, MetricsResponse, PingRequest, PingResponse, ReloadModelRequest,
+    ReloadModelResponse, ShutdownRequest, ShutdownResponse, StatusRequest, StatusResponse,
+    TokenizeRequest, TokenizeResponse, Usage,
 };
 
 /// Grace period for in-flight requests during shutdown.
 const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
 
+/// Generation timeout: 5 minutes max per turn.
+/// CPU inference is slow (~18 tokens/sec) and tool call JSON can be very verbose,
+/// especially for the extract_pattern tool which includes platform mappings.
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Default output budget: enough for extract_pattern JSON with platform mappings.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+
+/// Default cap on messages per completion request.
+const DEFAULT_MAX_MESSAGES: usize = 256;
+
+/// Default cap on tools per completion request.
+const DEFAULT_MAX_TOOLS: usize = 64;
+
+/// Default cap on completions (`n`) per completion request.
+const DEFAULT_MAX_SAMPLES: usize = 16;
+
+/// Context size of the embeddings context; longer inputs are rejected.
+const EMBEDDING_CTX: u32 = 2048;
+
 #[derive(Parser)]
 #[command(name = "tsuku-llm")]
 #[command(about = "Local LLM inference server for tsuku")]
@@ -50,14 +101,355 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Start the inference server
-    Serve {
-        /// Idle timeout before automatic shutdown (e.g., "5m", "300s")
-        #[arg(long, default_value = "5m", value_parser = parse_duration)]
-        idle_timeout: Duration,
+    Serve(Box<ServeArgs>),
+
+    /// Verify a downloaded model's checksum, and optionally that it loads
+    Verify(VerifyArgs),
+
+    /// Work with model manifest entries
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestCommand,
+    },
+
+    /// List the models in the manifest and whether they're downloaded
+    ListModels(ListModelsArgs),
+
+    /// Download a model ahead of time, so the server starts without waiting
+    Download {
+        /// Model name from the manifest (default: the model `serve` would select)
+        model: Option<String>,
+    },
+
+    /// Delete downloaded models other than the selected one
+    Prune(PruneArgs),
+}
+
+/// Options for the `verify` command.
+#[derive(Args, Debug, Clone)]
+struct VerifyArgs {
+    /// Model name from the manifest, or a path to a local GGUF file
+    model: String,
+
+    /// Also load the model and decode a token, checking the logits are finite
+    #[arg(long)]
+    deep_verify: bool,
+}
+
+/// Options for the `list-models` command.
+#[derive(Args, Debug, Clone)]
+struct ListModelsArgs {
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+/// Options for the `prune` command.
+#[derive(Args, Debug, Clone)]
+struct PruneArgs {
+    /// Also keep this model's files (repeatable). The model `serve` would
+    /// select is always kept.
+    #[arg(long, value_name = "MODEL")]
+    keep: Vec<String>,
+
+    /// List the files that would be deleted without deleting them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand)]
+enum ManifestCommand {
+    /// Print a manifest entry for a local GGUF file
+    Add {
+        /// Path to the GGUF file (the first part for split models)
+        path: PathBuf,
     },
 }
 
-/// Parse a duration string (e.g., "5m", "300s", "1h30m").
+/// Options for the `serve` command.
+#[derive(Args, Debug, Clone)]
+struct ServeArgs {
+    /// Idle timeout before automatic shutdown (e.g., "5m", "300s")
+    #[arg(long, default_value = "5m", value_parser = parse_duration)]
+    idle_timeout: Duration,
+
+    /// Hard ceiling on model plus KV cache memory, in MB. The context is
+    /// shrunk to fit, and startup fails if even a minimal context won't fit.
+    #[arg(long, env = "TSUKU_LLM_MAX_MEMORY_MB")]
+    max_memory_mb: Option<u64>,
+
+    /// Context window in tokens (default: the model's trained context,
+    /// capped to fit a 16 GB GPU). Clamped to the trained context unless
+    /// --yarn is set.
+    #[arg(long, env = "TSUKU_LLM_CTX_SIZE")]
+    ctx_size: Option<u32>,
+
+    /// Model layers to offload to the GPU (default: all). Lower it to fit a
+    /// model in limited VRAM; 0 runs entirely on the CPU.
+    #[arg(
+        long,
+        value_name = "N",
+        env = "TSUKU_LLM_GPU_LAYERS",
+        value_parser = clap::value_parser!(i32).range(0..)
+    )]
+    gpu_layers: Option<i32>,
+
+    /// CPU threads for inference (default: chosen by llama.cpp)
+    #[arg(
+        long,
+        value_name = "N",
+        env = "TSUKU_LLM_THREADS",
+        value_parser = clap::value_parser!(i32).range(1..)
+    )]
+    threads: Option<i32>,
+
+    /// Whether GetStatus counts as activity and resets the idle timeout.
+    /// Monitors should use Ping, which never does.
+    #[arg(
+        long,
+        value_name = "BOOL",
+        env = "TSUKU_LLM_STATUS_RESETS_IDLE",
+        default_value_t = true,
+        action = clap::ArgAction::Set
+    )]
+    status_resets_idle: bool,
+
+    /// Write a JSONL trace of each generation step of the next completion
+    /// to PATH: the chosen token, its log-probability and the top
+    /// alternatives. Only one request is traced.
+    #[arg(long, value_name = "PATH", env = "TSUKU_LLM_TRACE_NEXT")]
+    trace_next: Option<PathBuf>,
+
+    /// Use YaRN RoPE scaling to run a --ctx-size beyond the trained context
+    #[arg(long, env = "TSUKU_LLM_YARN")]
+    yarn: bool,
+
+    /// Force flash attention on (default: llama.cpp enables it where the
+    /// GPU supports it)
+    #[arg(long, env = "TSUKU_LLM_FLASH_ATTN")]
+    flash_attn: bool,
+
+    /// KV cache element type. q8_0 and q4_0 shrink the cache, fitting a
+    /// longer context in the same memory, and need --flash-attn.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = KvCacheType::F16,
+        env = "TSUKU_LLM_KV_CACHE_TYPE",
+        requires_ifs = [("q8_0", "flash_attn"), ("q4_0", "flash_attn")]
+    )]
+    kv_cache_type: KvCacheType,
+
+    /// Number of inference contexts, i.e. completions that can run at once
+    /// (default: as many as fit in memory next to the model, up to 4).
+    /// Never more than fit in `--max-memory-mb`.
+    #[arg(long, value_name = "N", env = "TSUKU_LLM_CONTEXTS")]
+    contexts: Option<usize>,
+
+    /// Tokens decoded at startup so GPU kernels are compiled before the first
+    /// request. A realistic prompt length (e.g. 512) also warms the batched
+    /// prompt path; 0 disables warmup.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        env = "TSUKU_LLM_WARMUP_TOKENS"
+    )]
+    warmup_tokens: usize,
+
+    /// Skip the startup warmup decode (same as --warmup-tokens 0)
+    #[arg(long, env = "TSUKU_LLM_NO_WARMUP", conflicts_with = "warmup_tokens")]
+    no_warmup: bool,
+
+    /// Reject completion requests with more messages than this
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_MESSAGES,
+        env = "TSUKU_LLM_MAX_MESSAGES"
+    )]
+    max_messages: usize,
+
+    /// Reject completion requests with more tools than this
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_TOOLS,
+        env = "TSUKU_LLM_MAX_TOOLS"
+    )]
+    max_tools: usize,
+
+    /// Reject completion requests for more completions (`n`) than this
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_SAMPLES,
+        env = "TSUKU_LLM_MAX_SAMPLES"
+    )]
+    max_samples: usize,
+
+    /// Also serve gRPC over TCP on this address (e.g., "127.0.0.1:50051"),
+    /// alongside the Unix socket, for clients in another container or VM.
+    /// A non-loopback address needs `--tcp-token`.
+    #[arg(
+        long,
+        alias = "listen",
+        value_name = "ADDR",
+        env = "TSUKU_LLM_TCP_ADDR"
+    )]
+    tcp_addr: Option<SocketAddr>,
+
+    /// Listen on this Linux abstract socket name instead of the socket file.
+    /// There's no file to clean up, but also no file permissions: any process
+    /// in the same network namespace can connect. Linux only.
+    #[arg(long, value_name = "NAME", env = "TSUKU_LLM_ABSTRACT_SOCKET")]
+    abstract_socket: Option<String>,
+
+    /// Require `authorization: Bearer <TOKEN>` on calls over TCP. Calls on
+    /// the Unix socket don't need it.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "TSUKU_LLM_TCP_TOKEN",
+        hide_env_values = true
+    )]
+    tcp_token: Option<String>,
+
+    /// Sampling preset for requests that don't name one
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SamplingPreset::Precise,
+        env = "TSUKU_LLM_SAMPLING_PRESET"
+    )]
+    sampling_preset: SamplingPreset,
+
+    /// Small GGUF model to run instead when the selected model isn't present
+    /// and can't be downloaded (e.g. offline). The server reports itself as
+    /// degraded while running it.
+    #[arg(long, value_name = "PATH", env = "TSUKU_FALLBACK_MODEL")]
+    fallback_model: Option<PathBuf>,
+
+    /// Pin inference threads to these CPU cores (e.g., "0-3" or "0,2,4"). Linux only.
+    #[arg(long, value_name = "CPUS", env = "TSUKU_LLM_CPU_AFFINITY")]
+    cpu_affinity: Option<String>,
+
+    /// Raw llama.cpp parameter override as key=value (e.g., rope_freq_base=1e6).
+    /// Repeatable; unknown keys are ignored with a warning.
+    #[arg(
+        long = "llama-param",
+        value_name = "KEY=VALUE",
+        env = "TSUKU_LLM_LLAMA_PARAMS",
+        value_delimiter = ','
+    )]
+    llama_params: Vec<String>,
+}
+
+impl ServeArgs {
+    /// Refuse to serve the model over TCP to other hosts without a token.
+    fn check_tcp_auth(&self) -> Result<()> {
+        if let Some(addr) = self.tcp_addr {
+            if self.tcp_token.is_none() && !addr.ip().is_loopback() {
+                bail!(
+                    "TCP address {} is reachable from other hosts; set --tcp-token to \
+                     require a token",
+                    addr
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Parameters for loading the model.
+    fn model_params(&self, overrides: ParamOverrides) -> ModelParams {
+        let defaults = ModelParams::for_gpu();
+        ModelParams {
+            n_gpu_layers: self.gpu_layers.unwrap_or(defaults.n_gpu_layers),
+            overrides,
+            ..defaults
+        }
+    }
+
+    /// Parameters for an inference context of `n_ctx` tokens.
+    fn context_params(&self, n_ctx: u32, overrides: ParamOverrides) -> ContextParams {
+        let mut params = ContextParams {
+            n_ctx,
+            n_batch: n_ctx,
+            flash_attn: self.flash_attn,
+            kv_cache_type: self.kv_cache_type,
+            overrides,
+            ..Default::default()
+        };
+        if let Some(threads) = self.threads {
+            params.n_threads = threads;
+            params.n_threads_batch = threads;
+        }
+        params
+    }
+
+    /// Parameters for the inference contexts of `model`: the requested
+    /// context size, shrunk to fit --max-memory-mb, with YaRN scaling when
+    /// it extends the trained context.
+    fn fitted_context_params(
+        &self,
+        model: &LlamaModel,
+        overrides: ParamOverrides,
+    ) -> Result<ContextParams, memory::BudgetError> {
+        let n_ctx_train = model.n_ctx_train();
+        let mut n_ctx = requested_context_size(self.ctx_size, n_ctx_train, self.yarn);
+
+        // Shrink the context to fit the operator's memory ceiling, if configured.
+        if let Some(budget_mb) = self.max_memory_mb {
+            let kv = memory::KvCacheShape::from_model(model, self.kv_cache_type);
+            let fitted = memory::fit_context(n_ctx, model.size_bytes(), kv, budget_mb)?;
+            if fitted < n_ctx {
+                warn!(
+                    "Reducing context from {} to {} tokens to fit {} MB memory budget",
+                    n_ctx, fitted, budget_mb
+                );
+            }
+            n_ctx = fitted;
+        }
+
+        let mut params = self.context_params(n_ctx, overrides);
+        if self.yarn && n_ctx > n_ctx_train {
+            info!(
+                "Extending context from {} to {} tokens with YaRN scaling",
+                n_ctx_train, n_ctx
+            );
+            params = params.with_yarn_scaling(n_ctx_train);
+        }
+        Ok(params)
+    }
+
+    /// Tokens to decode at startup in a context of `n_ctx` tokens.
+    fn warmup_tokens(&self, n_ctx: u32) -> usize {
+        if self.no_warmup {
+            return 0;
+        }
+        self.warmup_tokens.min(n_ctx as usize)
+    }
+}
+
+impl Default for ServeArgs {
+    /// The options used when no subcommand is given, i.e. `serve` with no flags.
+    fn default() -> Self {
+        use clap::FromArgMatches;
+        let cmd = ServeArgs::augment_args(clap::Command::new("serve"));
+        ServeArgs::from_arg_matches(&cmd.get_matches_from(["serve"]))
+            .expect("serve defaults must parse")
+    }
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Longest duration `parse_duration` accepts: 100 years of days. Anything
+/// longer can't be added to an `Instant` on every platform.
+const MAX_DURATION: Duration = Duration::from_secs(36_500 * 86_400);
+
+/// Parse a duration string (e.g., "5m", "300s", "1h30m", "1d", "500ms").
+///
+/// Durations longer than `MAX_DURATION` are rejected.
 fn parse_duration(s: &str) -> Result<Duration, String> {
     // Try parsing as Go-style duration
     let s = s.trim();
@@ -65,7 +457,7 @@ fn parse_duration(s: &str) -> Result<Duration, String> {
         return Err("empty duration".to_string());
     }
 
-    let mut total_secs: u64 = 0;
+    let mut total_nanos: u128 = 0;
     let mut num_start: Option<usize> = None;
     let chars: Vec<char> = s.chars().collect();
     let mut i = 0;
@@ -95,24 +487,19 @@ fn parse_duration(s: &str) -> Result<Duration, String> {
             }
             let unit = &s[unit_start..i];
 
-            let multiplier = match unit {
-                "ns" => continue,        // Nanoseconds too small, skip
-                "us" | "µs" => continue, // Microseconds too small, skip
-                "ms" => {
-                    // Milliseconds: only add if >= 1000
-                    if num >= 1000 {
-                        total_secs += num / 1000;
-                    }
-                    num_start = None;
-                    continue;
-                }
-                "s" => 1,
-                "m" => 60,
-                "h" => 3600,
+            let unit_nanos = match unit {
+                "ns" => 1,
+                "us" | "µs" => 1_000,
+                "ms" => 1_000_000,
+                "s" => NANOS_PER_SEC,
+                "m" => 60 * NANOS_PER_SEC,
+                "h" => 3600 * NANOS_PER_SEC,
+                "d" => 86_400 * NANOS_PER_SEC,
+                "w" => 604_800 * NANOS_PER_SEC,
                 _ => return Err(format!("unknown unit: {}", unit)),
             };
 
-            total_secs += num * multiplier;
+            total_nanos = total_nanos.saturating_add(num as u128 * unit_nanos);
             num_start = None;
             continue;
         }
@@ -125,44 +512,220 @@ fn parse_duration(s: &str) -> Result<Duration, String> {
         let num: u64 = s[start..]
             .parse()
             .map_err(|_| format!("invalid number: {}", &s[start..]))?;
-        total_secs += num;
+        total_nanos = total_nanos.saturating_add(num as u128 * NANOS_PER_SEC);
     }
 
-    if total_secs == 0 {
+    if total_nanos == 0 {
         return Err("duration must be positive".to_string());
     }
 
-    Ok(Duration::from_secs(total_secs))
+    if total_nanos > MAX_DURATION.as_nanos() {
+        return Err(format!(
+            "duration is too large (at most {}d)",
+            MAX_DURATION.as_secs() / 86_400
+        ));
+    }
+    let secs = (total_nanos / NANOS_PER_SEC) as u64;
+    Ok(Duration::new(secs, (total_nanos % NANOS_PER_SEC) as u32))
+}
+
+/// The served model and everything created from it, replaced as a whole
+/// by ReloadModel.
+struct LoadedModel {
+    /// Model name.
+    name: String,
+
+    /// Loaded model, shared by all contexts.
+    model: Arc<LlamaModel>,
+
+    /// Inference contexts; each request checks one out for its duration.
+    /// Shared with blocking tasks that drive streaming completions.
+    contexts: Arc<ContextPool<LlamaContext>>,
+
+    /// Size of each inference context, in tokens.
+    n_ctx: u32,
+
+    /// Prompt format of the model.
+    chat_template: ChatTemplate,
+
+    /// Embeddings context, created on the first Embed request so servers
+    /// that never embed don't pay for a second KV cache.
+    embedding_context: Mutex<Option<LlamaContext>>,
+
+    /// Running the fallback model because the selected one couldn't be
+    /// downloaded.
+    degraded: bool,
+
+    /// Size of the model's files on disk, all split parts included.
+    size_bytes: u64,
+
+    /// Quantization of the model (e.g. "q4_k_m"); empty if unknown.
+    quantization: String,
+
+    /// How long loading the model file took.
+    load_time: Duration,
+}
+
+impl LoadedModel {
+    fn new(
+        name: String,
+        model: Arc<LlamaModel>,
+        contexts: Vec<LlamaContext>,
+        chat_template: ChatTemplate,
+    ) -> Self {
+        Self {
+            name,
+            model,
+            n_ctx: contexts.first().map_or(0, |ctx| ctx.n_ctx()),
+            contexts: Arc::new(ContextPool::new(contexts)),
+            chat_template,
+            embedding_context: Mutex::new(None),
+            degraded: false,
+            size_bytes: 0,
+            quantization: String::new(),
+            load_time: Duration::ZERO,
+        }
+    }
+}
+
+/// Loads manifest models for ReloadModel the way the server loaded its
+/// first one.
+struct Reloader {
+    serve_args: ServeArgs,
+    param_overrides: ParamOverrides,
+    selector: model::ModelSelector,
+    model_manager: models::ModelManager,
+    /// Sizes the memory budget for each model's contexts.
+    hardware_profile: hardware::HardwareProfile,
+}
+
+impl Reloader {
+    /// Load a downloaded manifest model and create its contexts. Blocks
+    /// until the model is loaded.
+    fn load(&self, name: &str) -> Result<LoadedModel> {
+        let path = self.model_manager.model_path(name);
+        info!("Loading model from {:?}", path);
+        let model_params = self.serve_args.model_params(self.param_overrides.clone());
+        let load_start = std::time::Instant::now();
+        let model = LlamaModel::load_from_file(&path, model_params)
+            .map(Arc::new)
+            .with_context(|| format!("Failed to load {}", name))?;
+        let load_time = load_start.elapsed();
+        info!("Model loaded successfully in {:?}", load_time);
+
+        let context_params = self
+            .serve_args
+            .fitted_context_params(&model, self.param_overrides.clone())?;
+        let mut context = LlamaContext::new(model.clone(), context_params.clone())
+            .context("Failed to create an inference context")?;
+        let warmup_tokens = self.serve_args.warmup_tokens(context_params.n_ctx);
+        if let Err(e) = warmup(&mut context, warmup_tokens) {
+            warn!("Warmup decode failed: {}", e);
+        }
+        let n_contexts = context_count(
+            &self.serve_args,
+            &self.hardware_profile,
+            &model,
+            context_params.n_ctx,
+        );
+        let contexts = create_contexts(context, &model, &context_params, n_contexts);
+
+        let entry = self.selector.manifest().get(name);
+        let mut loaded = LoadedModel::new(
+            name.to_string(),
+            model,
+            contexts,
+            chat_template_for(entry, &path),
+        );
+        loaded.size_bytes = self.model_manager.size_on_disk(name);
+        loaded.quantization = entry.map(|e| e.quantization.clone()).unwrap_or_default();
+        loaded.load_time = load_time;
+        Ok(loaded)
+    }
+}
+
+/// What a request does, which decides whether it resets the idle timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+    /// Inference, reloads and other real work, which always does
+    Work,
+    /// GetStatus, which does unless `--status-resets-idle=false`
+    Status,
+    /// Ping, which never does: a monitor polling it mustn't keep an
+    /// otherwise idle server alive
+    Ping,
+}
+
+/// Signals to the idle timer that the server is in use.
+#[derive(Debug, Clone)]
+struct Activity {
+    tx: mpsc::Sender<()>,
+    /// Whether GetStatus resets the idle timeout like real work does.
+    status_resets_idle: bool,
+}
+
+impl Activity {
+    fn new(tx: mpsc::Sender<()>) -> Self {
+        Self {
+            tx,
+            status_resets_idle: true,
+        }
+    }
+
+    /// Reset the idle timeout if a request of this kind counts as activity.
+    fn record(&self, kind: RequestKind) {
+        let resets = match kind {
+            RequestKind::Work => true,
+            RequestKind::Status => self.status_resets_idle,
+            RequestKind::Ping => false,
+        };
+        if resets {
+            // Ignore if the channel is full; a pending signal already resets the timer
+            let _ = self.tx.try_send(());
+        }
+    }
 }
 
 /// Inference server implementation.
 struct LlmServer {
-    /// Loaded model name.
-    model_name: String,
-
     /// Hardware profile detected at startup.
     hardware_profile: hardware::HardwareProfile,
 
     /// Signal to initiate shutdown.
     shutdown_tx: mpsc::Sender<()>,
 
-    /// Signal that there's activity (resets idle timeout).
-    activity_tx: mpsc::Sender<()>,
+    /// Signals activity, which resets the idle timeout.
+    activity: Activity,
 
     /// Whether the server is shutting down.
     shutting_down: Arc<AtomicBool>,
 
+    /// Whether the server is paused for maintenance. Inference requests are
+    /// rejected, but the model stays loaded and the socket bound.
+    paused: Arc<AtomicBool>,
+
     /// Count of in-flight requests.
     in_flight: Arc<AtomicUsize>,
 
-    /// The loaded model (shared for creating new contexts if needed).
-    model: Arc<LlamaModel>,
+    /// Request metrics, shared with blocking tasks that drive streams.
+    metrics: Arc<Metrics>,
+
+    /// The served model. Requests keep the snapshot they started with, so
+    /// a reload doesn't affect them.
+    loaded: Arc<ModelSlot<LoadedModel>>,
 
-    /// Inference context (protected by mutex since it's not Sync).
-    context: Mutex<LlamaContext>,
+    /// Set when the server can load other models at runtime.
+    reloader: Option<Arc<Reloader>>,
 
-    /// Token sampler for inference.
-    sampler: Sampler,
+    /// Sampling preset for requests that don't name one.
+    sampling_preset: SamplingPreset,
+
+    /// Caps on completion request size.
+    limits: RequestLimits,
+
+    /// Where to trace the next completion's generation steps. Taken by the
+    /// first request traced.
+    trace_next: std::sync::Mutex<Option<PathBuf>>,
 }
 
 impl LlmServer {
@@ -172,285 +735,918 @@ impl LlmServer {
         shutdown_tx: mpsc::Sender<()>,
         activity_tx: mpsc::Sender<()>,
         model: Arc<LlamaModel>,
-        context: LlamaContext,
+        contexts: Vec<LlamaContext>,
+        chat_template: ChatTemplate,
     ) -> Self {
-        Self {
-            model_name,
+        Self::with_slot(
             hardware_profile,
             shutdown_tx,
             activity_tx,
+            ModelSlot::new(LoadedModel::new(model_name, model, contexts, chat_template)),
+        )
+    }
+
+    /// A server for whatever `loaded` holds.
+    fn with_slot(
+        hardware_profile: hardware::HardwareProfile,
+        shutdown_tx: mpsc::Sender<()>,
+        activity_tx: mpsc::Sender<()>,
+        loaded: ModelSlot<LoadedModel>,
+    ) -> Self {
+        Self {
+            hardware_profile,
+            shutdown_tx,
+            activity: Activity::new(activity_tx),
             shutting_down: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
             in_flight: Arc::new(AtomicUsize::new(0)),
-            model,
-            context: Mutex::new(context),
-            sampler: Sampler::greedy(),
+            metrics: Arc::new(Metrics::default()),
+            loaded: Arc::new(loaded),
+            reloader: None,
+            sampling_preset: SamplingPreset::default(),
+            limits: RequestLimits::default(),
+            trace_next: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Use `preset` for requests that don't name one.
+    fn with_sampling_preset(mut self, preset: SamplingPreset) -> Self {
+        self.sampling_preset = preset;
+        self
+    }
+
+    /// Reject completion requests exceeding `limits`.
+    fn with_request_limits(mut self, limits: RequestLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The served model, before the server is shared.
+    fn loaded_mut(&mut self) -> &mut LoadedModel {
+        Arc::get_mut(&mut self.loaded)
+            .expect("server isn't shared yet")
+            .get_mut()
+    }
+
+    /// Snapshot of the served model. Fails if a failed reload left none.
+    #[allow(clippy::result_large_err)] // handlers return the Status as is
+    fn current_model(&self) -> Result<Arc<LoadedModel>, Status> {
+        self.loaded
+            .get()
+            .ok_or_else(|| Status::unavailable("No model is loaded; the last reload failed"))
+    }
+
+    /// Report the server as running the fallback model.
+    fn with_degraded(mut self, degraded: bool) -> Self {
+        self.loaded_mut().degraded = degraded;
+        self
+    }
+
+    /// Describe the loaded model's files in status responses.
+    fn with_model_file(mut self, size_bytes: u64, quantization: String) -> Self {
+        let loaded = self.loaded_mut();
+        loaded.size_bytes = size_bytes;
+        loaded.quantization = quantization;
+        self
+    }
+
+    /// Record how long loading the model took.
+    fn with_model_load_time(mut self, elapsed: Duration) -> Self {
+        self.loaded_mut().load_time = elapsed;
+        self.metrics.set_model_load_time(elapsed);
+        self
+    }
+
+    /// Allow ReloadModel to switch to other models.
+    fn with_reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = Some(Arc::new(reloader));
+        self
+    }
+
+    /// Set whether GetStatus resets the idle timeout.
+    fn with_status_resets_idle(mut self, resets_idle: bool) -> Self {
+        self.activity.status_resets_idle = resets_idle;
+        self
+    }
+
+    /// Trace the generation steps of the next completion to `path`.
+    fn with_trace_next(self, path: Option<PathBuf>) -> Self {
+        *self.trace_next.lock().unwrap_or_else(|e| e.into_inner()) = path;
+        self
+    }
+
+    /// Open the pending trace file, if any, disarming tracing.
+    fn take_trace(&self) -> Option<std::io::BufWriter<File>> {
+        let path = self
+            .trace_next
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()?;
+        match File::create(&path) {
+            Ok(file) => {
+                info!("Tracing this completion's generation to {}", path.display());
+                Some(std::io::BufWriter::new(file))
+            }
+            Err(e) => {
+                warn!("Can't create trace file {}: {}", path.display(), e);
+                None
+            }
         }
     }
 
+    /// Whether the server accepts inference requests right now.
+    fn ready(&self) -> bool {
+        !self.shutting_down.load(Ordering::SeqCst)
+            && !self.paused.load(Ordering::SeqCst)
+            && !self.loaded.is_replacing()
+            && self.loaded.get().is_some()
+    }
+
     fn shutting_down(&self) -> Arc<AtomicBool> {
         self.shutting_down.clone()
     }
 
+    fn paused(&self) -> Arc<AtomicBool> {
+        self.paused.clone()
+    }
+
     fn in_flight(&self) -> Arc<AtomicUsize> {
         self.in_flight.clone()
     }
 
-    /// Build a prompt string from messages using ChatML format.
+    /// Extract every JSON object or array from model output.
     ///
-    /// Qwen 2.5 uses the ChatML template:
-    /// ```
-    /// <|im_start|>system
-    /// {system_prompt}<|im_end|>
-    /// <|im_start|>user
-    /// {user_message}<|im_end|>
-    /// <|im_start|>assistant
-    /// ```
-    ///
-    /// When tools are provided, adds tool calling instructions to the system prompt.
-    fn build_prompt(
-        &self,
-        system_prompt: &str,
-        messages: &[proto::Message],
-        tools: &[proto::ToolDef],
-    ) -> String {
-        let mut prompt = String::new();
+    /// The model may output text before/after the JSON, or several values
+    /// back to back. Each complete top-level value is returned in order.
+    fn extract_json_values(content: &str) -> Vec<serde_json::Value> {
+        let mut values = Vec::new();
+        let mut pos = 0;
+        while let Some(offset) = content[pos..].find(['{', '[']) {
+            let start = pos + offset;
+            let mut stream = serde_json::Deserializer::from_str(&content[start..])
+                .into_iter::<serde_json::Value>();
+            match stream.next() {
+                Some(Ok(value)) => {
+                    values.push(value);
+                    pos = start + stream.byte_offset();
+                }
+                // Not valid JSON from here; try the next bracket
+                _ => pos = start + 1,
+            }
+        }
+        values
+    }
 
-        // Build effective system prompt with tool instructions if tools provided
-        let effective_system_prompt = if tools.is_empty() {
-            system_prompt.to_string()
-        } else {
-            let tool_descriptions: Vec<String> = tools
-                .iter()
-                .map(|t| format!("- {}: {}", t.name, t.description))
-                .collect();
-
-            format!(
-                "{}\n\nYou have access to the following tools:\n{}\n\n\
-                 When you need to use a tool, respond with ONLY a JSON object in this exact format:\n\
-                 {{\"name\": \"tool_name\", \"arguments\": {{...}}}}\n\n\
-                 Do not include any other text before or after the JSON.",
-                system_prompt,
-                tool_descriptions.join("\n")
-            )
-        };
-
-        // Add system prompt
-        if !effective_system_prompt.is_empty() {
-            prompt.push_str("<|im_start|>system\n");
-            prompt.push_str(&effective_system_prompt);
-            prompt.push_str("<|im_end|>\n");
-        }
-
-        // Add each message
-        for msg in messages {
-            let role = match proto::Role::try_from(msg.role) {
-                Ok(proto::Role::User) => "user",
-                Ok(proto::Role::Assistant) => "assistant",
-                Ok(proto::Role::Tool) => "tool",
-                _ => "user", // Default to user for unknown roles
+    /// Contents of markdown code fences (```json or bare ```) in content.
+    fn fenced_blocks(content: &str) -> Vec<&str> {
+        let mut blocks = Vec::new();
+        let mut rest = content;
+        while let Some(open) = rest.find("```") {
+            // Skip the info string (e.g. "json") up to the end of the line
+            let after = &rest[open + 3..];
+            let Some(newline) = after.find('\n') else {
+                break;
+            };
+            let body = &after[newline + 1..];
+            let Some(close) = body.find("```") else {
+                break;
             };
+            blocks.push(&body[..close]);
+            rest = &body[close + 3..];
+        }
+        blocks
+    }
 
-            prompt.push_str(&format!("<|im_start|>{}\n", role));
-            prompt.push_str(&msg.content);
-            prompt.push_str("<|im_end|>\n");
+    /// Parse tool calls from content, extracting JSON if needed.
+    ///
+    /// Expected format: {"name": "tool_name", "arguments": {...}}, either
+    /// alone, as a JSON array of such objects, or as several objects in a row.
+    /// Qwen often wraps the JSON in a code fence with prose around it, so
+    /// fenced blocks are parsed first and the whole text only if they hold
+    /// no JSON.
+    fn parse_tool_calls(content: &str) -> Vec<proto::ToolCall> {
+        // Generate simple unique IDs using timestamp and position
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let mut values: Vec<serde_json::Value> = Self::fenced_blocks(content)
+            .into_iter()
+            .flat_map(Self::extract_json_values)
+            .collect();
+        if values.is_empty() {
+            values = Self::extract_json_values(content);
         }
 
-        // Add assistant start token for completion
-        prompt.push_str("<|im_start|>assistant\n");
-        prompt
+        values
+            .into_iter()
+            .flat_map(|value| match value {
+                serde_json::Value::Array(items) => items,
+                value => vec![value],
+            })
+            .filter_map(|value| {
+                let name = value.get("name")?.as_str()?.to_string();
+                let arguments = value.get("arguments")?;
+                Some((name, arguments.to_string()))
+            })
+            .enumerate()
+            .map(|(i, (name, arguments_json))| proto::ToolCall {
+                id: format!("call_{}_{}", nanos, i),
+                name,
+                arguments_json,
+            })
+            .collect()
     }
 
-    /// Extract JSON object from model output.
+    /// Check tool calls against the parameter schemas of the offered tools.
     ///
-    /// The model may output text before/after the JSON. This function finds
-    /// the first complete JSON object in the content.
-    fn extract_json(content: &str) -> Option<&str> {
-        // Find the first '{' and match to its closing '}'
-        let start = content.find('{')?;
-        let bytes = content.as_bytes();
-        let mut depth = 0;
-        let mut in_string = false;
-        let mut escape_next = false;
-
-        for (i, &byte) in bytes[start..].iter().enumerate() {
-            if escape_next {
-                escape_next = false;
+    /// Tools without a schema accept any arguments.
+    fn validate_tool_calls(
+        tool_calls: &[proto::ToolCall],
+        tools: &[proto::ToolDef],
+    ) -> Result<(), String> {
+        for call in tool_calls {
+            let tool = tools
+                .iter()
+                .find(|t| t.name == call.name)
+                .ok_or_else(|| format!("unknown tool '{}'", call.name))?;
+            if tool.parameters_schema.trim().is_empty() {
                 continue;
             }
-
-            match byte {
-                b'\\' if in_string => escape_next = true,
-                b'"' => in_string = !in_string,
-                b'{' if !in_string => depth += 1,
-                b'}' if !in_string => {
-                    depth -= 1;
-                    if depth == 0 {
-                        return Some(&content[start..start + i + 1]);
-                    }
+            let schema: serde_json::Value = match serde_json::from_str(&tool.parameters_schema) {
+                Ok(schema) => schema,
+                Err(e) => {
+                    warn!(
+                        "Tool '{}' has an invalid parameters schema: {}",
+                        tool.name, e
+                    );
+                    continue;
                 }
-                _ => {}
-            }
+            };
+            let arguments: serde_json::Value =
+                serde_json::from_str(&call.arguments_json).map_err(|e| e.to_string())?;
+            schema::validate(&schema, &arguments).map_err(|e| format!("{}: {}", call.name, e))?;
         }
-        None
+        Ok(())
     }
 
-    /// Parse tool call from content, extracting JSON if needed.
+    /// Map a generation outcome to the response stop reason and tool calls.
     ///
-    /// Expected format: {"name": "tool_name", "arguments": {...}}
-    fn parse_tool_call(content: &str) -> Option<proto::ToolCall> {
-        // Try to extract JSON from the content
-        let json_str = Self::extract_json(content).or_else(|| {
-            // Fallback: try parsing the trimmed content directly
-            Some(content.trim())
-        })?;
+    /// Tool calls are only parsed when the model finished on its own and the
+    /// request offered tools. Calls whose arguments don't match the tool's
+    /// schema are dropped. A model that finished without a valid tool call
+    /// stops with "eos", whether or not it was offered tools.
+    fn resolve_stop(
+        content: &str,
+        stop: &StopCause,
+        tools: &[proto::ToolDef],
+    ) -> (String, Vec<proto::ToolCall>) {
+        let mut tool_calls = Vec::new();
+        let stop_reason = match stop {
+            StopCause::Timeout => "timeout",
+            StopCause::MaxTokens => "max_tokens",
+            StopCause::Cancelled => "cancelled",
+            StopCause::DecodeError(_) => "decode_error",
+            StopCause::Eog if !tools.is_empty() => {
+                // Try to parse tool calls from content (using JSON extraction)
+                tool_calls = Self::parse_tool_calls(content);
+                for tool_call in &tool_calls {
+                    info!(
+                        "Parsed tool call: {} with args {}",
+                        tool_call.name, tool_call.arguments_json
+                    );
+                }
+                if tool_calls.is_empty() {
+                    debug!("No tool call found in response: {}", content);
+                    "eos"
+                } else if let Err(e) = Self::validate_tool_calls(&tool_calls, tools) {
+                    warn!("Rejecting invalid tool call ({}): {}", e, content);
+                    tool_calls.clear();
+                    "eos"
+                } else {
+                    "tool_use"
+                }
+            }
+            StopCause::Eog => "eos",
+        };
 
-        let value: serde_json::Value = serde_json::from_str(json_str).ok()?;
+        (stop_reason.to_string(), tool_calls)
+    }
+}
 
-        let name = value.get("name")?.as_str()?;
-        let arguments = value.get("arguments")?;
+/// Build generation limits from a completion request served in a context
+/// of `n_ctx` tokens.
+///
+/// Without a `max_tokens` in the request, the default is capped at half the
+/// context, so the prompt still has room once the output is reserved.
+fn generation_config(req: &CompletionRequest, n_ctx: usize) -> GenerationConfig {
+    GenerationConfig {
+        max_tokens: if req.max_tokens > 0 {
+            req.max_tokens as usize
+        } else {
+            DEFAULT_MAX_TOKENS.min(n_ctx / 2)
+        },
+        timeout: GENERATION_TIMEOUT,
+        // Negative or NaN penalties are treated as disabled
+        length_penalty: req.length_penalty.max(0.0),
+        logit_bias: req
+            .logit_bias
+            .iter()
+            .map(|(&token, &bias)| (token, bias))
+            .collect(),
+        repetition_penalty: req.repetition_penalty,
+    }
+}
 
-        // Generate a simple unique ID using timestamp
-        let id = format!(
-            "call_{}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos()
-        );
+/// The error for a request whose logit bias isn't a finite number, if it
+/// has one: a NaN would poison every comparison the sampler makes.
+fn check_logit_bias(req: &CompletionRequest) -> Option<Status> {
+    let (token, bias) = req.logit_bias.iter().find(|(_, bias)| !bias.is_finite())?;
+    Some(Status::invalid_argument(format!(
+        "Logit bias for token {} is not finite: {}",
+        token, bias
+    )))
+}
 
-        Some(proto::ToolCall {
-            id,
-            name: name.to_string(),
-            arguments_json: arguments.to_string(),
-        })
+/// Caps on completion request size, so a single request can't exhaust the
+/// server with thousands of messages or tools.
+#[derive(Debug, Clone, Copy)]
+struct RequestLimits {
+    max_messages: usize,
+    max_tools: usize,
+    max_samples: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_messages: DEFAULT_MAX_MESSAGES,
+            max_tools: DEFAULT_MAX_TOOLS,
+            max_samples: DEFAULT_MAX_SAMPLES,
+        }
     }
 }
 
-#[tonic::async_trait]
-impl InferenceService for LlmServer {
-    async fn complete(
-        &self,
-        request: Request<CompletionRequest>,
-    ) -> Result<Response<CompletionResponse>, Status> {
-        if self.shutting_down.load(Ordering::SeqCst) {
-            return Err(Status::unavailable("Server is shutting down"));
+impl RequestLimits {
+    /// The error for a request over a limit, if it is.
+    fn check(&self, req: &CompletionRequest) -> Option<Status> {
+        if req.messages.len() > self.max_messages {
+            return Some(Status::invalid_argument(format!(
+                "Too many messages: {} (limit {})",
+                req.messages.len(),
+                self.max_messages
+            )));
+        }
+        if req.tools.len() > self.max_tools {
+            return Some(Status::invalid_argument(format!(
+                "Too many tools: {} (limit {})",
+                req.tools.len(),
+                self.max_tools
+            )));
         }
+        if req.n as usize > self.max_samples {
+            return Some(Status::invalid_argument(format!(
+                "Too many completions: {} (limit {})",
+                req.n, self.max_samples
+            )));
+        }
+        None
+    }
+}
 
-        // Signal activity to reset idle timeout (ignore if channel is full)
-        let _ = self.activity_tx.try_send(());
+/// A warning for a request for `samples` completions, if it's wasteful:
+/// with greedy sampling they all come out the same.
+fn samples_warning(samples: usize, sampler: &Sampler) -> Option<String> {
+    (samples > 1 && sampler.is_greedy()).then(|| {
+        format!(
+            "{} completions requested with greedy sampling; they will be identical",
+            samples
+        )
+    })
+}
 
-        // Track in-flight requests
-        self.in_flight.fetch_add(1, Ordering::SeqCst);
-        let _guard = scopeguard::guard((), |_| {
-            self.in_flight.fetch_sub(1, Ordering::SeqCst);
-        });
+/// Parameters for the context that serves Embed requests.
+fn embedding_context_params() -> ContextParams {
+    ContextParams {
+        n_ctx: EMBEDDING_CTX,
+        n_batch: EMBEDDING_CTX,
+        embeddings: true,
+        pooling: Pooling::Mean,
+        ..Default::default()
+    }
+}
 
-        let req = request.into_inner();
-        info!(
-            "Complete request: {} messages, {} tools, system_prompt: {} chars",
-            req.messages.len(),
-            req.tools.len(),
-            req.system_prompt.len()
-        );
+/// Decode `text` in an embeddings context and return its normalized
+/// embedding along with the input token count.
+fn embed_text(ctx: &mut LlamaContext, text: &str) -> llama::Result<(Vec<f32>, usize)> {
+    ctx.clear_kv_cache();
+    let tokens = ctx.tokenize(text, true, false)?;
+    ctx.decode(&tokens, 0)?;
 
-        // Build prompt from messages using ChatML format
-        let prompt = self.build_prompt(&req.system_prompt, &req.messages, &req.tools);
-        debug!(
-            "Built prompt ({} chars):\n{}",
-            prompt.len(),
-            &prompt[..prompt.len().min(500)]
-        );
+    let mut embedding = ctx.embeddings()?.to_vec();
+    l2_normalize(&mut embedding);
+    Ok((embedding, tokens.len()))
+}
 
-        // Acquire context lock for inference
-        let mut ctx = self.context.lock().await;
+/// Scale `v` to unit length. All-zero vectors are left unchanged.
+fn l2_normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
 
-        // Clear KV cache for fresh generation
-        ctx.clear_kv_cache();
+/// Render a request with `messages` as its conversation, in the model's chat
+/// template.
+///
+/// The assistant prefill is placed right after the opening of the assistant
+/// turn, so generation continues from it instead of starting the turn fresh.
+/// A response schema adds answer-format instructions to the system prompt.
+fn build_prompt(
+    template: ChatTemplate,
+    req: &CompletionRequest,
+    messages: &[proto::Message],
+) -> Prompt {
+    let answer_format = req
+        .response_schema
+        .as_ref()
+        .and_then(structured::instructions);
+    let system_prompt = match answer_format {
+        Some(format) if req.system_prompt.is_empty() => format,
+        Some(format) => format!("{}\n\n{}", req.system_prompt, format),
+        None => req.system_prompt.clone(),
+    };
 
-        // Tokenize the prompt
-        let tokens = ctx.tokenize(&prompt, true, true).map_err(|e| {
-            error!("Tokenization failed: {}", e);
-            Status::internal(format!("Tokenization failed: {}", e))
-        })?;
+    let mut prompt = template.render(&system_prompt, messages, &req.tools);
+    prompt.prefill = req.assistant_prefill.clone();
+    prompt
+}
 
-        let input_tokens = tokens.len();
-        debug!("Tokenized {} input tokens", input_tokens);
+/// Tokens a prompt may use in a context of `n_ctx`, leaving room for all
+/// `max_tokens` of output.
+fn prompt_budget(n_ctx: usize, max_tokens: usize) -> usize {
+    n_ctx.saturating_sub(max_tokens)
+}
 
-        // Decode prompt tokens
-        ctx.decode(&tokens, 0).map_err(|e| {
-            error!("Decode failed: {}", e);
-            Status::internal(format!("Decode failed: {}", e))
-        })?;
+/// Render the request, dropping its oldest messages until the prompt is
+/// within `budget` tokens. Returns the prompt and whether anything was
+/// dropped.
+///
+/// The system prompt and the most recent user message are always kept; if
+/// those alone are over budget, `prepare_prompt` reports the overflow.
+fn fit_prompt<F>(
+    template: ChatTemplate,
+    req: &mut CompletionRequest,
+    budget: usize,
+    count_tokens: F,
+) -> (Prompt, bool)
+where
+    F: Fn(&Prompt) -> usize,
+{
+    let mut messages = std::mem::take(&mut req.messages);
+    // Each message is tokenized once, on its own, so dropping it just takes
+    // its tokens off the running total
+    let empty = count_tokens(&template.render("", &[], &[]));
+    let mut costs: Vec<usize> = messages
+        .iter()
+        .map(|m| count_tokens(&template.render("", std::slice::from_ref(m), &[])))
+        .map(|tokens| tokens.saturating_sub(empty))
+        .collect();
+
+    let mut prompt = build_prompt(template, req, &messages);
+    let mut total = count_tokens(&prompt);
+    let mut dropped = 0;
+    loop {
+        let dropped_before = dropped;
+        while total > budget {
+            let latest_user = messages
+                .iter()
+                .rposition(|m| m.role == proto::Role::User as i32);
+            let Some(oldest) = (0..messages.len()).find(|&i| Some(i) != latest_user) else {
+                break;
+            };
+            messages.remove(oldest);
+            total = total.saturating_sub(costs.remove(oldest));
+            dropped += 1;
+        }
+        if dropped == dropped_before {
+            break;
+        }
+        // The per-message counts are off by a little where the template
+        // folds the system prompt into the first message, so recount
+        prompt = build_prompt(template, req, &messages);
+        total = count_tokens(&prompt);
+    }
+    if dropped > 0 {
+        warn!(
+            "Dropped {} oldest message(s) to fit the prompt in {} tokens",
+            dropped, budget
+        );
+    }
 
-        // Generate response tokens
-        let mut output_tokens: Vec<i32> = Vec::new();
-        let max_tokens = if req.max_tokens > 0 {
-            req.max_tokens as usize
-        } else {
-            4096 // Default: enough for extract_pattern JSON with platform mappings
-        };
-        let mut pos = tokens.len() as i32;
+    req.messages = messages;
+    (prompt, dropped > 0)
+}
 
-        // NOTE: Grammar-constrained generation is disabled due to llama.cpp compatibility
-        // issues with Qwen models (crashes with "Unexpected empty grammar stack").
-        // See: https://github.com/ggml-org/llama.cpp/issues/11938
-        // TODO: Re-enable when upstream fix is available (file issue to track).
-        // Instead, we use prompt engineering + JSON extraction.
-        let _ = &req.json_schema; // Suppress unused warning
+/// Number of tokens `prompt` tokenizes to, or 0 if it can't be tokenized
+/// (left for `prepare_prompt` to report).
+fn count_prompt_tokens(model: &LlamaModel, prompt: &Prompt) -> usize {
+    tokenize_prompt(model, prompt).map_or(0, |(prefix, suffix)| prefix.len() + suffix.len())
+}
 
-        // Track the batch index where logits are available.
-        // After prompt decode, logits are at the last token index.
-        // After single-token decodes, logits are at index 0.
-        let mut logits_idx = (tokens.len() - 1) as i32;
+/// Tokenize a prompt into its system block and the rest.
+///
+/// The template writes BOS itself. Text is tokenized in pieces between
+/// special tokens, so where the split falls on a turn marker the halves
+/// give the same tokens as the whole prompt would. The prefill is parsed
+/// as plain text, so markup in it can't open or close a turn.
+fn tokenize_prompt(model: &LlamaModel, prompt: &Prompt) -> llama::Result<(Vec<i32>, Vec<i32>)> {
+    let prefix = model.tokenize(&prompt.prefix, false, true)?;
+    let mut suffix = model.tokenize(&prompt.suffix, false, true)?;
+    suffix.extend(model.tokenize(&prompt.prefill, false, false)?);
+    Ok((prefix, suffix))
+}
 
-        // Generation timeout: 5 minutes max per turn.
-        // CPU inference is slow (~18 tokens/sec) and tool call JSON can be very verbose,
-        // especially for the extract_pattern tool which includes platform mappings.
-        const GENERATION_TIMEOUT: Duration = Duration::from_secs(300);
-        let generation_start = std::time::Instant::now();
-        let mut timed_out = false;
+/// Decode the prompt into a checked-out context, returning its tokens.
+///
+/// Fails with `ContextWindowExceeded` before decoding anything if the prompt
+/// plus `reserve` tokens of output won't fit. The system block is only
+/// decoded if the context doesn't already hold it from an earlier request.
+fn prepare_prompt(
+    ctx: &mut PooledContext<LlamaContext>,
+    prompt: &Prompt,
+    reserve: usize,
+) -> llama::Result<Vec<i32>> {
+    let (ctx, prefix_cache) = ctx.parts();
+    let (prefix, suffix) = tokenize_prompt(ctx.model(), prompt)?;
+    debug!(
+        "Tokenized {} input tokens ({} in system block)",
+        prefix.len() + suffix.len(),
+        prefix.len()
+    );
+    ctx.check_fits(prefix.len() + suffix.len(), reserve)?;
 
-        for _ in 0..max_tokens {
-            // Check timeout
-            if generation_start.elapsed() > GENERATION_TIMEOUT {
-                warn!("Generation timeout reached after 5 minutes");
-                timed_out = true;
-                break;
+    prefix_cache.decode_prompt(ctx, &prefix, &suffix)?;
+    Ok([prefix, suffix].concat())
+}
+
+/// The status for a failed `prepare_prompt`: a prompt too long for the
+/// context is the caller's problem, anything else is ours.
+fn prompt_error_status(e: llama::LlamaError) -> Status {
+    match e {
+        llama::LlamaError::ContextWindowExceeded { .. } => Status::invalid_argument(e.to_string()),
+        _ => {
+            error!("Prompt preparation failed: {}", e);
+            Status::internal(e.to_string())
+        }
+    }
+}
+
+/// Decode a batch of `n_tokens` BOS tokens, then clear the KV cache.
+///
+/// The first decode of a given batch size pays for kernel compilation, so
+/// doing it at startup keeps that cost off the first real request. BOS is
+/// used rather than EOS, which some backends short-circuit on; models
+/// without one (`llama_vocab_bos` returns -1) fall back to EOS.
+fn warmup<C: InferenceContext + ?Sized>(ctx: &mut C, n_tokens: usize) -> llama::Result<()> {
+    if n_tokens == 0 {
+        return Ok(());
+    }
+    let token = match ctx.bos_token() {
+        bos if bos >= 0 => bos,
+        _ => ctx.eos_token(),
+    };
+    let tokens = vec![token; n_tokens];
+    let result = ctx.decode(&tokens, 0);
+    ctx.clear_kv_cache();
+    result
+}
+
+/// Sets a flag when dropped.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Run blocking generation work on a blocking thread until it finishes or
+/// the returned future is dropped.
+///
+/// tonic drops a unary handler's future when the client disconnects, but
+/// blocking work can't be interrupted by that, so `work` is given a flag that
+/// is set at that point and should stop early once it is. Whatever `work`
+/// returns after cancellation (e.g. a pooled context) is dropped.
+async fn run_until_disconnect<T, F>(work: F) -> Result<T, tokio::task::JoinError>
+where
+    F: FnOnce(&AtomicBool) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _cancel_on_drop = CancelOnDrop(cancelled.clone());
+    tokio::task::spawn_blocking(move || work(&cancelled)).await
+}
+
+/// Build the unary response for one or more samples of a completion.
+///
+/// The top-level fields describe the first sample, so callers that don't
+/// ask for several are unaffected; with more than one, every sample is also
+/// listed in `completions`. Usage counts the output of all of them.
+fn samples_response<C: InferenceContext + ?Sized>(
+    ctx: &C,
+    generations: &[Generation],
+    input_tokens: usize,
+    req: &CompletionRequest,
+) -> llama::Result<CompletionResponse> {
+    let (first, _) = generations
+        .split_first()
+        .expect("at least one sample is generated");
+    let mut response = completion_response(ctx, first, input_tokens, req)?;
+    if generations.len() > 1 {
+        for generation in generations {
+            let sample = completion_response(ctx, generation, input_tokens, req)?;
+            response.completions.push(proto::Completion {
+                content: sample.content,
+                tool_calls: sample.tool_calls,
+                stop_reason: sample.stop_reason,
+                error: sample.error,
+                structured_result: sample.structured_result,
+            });
+        }
+        let output_tokens: usize = generations.iter().map(|g| g.tokens.len()).sum();
+        if let Some(usage) = &mut response.usage {
+            usage.output_tokens = output_tokens as i32;
+        }
+    }
+    Ok(response)
+}
+
+/// Build the unary response for a finished generation.
+///
+/// The content starts with the request's assistant prefill, which the model
+/// continued from. A decode failure mid-generation still returns the tokens
+/// produced before it, with `stop_reason: "decode_error"`.
+fn completion_response<C: InferenceContext + ?Sized>(
+    ctx: &C,
+    generation: &Generation,
+    input_tokens: usize,
+    req: &CompletionRequest,
+) -> llama::Result<CompletionResponse> {
+    let content = format!(
+        "{}{}",
+        req.assistant_prefill,
+        ctx.detokenize(&generation.tokens)?
+    );
+    let (stop_reason, tool_calls) = LlmServer::resolve_stop(&content, &generation.stop, &req.tools);
+    Ok(CompletionResponse {
+        structured_result: structured_result(req, &content),
+        content,
+        tool_calls,
+        stop_reason,
+        usage: Some(Usage {
+            input_tokens: input_tokens as i32,
+            output_tokens: generation.tokens.len() as i32,
+        }),
+        error: stop_error(&generation.stop),
+        ..Default::default()
+    })
+}
+
+/// The answer parsed from `content`, if the request asked for one.
+fn structured_result(req: &CompletionRequest, content: &str) -> Option<proto::StructuredResult> {
+    let schema = req.response_schema.as_ref()?;
+    let result = structured::parse(schema, content);
+    if result.is_none() {
+        warn!("No answer matching the response schema in: {}", content);
+    }
+    result
+}
+
+/// The error description for a response, logging decode failures.
+fn stop_error(stop: &StopCause) -> String {
+    match stop {
+        StopCause::DecodeError(e) => {
+            error!("Decode failed mid-generation: {}", e);
+            format!("Decode failed: {}", e)
+        }
+        _ => String::new(),
+    }
+}
+
+/// Run a generation and emit it as a sequence of streaming chunks.
+///
+/// The assistant prefill, if any, is sent as the first content delta, then
+/// each generated token as its own delta. The stream always ends
+/// with a terminal chunk carrying the stop reason and usage, unless the
+/// consumer went away. A decode failure after generation started ends the
+/// stream normally, with `stop_reason: "decode_error"` on the terminal chunk.
+///
+/// `emit` returns `false` when the receiver is gone.
+fn stream_completion<C, F>(
+    ctx: &mut C,
+    sampler: &Sampler,
+    prompt_len: usize,
+    config: &GenerationConfig,
+    req: &CompletionRequest,
+    mut emit: F,
+) where
+    C: InferenceContext + ?Sized,
+    F: FnMut(CompletionResponse) -> bool,
+{
+    let prefill = &req.assistant_prefill;
+    if !prefill.is_empty()
+        && !emit(CompletionResponse {
+            content: prefill.clone(),
+            ..Default::default()
+        })
+    {
+        debug!("Stream receiver dropped before generation");
+        return;
+    }
+
+    let mut detokenizer = StreamingDetokenizer::new();
+    let generation = generation::generate(ctx, sampler, prompt_len, config, |ctx, token| {
+        match ctx.token_bytes(token) {
+            // Tokens that end mid-character produce no text until completed
+            Ok(bytes) => match detokenizer.push(&bytes) {
+                piece if piece.is_empty() => true,
+                piece => emit(CompletionResponse {
+                    content: piece,
+                    ..Default::default()
+                }),
+            },
+            Err(e) => {
+                warn!("Failed to detokenize token {}: {}", token, e);
+                true
             }
+        }
+    });
 
-            // Sample next token using regular sampling
-            let logits = ctx.get_logits(logits_idx);
-            let next_token = self.sampler.sample(logits);
+    let usage = Some(Usage {
+        input_tokens: prompt_len as i32,
+        output_tokens: generation.tokens.len() as i32,
+    });
 
-            // Check for end-of-generation tokens using the model's vocabulary.
-            // For Qwen 2.5, this includes <|im_end|> (151645), <|endoftext|> (151643), etc.
-            if self.model.is_eog(next_token) {
-                debug!("EOG token {} encountered", next_token);
-                break;
+    let terminal = match &generation.stop {
+        StopCause::Cancelled => {
+            debug!("Stream receiver dropped, stopping generation");
+            return;
+        }
+        stop => {
+            let content = match ctx.detokenize(&generation.tokens) {
+                Ok(content) => format!("{}{}", prefill, content),
+                Err(e) => {
+                    let _ = emit(CompletionResponse {
+                        stop_reason: "error".to_string(),
+                        error: format!("Detokenization failed: {}", e),
+                        usage,
+                        ..Default::default()
+                    });
+                    return;
+                }
+            };
+            let (stop_reason, tool_calls) = LlmServer::resolve_stop(&content, stop, &req.tools);
+            CompletionResponse {
+                structured_result: structured_result(req, &content),
+                content: detokenizer.finish(),
+                tool_calls,
+                stop_reason,
+                usage,
+                error: stop_error(stop),
+                ..Default::default()
             }
+        }
+    };
 
-            output_tokens.push(next_token);
+    let _ = emit(terminal);
+}
 
-            // Decode the new token
-            ctx.decode(&[next_token], pos).map_err(|e| {
-                error!("Decode failed during generation: {}", e);
-                Status::internal(format!("Decode failed: {}", e))
-            })?;
+#[tonic::async_trait]
+impl InferenceService for LlmServer {
+    async fn complete(
+        &self,
+        request: Request<CompletionRequest>,
+    ) -> Result<Response<CompletionResponse>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.paused.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is paused"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        // Track in-flight requests
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _guard = scopeguard::guard((), |_| {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
 
-            pos += 1;
-            // After single-token decode, logits are at batch index 0
-            logits_idx = 0;
+        let mut req = request.into_inner();
+        info!(
+            "Complete request: {} messages, {} tools, system_prompt: {} chars",
+            req.messages.len(),
+            req.tools.len(),
+            req.system_prompt.len()
+        );
+        if let Some(status) = self.limits.check(&req) {
+            return Err(status);
+        }
+        if let Some(status) = check_logit_bias(&req) {
+            return Err(status);
         }
 
-        // Detokenize output
-        let content = ctx.detokenize(&output_tokens).map_err(|e| {
-            error!("Detokenization failed: {}", e);
-            Status::internal(format!("Detokenization failed: {}", e))
+        // Check out a context; waits if every context is busy
+        let loaded = self.current_model()?;
+        let mut ctx = loaded.contexts.acquire().await;
+        let n_ctx = ctx.n_ctx() as usize;
+        let config = generation_config(&req, n_ctx);
+
+        // Build prompt from messages in the model's chat template
+        let (prompt, prompt_truncated) = fit_prompt(
+            loaded.chat_template,
+            &mut req,
+            prompt_budget(n_ctx, config.max_tokens),
+            |prompt| count_prompt_tokens(ctx.model(), prompt),
+        );
+        debug!(
+            "Built prompt ({} chars):\n{}",
+            prompt.prefix.len() + prompt.suffix.len() + prompt.prefill.len(),
+            &prompt.prefix[..prompt.prefix.len().min(500)]
+        );
+
+        let prompt_tokens =
+            prepare_prompt(&mut ctx, &prompt, config.max_tokens).map_err(prompt_error_status)?;
+        let input_tokens = prompt_tokens.len();
+
+        // NOTE: Grammar-constrained generation is disabled due to llama.cpp compatibility
+        // issues with Qwen models (crashes with "Unexpected empty grammar stack").
+        // See: https://github.com/ggml-org/llama.cpp/issues/11938
+        // TODO: Re-enable when upstream fix is available (file issue to track).
+        // Instead, we use prompt engineering + JSON extraction.
+        let _ = &req.json_schema; // Suppress unused warning
+
+        let sampler = sampling::sampler_for(&req, self.sampling_preset);
+        let samples = (req.n as usize).max(1);
+        if let Some(warning) = samples_warning(samples, &sampler) {
+            warn!("{}", warning);
+        }
+        // Positions in a trace are per sample, so leave multi-sample
+        // requests to the next one
+        let trace = if samples == 1 {
+            self.take_trace()
+        } else {
+            None
+        };
+        let generation_start = std::time::Instant::now();
+        // The work outlives this handler if the client goes away, so it
+        // holds its own snapshot for a reload to wait on
+        let snapshot = loaded.clone();
+        let (ctx, generations) = run_until_disconnect(move |cancelled| {
+            let _snapshot = snapshot;
+            let mut trace = trace.map(|out| Trace::new(out, prompt_tokens.len()));
+            let generations = generation::generate_samples(
+                &mut *ctx,
+                &sampler,
+                &prompt_tokens,
+                samples,
+                &config,
+                |ctx, token| {
+                    if let Some(Err(e)) = trace.as_mut().map(|t| t.step(ctx, token)) {
+                        warn!("Stopped tracing: {}", e);
+                        trace = None;
+                    }
+                    !cancelled.load(Ordering::Relaxed)
+                },
+            );
+            if let Some(Err(e)) = trace.map(Trace::finish) {
+                warn!("Failed to write trace: {}", e);
+            }
+            (ctx, generations)
+        })
+        .await
+        .map_err(|e| {
+            error!("Generation task failed: {}", e);
+            Status::internal(format!("Generation failed: {}", e))
         })?;
 
+        let mut response =
+            samples_response(&*ctx, &generations, input_tokens, &req).map_err(|e| {
+                error!("Detokenization failed: {}", e);
+                Status::internal(format!("Detokenization failed: {}", e))
+            })?;
+        response.prompt_truncated = prompt_truncated;
+        let output_tokens: usize = generations.iter().map(|g| g.tokens.len()).sum();
+
+        let content = &response.content;
         info!(
             "Generated {} tokens in {:?}: {}",
-            output_tokens.len(),
+            output_tokens,
             generation_start.elapsed(),
             if content.len() > 100 {
                 format!("{}...", &content[..100])
@@ -459,40 +1655,259 @@ impl InferenceService for LlmServer {
             }
         );
 
-        // Parse tool calls if tools were provided in the request
-        let mut tool_calls = Vec::new();
-        let stop_reason = if timed_out {
-            "timeout".to_string()
-        } else if output_tokens.len() >= max_tokens {
-            "max_tokens".to_string()
-        } else if !req.tools.is_empty() {
-            // Try to parse tool call from content (using JSON extraction)
-            if let Some(tool_call) = Self::parse_tool_call(&content) {
-                info!(
-                    "Parsed tool call: {} with args {}",
-                    tool_call.name, tool_call.arguments_json
-                );
-                tool_calls.push(tool_call);
-                "tool_use".to_string()
-            } else {
-                debug!("No tool call found in response: {}", content);
-                "end_turn".to_string()
+        if response.error.is_empty() {
+            self.metrics
+                .record_completion(input_tokens, output_tokens, generation_start.elapsed());
+        }
+
+        Ok(Response::new(response))
+    }
+
+    type CompleteStreamStream = ReceiverStream<Result<CompletionResponse, Status>>;
+
+    async fn complete_stream(
+        &self,
+        request: Request<CompletionRequest>,
+    ) -> Result<Response<Self::CompleteStreamStream>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.paused.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is paused"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        let mut req = request.into_inner();
+        info!(
+            "CompleteStream request: {} messages, {} tools, system_prompt: {} chars",
+            req.messages.len(),
+            req.tools.len(),
+            req.system_prompt.len()
+        );
+        if let Some(status) = self.limits.check(&req) {
+            return Err(status);
+        }
+        if let Some(status) = check_logit_bias(&req) {
+            return Err(status);
+        }
+        if req.n > 1 {
+            return Err(Status::invalid_argument(
+                "n > 1 is only supported by Complete, not CompleteStream",
+            ));
+        }
+
+        let loaded = self.current_model()?;
+        let chat_template = loaded.chat_template;
+
+        let (tx, rx) = mpsc::channel(32);
+        let sampler = sampling::sampler_for(&req, self.sampling_preset);
+        let in_flight = self.in_flight.clone();
+        let activity = self.activity.clone();
+        let metrics = self.metrics.clone();
+
+        // Generation is blocking work; run it off the async runtime and feed
+        // chunks through the channel as tokens are produced. The in-flight
+        // count covers the whole stream, not just this call.
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        tokio::task::spawn_blocking(move || {
+            let _guard = scopeguard::guard((), |_| {
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            });
+
+            // Holding the snapshot makes a reload wait for the stream to end
+            let mut ctx = tokio::runtime::Handle::current().block_on(loaded.contexts.acquire());
+            let n_ctx = ctx.n_ctx() as usize;
+            let config = generation_config(&req, n_ctx);
+            let (prompt, prompt_truncated) = fit_prompt(
+                chat_template,
+                &mut req,
+                prompt_budget(n_ctx, config.max_tokens),
+                |prompt| count_prompt_tokens(ctx.model(), prompt),
+            );
+            let input_tokens = match prepare_prompt(&mut ctx, &prompt, config.max_tokens) {
+                Ok(prompt_tokens) => prompt_tokens.len(),
+                Err(e) => {
+                    // Nothing has been streamed yet, so a plain status is enough.
+                    let _ = tx.blocking_send(Err(prompt_error_status(e)));
+                    return;
+                }
+            };
+
+            let generation_start = std::time::Instant::now();
+            stream_completion(
+                &mut *ctx,
+                &sampler,
+                input_tokens,
+                &config,
+                &req,
+                |mut chunk| {
+                    // Only the terminal chunk carries usage
+                    if chunk.usage.is_some() {
+                        chunk.prompt_truncated = prompt_truncated;
+                    }
+                    if chunk.error.is_empty() {
+                        if let Some(usage) = &chunk.usage {
+                            metrics.record_completion(
+                                input_tokens,
+                                usage.output_tokens as usize,
+                                generation_start.elapsed(),
+                            );
+                        }
+                    }
+                    send_stream_chunk(&tx, &activity, chunk)
+                },
+            );
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn embed(
+        &self,
+        request: Request<EmbedRequest>,
+    ) -> Result<Response<EmbedResponse>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.paused.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is paused"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _guard = scopeguard::guard((), |_| {
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        let req = request.into_inner();
+        info!("Embed request: {} chars", req.text.len());
+
+        let loaded = self.current_model()?;
+        let mut embedding_context = loaded.embedding_context.lock().await;
+        if embedding_context.is_none() {
+            let ctx = LlamaContext::new(loaded.model.clone(), embedding_context_params()).map_err(
+                |e| {
+                    error!("Embeddings context creation failed: {}", e);
+                    Status::internal(e.to_string())
+                },
+            )?;
+            info!("Embeddings context created");
+            *embedding_context = Some(ctx);
+        }
+        let ctx = embedding_context
+            .as_mut()
+            .expect("embeddings context initialized");
+
+        let (embedding, input_tokens) = embed_text(ctx, &req.text).map_err(|e| match e {
+            llama::LlamaError::ContextWindowExceeded { .. } => {
+                Status::invalid_argument(e.to_string())
             }
-        } else {
-            "end_turn".to_string()
-        };
+            _ => {
+                error!("Embedding failed: {}", e);
+                Status::internal(e.to_string())
+            }
+        })?;
 
-        let response = CompletionResponse {
-            content,
-            tool_calls,
-            stop_reason,
-            usage: Some(Usage {
-                input_tokens: input_tokens as i32,
-                output_tokens: output_tokens.len() as i32,
-            }),
+        Ok(Response::new(EmbedResponse {
+            embedding,
+            input_tokens: input_tokens as i32,
+        }))
+    }
+
+    async fn count_tokens(
+        &self,
+        request: Request<CountTokensRequest>,
+    ) -> Result<Response<CountTokensResponse>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        let req = request.into_inner();
+        let req = CompletionRequest {
+            system_prompt: req.system_prompt,
+            messages: req.messages,
+            tools: req.tools,
+            ..Default::default()
         };
 
-        Ok(Response::new(response))
+        if let Some(status) = self.limits.check(&req) {
+            return Err(status);
+        }
+
+        // Tokenizing needs only the model, so this answers even while every
+        // context is busy with a long generation
+        let loaded = self.current_model()?;
+        let prompt = build_prompt(loaded.chat_template, &req, &req.messages);
+        Ok(Response::new(CountTokensResponse {
+            token_count: count_prompt_tokens(&loaded.model, &prompt) as i32,
+            n_ctx: loaded.n_ctx as i32,
+        }))
+    }
+
+    async fn tokenize(
+        &self,
+        request: Request<TokenizeRequest>,
+    ) -> Result<Response<TokenizeResponse>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        let req = request.into_inner();
+        let loaded = self.current_model()?;
+        let tokens = loaded
+            .model
+            .tokenize(&req.text, req.add_special, req.parse_special)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        Ok(Response::new(TokenizeResponse { tokens }))
+    }
+
+    async fn detokenize(
+        &self,
+        request: Request<DetokenizeRequest>,
+    ) -> Result<Response<DetokenizeResponse>, Status> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        if self.loaded.is_replacing() {
+            return Err(Status::unavailable("Model is being reloaded"));
+        }
+
+        self.activity.record(RequestKind::Work);
+
+        let req = request.into_inner();
+        let loaded = self.current_model()?;
+        // llama.cpp doesn't bounds-check token ids
+        let n_vocab = loaded.model.n_vocab() as i32;
+        if let Some(token) = req.tokens.iter().find(|&&t| !(0..n_vocab).contains(&t)) {
+            return Err(Status::invalid_argument(format!(
+                "Token {} is outside the vocabulary ({} tokens)",
+                token, n_vocab
+            )));
+        }
+
+        let text = loaded.model.detokenize(&req.tokens).map_err(|e| {
+            error!("Detokenization failed: {}", e);
+            Status::internal(e.to_string())
+        })?;
+        Ok(Response::new(DetokenizeResponse { text }))
     }
 
     async fn shutdown(
@@ -516,35 +1931,187 @@ impl InferenceService for LlmServer {
         &self,
         _request: Request<StatusRequest>,
     ) -> Result<Response<StatusResponse>, Status> {
-        // Signal activity to reset idle timeout (ignore if channel is full)
-        let _ = self.activity_tx.try_send(());
+        self.activity.record(RequestKind::Status);
 
+        // Never touches the contexts, so it answers even while every one is
+        // busy with a long generation
+        let paused = self.paused.load(Ordering::SeqCst);
+        let loaded = self.loaded.get();
+        let loaded = loaded.as_deref();
         let response = StatusResponse {
-            ready: !self.shutting_down.load(Ordering::SeqCst),
-            model_name: self.model_name.clone(),
-            model_size_bytes: 0, // TODO: Actual model size when model is loaded
+            ready: self.ready(),
+            model_name: loaded.map(|l| l.name.clone()).unwrap_or_default(),
+            model_size_bytes: loaded.map_or(0, |l| l.size_bytes as i64),
             backend: self.hardware_profile.gpu_backend.to_string(),
             available_vram_bytes: self.hardware_profile.vram_bytes as i64,
+            gpu_name: self.hardware_profile.gpu_name.clone().unwrap_or_default(),
             addon_version: env!("CARGO_PKG_VERSION").to_string(),
+            paused,
+            degraded: loaded.is_some_and(|l| l.degraded),
+            quantization: loaded.map(|l| l.quantization.clone()).unwrap_or_default(),
         };
 
         Ok(Response::new(response))
     }
+
+    async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+        self.activity.record(RequestKind::Ping);
+        Ok(Response::new(PingResponse {
+            ready: self.ready(),
+        }))
+    }
+
+    async fn get_metrics(
+        &self,
+        _request: Request<MetricsRequest>,
+    ) -> Result<Response<MetricsResponse>, Status> {
+        Ok(Response::new(MetricsResponse {
+            text: self.metrics.render(self.in_flight.load(Ordering::SeqCst)),
+        }))
+    }
+
+    async fn cancel_startup(
+        &self,
+        _request: Request<CancelStartupRequest>,
+    ) -> Result<Response<CancelStartupResponse>, Status> {
+        Err(Status::failed_precondition("Startup has already finished"))
+    }
+
+    async fn reload_model(
+        &self,
+        request: Request<ReloadModelRequest>,
+    ) -> Result<Response<ReloadModelResponse>, Status> {
+        let Some(reloader) = self.reloader.clone() else {
+            return Err(Status::failed_precondition(
+                "This server can't reload models",
+            ));
+        };
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Status::unavailable("Server is shutting down"));
+        }
+        self.activity.record(RequestKind::Work);
+
+        // Check the model before touching the one being served
+        let model_name = request.into_inner().model_name;
+        reloader
+            .selector
+            .select_named(&model_name, &self.hardware_profile)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        if !reloader.model_manager.is_available(&model_name).await {
+            return Err(Status::failed_precondition(format!(
+                "Model {} isn't downloaded; run `tsuku-llm download {}` first",
+                model_name, model_name
+            )));
+        }
+
+        info!("Reloading with model {}", model_name);
+        let load = {
+            let reloader = reloader.clone();
+            let model_name = model_name.clone();
+            async move {
+                tokio::task::spawn_blocking(move || reloader.load(&model_name))
+                    .await
+                    .context("Model loading task panicked")?
+            }
+        };
+        let unload = |previous: LoadedModel| {
+            info!("Unloading model {}", previous.name);
+            previous.name.clone()
+        };
+        let restore = |previous: &String| {
+            let previous = previous.clone();
+            async move {
+                warn!("Loading model {} again", previous);
+                tokio::task::spawn_blocking(move || reloader.load(&previous))
+                    .await
+                    .context("Model loading task panicked")?
+            }
+        };
+        let previous = self
+            .loaded
+            .replace(unload, load, restore)
+            .await
+            .map_err(|e| match e {
+                ReloadError::InProgress => Status::aborted(e.to_string()),
+                ReloadError::Load(e) => {
+                    error!("Model reload failed, kept the previous model: {:#}", e);
+                    Status::internal(format!("{:#}", e))
+                }
+                ReloadError::Restore { load, restore } => {
+                    error!(
+                        "Model reload failed: {:#}; reloading the previous model also \
+                         failed, no model is loaded: {:#}",
+                        load, restore
+                    );
+                    Status::internal(format!("{:#}", load))
+                }
+            })?
+            .unwrap_or_default();
+        info!("Replaced model {} with {}", previous, model_name);
+        if let Some(loaded) = self.loaded.get() {
+            self.metrics.set_model_load_time(loaded.load_time);
+        }
+
+        Ok(Response::new(ReloadModelResponse {
+            model_name,
+            previous_model_name: previous,
+        }))
+    }
 }
 
-/// Returns the path to the Unix domain socket.
-fn socket_path() -> PathBuf {
+/// Returns the tsuku home directory.
+fn tsuku_home() -> PathBuf {
     // Use TSUKU_HOME if set, otherwise default to ~/.tsuku
-    let home = std::env::var("TSUKU_HOME")
+    std::env::var("TSUKU_HOME")
         .ok()
         .map(PathBuf::from)
         .unwrap_or_else(|| {
             dirs::home_dir()
                 .expect("Could not determine home directory")
                 .join(".tsuku")
-        });
+        })
+}
+
+/// Make sure the tsuku home directory is usable: it exists (or can be
+/// created), is a directory, and is writable. The socket, lock file and
+/// models all live under it, so this runs before any of them are touched.
+fn check_tsuku_home(home: &Path) -> Result<()> {
+    if home.exists() && !home.is_dir() {
+        bail!("TSUKU_HOME {:?} is not a directory", home);
+    }
+    std::fs::create_dir_all(home)
+        .with_context(|| format!("TSUKU_HOME {:?} does not exist and can't be created", home))?;
+
+    let probe = home.join(format!(".tsuku-llm-write-test-{}", std::process::id()));
+    File::create(&probe).with_context(|| format!("TSUKU_HOME {:?} is not writable", home))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Returns the path to the Unix domain socket.
+fn socket_path() -> PathBuf {
+    tsuku_home().join("llm.sock")
+}
+
+/// Returns the directory where models are stored.
+fn models_dir() -> PathBuf {
+    tsuku_home().join("models")
+}
+
+/// Returns the path to an optional user manifest that replaces the built-in one.
+fn manifest_path() -> PathBuf {
+    tsuku_home().join("models.json")
+}
 
-    home.join("llm.sock")
+/// Load the user manifest if there is one, otherwise the built-in one.
+///
+/// If TSUKU_LLM_MANIFEST_PUBLIC_KEY is set, a user manifest must be signed
+/// with that minisign key.
+fn load_manifest() -> Result<model::ModelManifest, model::ManifestError> {
+    let public_key = std::env::var("TSUKU_LLM_MANIFEST_PUBLIC_KEY")
+        .ok()
+        .filter(|s| !s.is_empty());
+    model::ModelManifest::load_or_default(&manifest_path(), public_key.as_deref())
 }
 
 /// Returns the path to the lock file.
@@ -556,6 +2123,23 @@ fn lock_path() -> PathBuf {
     path
 }
 
+/// Pick the model to run after the selected one failed to download: the
+/// fallback model if one is configured and present, otherwise the error.
+fn fallback_model(download_error: models::ModelError, fallback: Option<&Path>) -> Result<PathBuf> {
+    let download_error = anyhow::Error::new(download_error).context("Failed to download model");
+    let Some(fallback) = fallback else {
+        return Err(download_error);
+    };
+    if !fallback.is_file() {
+        return Err(download_error.context(format!("Fallback model {:?} not found", fallback)));
+    }
+    warn!(
+        "{:#}; running degraded on fallback model {:?}",
+        download_error, fallback
+    );
+    Ok(fallback.to_path_buf())
+}
+
 /// Tries to acquire an exclusive lock on the lock file.
 /// Returns the lock file handle if successful, or an error if another process holds the lock.
 fn acquire_lock(lock: &PathBuf) -> Result<File> {
@@ -570,6 +2154,7 @@ fn acquire_lock(lock: &PathBuf) -> Result<File> {
         .write(true)
         .open(lock)
         .context("Failed to open lock file")?;
+    restrict_to_owner(lock)?;
 
     // Try to acquire exclusive non-blocking lock
     let fd = file.as_raw_fd();
@@ -587,14 +2172,77 @@ fn acquire_lock(lock: &PathBuf) -> Result<File> {
     Ok(file)
 }
 
-/// Clean up socket and lock files.
-fn cleanup_files(socket: &PathBuf, lock: &PathBuf) {
-    if socket.exists() {
-        if let Err(e) = std::fs::remove_file(socket) {
-            warn!("Failed to remove socket file: {}", e);
-        } else {
-            info!("Removed socket file: {:?}", socket);
-        }
+/// Bind the Unix socket, reachable only by the user running the server.
+///
+/// The socket is bound inside a private 0700 directory next to `socket`,
+/// restricted to its owner, then renamed into place, so there is no moment
+/// at which another user could connect to it.
+fn bind_socket(socket: &Path) -> Result<UnixListener> {
+    use std::os::unix::fs::DirBuilderExt;
+    let parent = socket
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staging = parent.join(format!(".tsuku-llm-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("Failed to create {:?}", staging))?;
+
+    let staged = staging.join("s");
+    let bound = UnixListener::bind(&staged)
+        .context("Failed to bind Unix socket")
+        .and_then(|listener| {
+            restrict_to_owner(&staged)?;
+            std::fs::rename(&staged, socket)
+                .with_context(|| format!("Failed to move socket to {:?}", socket))?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_file(&staged);
+    if let Err(e) = std::fs::remove_dir(&staging) {
+        warn!("Failed to remove {:?}: {}", staging, e);
+    }
+    bound
+}
+
+/// Bind `name` in the Linux abstract socket namespace.
+///
+/// Fails if another process has bound the name already, like a socket file
+/// would, but leaves nothing behind on exit.
+#[cfg(target_os = "linux")]
+fn bind_abstract_socket(name: &str) -> Result<UnixListener> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())
+        .context("Invalid abstract socket name")?;
+    let listener = std::os::unix::net::UnixListener::bind_addr(&addr)
+        .with_context(|| format!("Failed to bind abstract socket @{}", name))?;
+    listener.set_nonblocking(true)?;
+    Ok(UnixListener::from_std(listener)?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_abstract_socket(_name: &str) -> Result<UnixListener> {
+    bail!("--abstract-socket is only supported on Linux")
+}
+
+/// Make `path` readable and writable by its owner only, whatever the
+/// umask, so other local users can't reach the server.
+fn restrict_to_owner(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to set permissions on {:?}", path))?;
+    info!("Set mode 0600 on {:?}", path);
+    Ok(())
+}
+
+/// Clean up socket and lock files.
+fn cleanup_files(socket: Option<&Path>, lock: &PathBuf) {
+    if let Some(socket) = socket.filter(|socket| socket.exists()) {
+        if let Err(e) = std::fs::remove_file(socket) {
+            warn!("Failed to remove socket file: {}", e);
+        } else {
+            info!("Removed socket file: {:?}", socket);
+        }
     }
 
     if lock.exists() {
@@ -606,12 +2254,141 @@ fn cleanup_files(socket: &PathBuf, lock: &PathBuf) {
     }
 }
 
+/// Send one stream chunk to the client, blocking while the channel is full.
+///
+/// Each chunk the client accepts counts as activity, so a stream that is
+/// still being consumed keeps the daemon alive past the idle timeout.
+/// Returns false once the client has gone away.
+fn send_stream_chunk(
+    tx: &mpsc::Sender<Result<CompletionResponse, Status>>,
+    activity: &Activity,
+    chunk: CompletionResponse,
+) -> bool {
+    if tx.blocking_send(Ok(chunk)).is_err() {
+        return false;
+    }
+    activity.record(RequestKind::Work);
+    true
+}
+
+/// Resolve once no activity has been signalled for `idle_timeout`.
+///
+/// A paused server is being kept around on purpose, so it never idles out.
+async fn wait_for_idle(
+    activity_rx: &mut mpsc::Receiver<()>,
+    idle_timeout: Duration,
+    paused: &AtomicBool,
+) {
+    let mut idle_deadline = deadline_after(idle_timeout);
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(idle_deadline) => {
+                if !paused.load(Ordering::SeqCst) {
+                    return;
+                }
+                idle_deadline = deadline_after(idle_timeout);
+            }
+            Some(()) = activity_rx.recv() => {
+                // Activity received, reset the idle deadline
+                idle_deadline = deadline_after(idle_timeout);
+            }
+        }
+    }
+}
+
+/// `timeout` from now, or `MAX_DURATION` from now if that's too far off for
+/// an `Instant`.
+fn deadline_after(timeout: Duration) -> tokio::time::Instant {
+    let now = tokio::time::Instant::now();
+    now.checked_add(timeout)
+        .unwrap_or_else(|| now + MAX_DURATION)
+}
+
+/// The signals that shut the server down gracefully: SIGTERM, and SIGINT
+/// so that Ctrl-C in a terminal cleans up the socket and lock too.
+///
+/// Each signal is forwarded into a channel by name, so tests can send
+/// names instead of signalling the whole test process.
+struct ShutdownSignals {
+    rx: mpsc::Receiver<&'static str>,
+}
+
+impl ShutdownSignals {
+    fn register() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        let (tx, rx) = mpsc::channel(4);
+        for (kind, name) in [
+            (SignalKind::terminate(), "SIGTERM"),
+            (SignalKind::interrupt(), "SIGINT"),
+        ] {
+            let mut stream =
+                signal(kind).with_context(|| format!("Failed to register {} handler", name))?;
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while stream.recv().await.is_some() {
+                    if tx.send(name).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(Self { rx })
+    }
+
+    /// Wait for either signal, returning its name.
+    async fn recv(&mut self) -> &'static str {
+        match self.rx.recv().await {
+            Some(name) => name,
+            // No signal can arrive any more
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Download `model_name` while the startup service answers clients, so they
+/// can see the download is under way and cancel it.
+///
+/// If a shutdown signal or `CancelStartup` ends the download first, the
+/// socket and lock are cleaned up and the process exits.
+async fn download_during_startup<S>(
+    model_manager: &models::ModelManager,
+    model_name: &str,
+    incoming: std::pin::Pin<&mut S>,
+    tcp_token: Option<String>,
+    signals: &mut ShutdownSignals,
+    socket: Option<&Path>,
+    lock: &PathBuf,
+) -> Result<PathBuf, models::ModelError>
+where
+    S: futures_util::Stream<Item = std::io::Result<listener::Connection>>,
+{
+    let outcome = startup::download_model(
+        model_manager,
+        model_name,
+        incoming,
+        listener::TcpAuth::new(tcp_token),
+        async {
+            signals.recv().await;
+        },
+    )
+    .await;
+    let reason = match outcome {
+        startup::Outcome::Finished(result) => return result,
+        startup::Outcome::Cancelled => "startup cancelled",
+        startup::Outcome::Terminated => "signal during startup",
+    };
+    info!("Model download stopped ({}), cleaning up", reason);
+    cleanup_files(socket, lock);
+    info!("Server shutdown complete (reason: {})", reason);
+    std::process::exit(outcome.exit_code().unwrap_or(0));
+}
+
 /// Wait for in-flight requests to complete with a timeout.
 /// Returns true if interrupted by a second signal, false otherwise.
 async fn wait_for_in_flight(
     in_flight: &Arc<AtomicUsize>,
     timeout: Duration,
-    sigterm: &mut tokio::signal::unix::Signal,
+    signals: &mut ShutdownSignals,
 ) -> bool {
     let start = std::time::Instant::now();
 
@@ -633,17 +2410,374 @@ async fn wait_for_in_flight(
             (timeout - start.elapsed()).as_secs_f32()
         );
 
-        // Wait for either the poll interval or a second SIGTERM
+        // Wait for either the poll interval or a second signal
         tokio::select! {
             _ = tokio::time::sleep(Duration::from_millis(100)) => {}
-            _ = sigterm.recv() => {
-                warn!("Received second SIGTERM during grace period, forcing immediate cleanup");
+            signal = signals.recv() => {
+                warn!("Received second {} during grace period, forcing immediate cleanup", signal);
                 return true;
             }
         }
     }
 }
 
+/// Default context cap when no size is requested.
+///
+/// Recipe generation prompts can reach ~27K tokens. We cap context size
+/// rather than using n_ctx_train() because larger models have huge training
+/// contexts (14B has 128K) that would exhaust GPU VRAM on KV cache alone.
+///
+/// Context budget: 14B Q4_K_M model = 8.1 GB, KV cache = ~0.19 GB/K tokens.
+/// On a 16 GB GPU (~13.7 GB free), 32K context would need 6 GB KV = 14.1 GB total (OOM).
+/// 24K context needs 4.5 GB KV = 12.6 GB total (fits with headroom).
+const MAX_CTX: u32 = 24576;
+
+/// How many `n_ctx`-token inference contexts to create for `model`:
+/// `--contexts`, cut down to what fits in `--max-memory-mb`, or as many KV
+/// caches as fit in the memory budget.
+fn context_count(
+    serve_args: &ServeArgs,
+    hardware_profile: &hardware::HardwareProfile,
+    model: &LlamaModel,
+    n_ctx: u32,
+) -> usize {
+    let kv = memory::KvCacheShape::from_model(model, serve_args.kv_cache_type);
+    let budget_bytes = serve_args
+        .max_memory_mb
+        .map(|mb| mb.saturating_mul(1024 * 1024));
+    match (serve_args.contexts, budget_bytes) {
+        (Some(requested), Some(budget_bytes)) => {
+            let fits = memory::max_context_count(budget_bytes, model.size_bytes(), n_ctx, kv);
+            if requested > fits {
+                warn!(
+                    "Only {} of {} inference contexts fit in the memory budget",
+                    fits, requested
+                );
+            }
+            requested.min(fits)
+        }
+        (Some(requested), None) => requested,
+        (None, _) => {
+            let memory_bytes = match budget_bytes {
+                Some(bytes) => bytes,
+                None if hardware_profile.vram_bytes > 0 => hardware_profile.vram_bytes,
+                None => hardware_profile.ram_bytes,
+            };
+            memory::default_context_count(memory_bytes, model.size_bytes(), n_ctx, kv)
+        }
+    }
+}
+
+/// Up to `n` inference contexts on `model`, starting with `first`. Stops
+/// early, keeping those created so far, once one can't be created.
+fn create_contexts(
+    first: LlamaContext,
+    model: &Arc<LlamaModel>,
+    params: &ContextParams,
+    n: usize,
+) -> Vec<LlamaContext> {
+    let mut contexts = vec![first];
+    while contexts.len() < n {
+        match LlamaContext::new(model.clone(), params.clone()) {
+            Ok(ctx) => contexts.push(ctx),
+            Err(e) => {
+                warn!(
+                    "Created {} of {} inference contexts: {}",
+                    contexts.len(),
+                    n,
+                    e
+                );
+                break;
+            }
+        }
+    }
+    info!("{} inference context(s) created", contexts.len());
+    contexts
+}
+
+/// The prompt format of the model at `path`: the one its manifest `entry`
+/// names, otherwise the one recognized from the model's own template.
+fn chat_template_for(entry: Option<&model::ModelEntry>, path: &Path) -> ChatTemplate {
+    entry
+        .and_then(|entry| entry.chat_template)
+        .or_else(|| {
+            let metadata = gguf::read_metadata(path).ok()?;
+            metadata.chat_template().and_then(ChatTemplate::detect)
+        })
+        .unwrap_or_default()
+}
+
+/// Pick the context size before any memory budget is applied.
+///
+/// An explicit request beyond the trained context is clamped to it, unless
+/// YaRN scaling is enabled, in which case the request is honored as-is.
+fn requested_context_size(requested: Option<u32>, n_ctx_train: u32, yarn: bool) -> u32 {
+    match requested {
+        Some(n_ctx) if yarn => n_ctx,
+        Some(n_ctx) => {
+            if n_ctx > n_ctx_train {
+                warn!(
+                    "Requested context {} exceeds trained context {}; clamping (use --yarn to extend)",
+                    n_ctx, n_ctx_train
+                );
+            }
+            n_ctx.min(n_ctx_train)
+        }
+        None => n_ctx_train.min(MAX_CTX),
+    }
+}
+
+/// Handle `tsuku-llm verify`.
+///
+/// Manifest models are checked against their SHA256 first; local files have
+/// no checksum to compare, so `--deep-verify` is the only check for them.
+async fn run_verify_command(args: VerifyArgs) -> Result<()> {
+    let local_path = PathBuf::from(&args.model);
+    let path = if local_path.is_file() {
+        local_path
+    } else {
+        let manifest = load_manifest()?;
+        let manager = models::ModelManager::with_manifest(models_dir(), manifest);
+        if !manager
+            .verify(&args.model)
+            .await
+            .context("Checksum verification failed")?
+        {
+            bail!(
+                "Model {} is missing or failed checksum verification",
+                args.model
+            );
+        }
+        println!("{}: checksum OK", args.model);
+        manager.model_path(&args.model)
+    };
+
+    if args.deep_verify {
+        let probe_path = path.clone();
+        tokio::task::spawn_blocking(move || verify::deep_verify(&probe_path))
+            .await
+            .context("Deep verification task panicked")?
+            .with_context(|| format!("Deep verification failed for {:?}", path))?;
+        println!("{}: deep verification OK", args.model);
+    }
+
+    Ok(())
+}
+
+/// Handle `tsuku-llm manifest` subcommands.
+async fn run_manifest_command(action: ManifestCommand) -> Result<()> {
+    match action {
+        ManifestCommand::Add { path } => {
+            let metadata = gguf::read_metadata(&path)
+                .with_context(|| format!("Failed to read GGUF metadata from {:?}", path))?;
+            let entry = models::local_manifest_entry(&path, &metadata).await?;
+
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            eprintln!(
+                "Architecture: {}, quantization: {}",
+                metadata.architecture().unwrap_or("unknown"),
+                entry.quantization
+            );
+            eprintln!("Replace download_url before adding this entry to the manifest.");
+
+            let json = serde_json::json!({ name: entry.to_json() });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            Ok(())
+        }
+    }
+}
+
+/// A manifest entry as shown by `tsuku-llm list-models`.
+#[derive(Debug, serde::Serialize)]
+struct ModelListing {
+    name: String,
+    quantization: String,
+    size_bytes: u64,
+    supported_backends: Vec<model::Backend>,
+    /// Downloaded, with checksums matching where the manifest has them
+    available: bool,
+}
+
+/// Describe every manifest entry, sorted by name.
+async fn list_models(manager: &models::ModelManager) -> Vec<ModelListing> {
+    let mut entries: Vec<_> = manager.manifest().models.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut listings = Vec::with_capacity(entries.len());
+    for (name, entry) in entries {
+        listings.push(ModelListing {
+            name: name.clone(),
+            quantization: entry.quantization.clone(),
+            size_bytes: manager.total_download_size(name),
+            supported_backends: entry.supported_backends.clone(),
+            available: manager.is_available(name).await,
+        });
+    }
+    listings
+}
+
+/// Handle `tsuku-llm list-models`.
+async fn run_list_models_command(args: ListModelsArgs) -> Result<()> {
+    let manifest = load_manifest()?;
+    let manager = models::ModelManager::with_manifest(models_dir(), manifest);
+    let listings = list_models(&manager).await;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&listings)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<32} {:<10} {:>9}  {:<28} AVAILABLE",
+        "NAME", "QUANT", "SIZE", "BACKENDS"
+    );
+    for listing in &listings {
+        let backends: Vec<String> = listing
+            .supported_backends
+            .iter()
+            .map(|b| b.to_string())
+            .collect();
+        println!(
+            "{:<32} {:<10} {:>6.1} GB  {:<28} {}",
+            listing.name,
+            listing.quantization,
+            listing.size_bytes as f64 / 1e9,
+            backends.join(","),
+            if listing.available { "yes" } else { "no" }
+        );
+    }
+    Ok(())
+}
+
+/// Model selection overrides from the environment (for testing).
+fn model_config_from_env() -> model::ModelConfig {
+    model::ModelConfig {
+        local_model: std::env::var("TSUKU_LLM_MODEL")
+            .ok()
+            .filter(|s| !s.is_empty()),
+        local_backend: std::env::var("TSUKU_LLM_BACKEND")
+            .ok()
+            .filter(|s| !s.is_empty()),
+        quantization: std::env::var("TSUKU_LLM_QUANTIZATION")
+            .ok()
+            .filter(|s| !s.is_empty()),
+    }
+}
+
+/// Download caps from the environment: TSUKU_LLM_DOWNLOAD_RATE in bytes per
+/// second, and TSUKU_LLM_MAX_DOWNLOADS models at a time. Unset or invalid
+/// values leave downloads uncapped.
+fn download_limits_from_env() -> models::DownloadLimits {
+    fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+        let value = std::env::var(name).ok().filter(|s| !s.is_empty())?;
+        let parsed = value.parse().ok();
+        if parsed.is_none() {
+            warn!("Ignoring invalid {}: {}", name, value);
+        }
+        parsed
+    }
+    models::DownloadLimits::new(
+        var("TSUKU_LLM_DOWNLOAD_RATE"),
+        var("TSUKU_LLM_MAX_DOWNLOADS"),
+    )
+}
+
+/// The model `tsuku-llm download` fetches: the one named, or the one `serve`
+/// would select on this hardware.
+fn download_target(
+    model: Option<String>,
+    selector: &model::ModelSelector,
+    profile: &hardware::HardwareProfile,
+) -> Result<String> {
+    match model {
+        Some(name) if selector.manifest().models.contains_key(&name) => Ok(name),
+        Some(name) => bail!("Model {} is not in the manifest", name),
+        None => Ok(selector
+            .select(profile)
+            .context("Model selection failed")?
+            .name),
+    }
+}
+
+/// One line of the download progress bar, e.g.
+/// `[=======>            ]  38% 1.8 GB / 4.7 GB at 31.0 MB/s, ETA 1m35s`.
+fn progress_bar(progress: &models::DownloadProgress, width: usize) -> String {
+    if progress.total_bytes == 0 {
+        return progress.to_string();
+    }
+    let fraction = (progress.bytes_downloaded as f64 / progress.total_bytes as f64).min(1.0);
+    let filled = (fraction * width as f64) as usize;
+    let bar = if filled < width {
+        format!("{}>{}", "=".repeat(filled), " ".repeat(width - filled - 1))
+    } else {
+        "=".repeat(width)
+    };
+    format!("[{}] {:>3}% {}", bar, (fraction * 100.0) as u32, progress)
+}
+
+/// Handle `tsuku-llm download`.
+async fn run_download_command(model: Option<String>) -> Result<()> {
+    let manifest = load_manifest().context("Failed to load model manifest")?;
+    let selector =
+        model::ModelSelector::with_manifest_and_config(manifest.clone(), model_config_from_env());
+    let name = download_target(model, &selector, &hardware::HardwareDetector::detect())?;
+
+    let manager = models::ModelManager::with_manifest(models_dir(), manifest)
+        .with_download_limits(download_limits_from_env())
+        .with_hf_auth(models::HfAuth::from_env());
+    if manager.is_available(&name).await {
+        println!("{}: already downloaded", name);
+        return Ok(());
+    }
+
+    // Redraw the bar in place; skip it when stderr isn't a terminal
+    let interactive = std::io::stderr().is_terminal();
+    let result = manager
+        .download(&name, |progress| {
+            if interactive {
+                eprint!("\r{}\x1b[K", progress_bar(&progress, 30));
+            }
+        })
+        .await;
+    if interactive {
+        eprintln!();
+    }
+
+    let path = result.with_context(|| format!("Failed to download {}", name))?;
+    println!("{}: downloaded to {:?}", name, path);
+    Ok(())
+}
+
+/// Handle `tsuku-llm prune`.
+async fn run_prune_command(args: PruneArgs) -> Result<()> {
+    let manifest = load_manifest().context("Failed to load model manifest")?;
+    let selector =
+        model::ModelSelector::with_manifest_and_config(manifest.clone(), model_config_from_env());
+
+    let mut keep = args.keep;
+    match selector.select(&hardware::HardwareDetector::detect()) {
+        Ok(spec) => keep.push(spec.name),
+        // Nothing on this machine would run, so there's no model to protect
+        Err(e) => warn!("No model selected ({}), keeping only --keep models", e),
+    }
+    let keep: Vec<&str> = keep.iter().map(String::as_str).collect();
+
+    let manager = models::ModelManager::with_manifest(models_dir(), manifest);
+    if args.dry_run {
+        for path in manager.prunable(&keep).await? {
+            println!("would delete {}", path.display());
+        }
+    } else {
+        for path in manager.prune(&keep).await? {
+            println!("deleted {}", path.display());
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
@@ -655,42 +2789,108 @@ async fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
+    check_tsuku_home(&tsuku_home())?;
 
     // Default to serve command if none specified
-    let idle_timeout = match cli.command {
-        Some(Commands::Serve { idle_timeout }) => idle_timeout,
-        None => Duration::from_secs(5 * 60), // Default 5 minutes
+    let serve_args = match cli.command {
+        Some(Commands::Serve(args)) => *args,
+        Some(Commands::Verify(args)) => return run_verify_command(args).await,
+        Some(Commands::Manifest { action }) => return run_manifest_command(action).await,
+        Some(Commands::ListModels(args)) => return run_list_models_command(args).await,
+        Some(Commands::Download { model }) => return run_download_command(model).await,
+        Some(Commands::Prune(args)) => return run_prune_command(args).await,
+        None => ServeArgs::default(),
     };
+    let idle_timeout = serve_args.idle_timeout;
+    let param_overrides = ParamOverrides::parse(&serve_args.llama_params)
+        .context("Invalid --llama-param override")?;
+    if !param_overrides.is_empty() {
+        info!(
+            "Applying {} llama.cpp parameter override(s)",
+            param_overrides.len()
+        );
+    }
+    let cpu_affinity = serve_args
+        .cpu_affinity
+        .as_deref()
+        .map(|spec| affinity::parse_cpu_list(spec, affinity::online_cores()))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid --cpu-affinity: {}", e))?;
+    serve_args.check_tcp_auth()?;
 
     info!("Idle timeout: {:?}", idle_timeout);
 
-    let socket = socket_path();
+    // An abstract socket has no file to manage
+    let socket = match &serve_args.abstract_socket {
+        Some(name) => {
+            info!("Starting tsuku-llm server at abstract socket @{}", name);
+            None
+        }
+        None => Some(socket_path()),
+    };
     let lock = lock_path();
-    info!("Starting tsuku-llm server at {:?}", socket);
+    if let Some(socket) = &socket {
+        info!("Starting tsuku-llm server at {:?}", socket);
+    }
 
     // Try to acquire the lock file first
     let _lock_file = acquire_lock(&lock)?;
 
-    // Set up SIGTERM handler EARLY - before any long-running operations like model download.
-    // This ensures we can catch SIGTERM during startup and clean up properly.
+    // Set up SIGTERM/SIGINT handlers EARLY - before any long-running operations like model
+    // download. This ensures we can catch them during startup and clean up properly.
     #[cfg(unix)]
-    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
-        .context("Failed to register SIGTERM handler")?;
-
-    // Now that we have the lock, remove stale socket if it exists
-    if socket.exists() {
-        std::fs::remove_file(&socket).context("Failed to remove stale socket")?;
-        info!("Removed stale socket file");
-    }
+    let mut signals = ShutdownSignals::register()?;
+    // Pause/resume signals would otherwise terminate the process
+    #[cfg(unix)]
+    let mut sigusr1 = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())
+        .context("Failed to register SIGUSR1 handler")?;
+    #[cfg(unix)]
+    let mut sigusr2 = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined2())
+        .context("Failed to register SIGUSR2 handler")?;
+
+    if let Some(socket) = &socket {
+        // Now that we have the lock, remove stale socket if it exists
+        if socket.exists() {
+            std::fs::remove_file(socket).context("Failed to remove stale socket")?;
+            info!("Removed stale socket file");
+        }
 
-    // Ensure parent directory exists
-    if let Some(parent) = socket.parent() {
-        std::fs::create_dir_all(parent).context("Failed to create socket directory")?;
+        // Ensure parent directory exists
+        if let Some(parent) = socket.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create socket directory")?;
+        }
     }
 
     // Create Unix listener
-    let listener = UnixListener::bind(&socket).context("Failed to bind Unix socket")?;
-    let stream = UnixListenerStream::new(listener);
+    let bound = match (&socket, &serve_args.abstract_socket) {
+        (Some(socket), _) => bind_socket(socket),
+        (None, Some(name)) => bind_abstract_socket(name),
+        (None, None) => unreachable!("a socket file is used unless an abstract name is set"),
+    };
+    let unix_listener = match bound {
+        Ok(listener) => listener,
+        Err(e) => {
+            cleanup_files(socket.as_deref(), &lock);
+            return Err(e);
+        }
+    };
+
+    // Optionally listen on TCP as well; there's no file to clean up for it,
+    // but the socket and lock must not outlive a failed bind.
+    let tcp_listener = match serve_args.tcp_addr {
+        Some(addr) => match TcpListener::bind(addr).await {
+            Ok(tcp) => {
+                info!("Also serving on TCP {}", addr);
+                Some(tcp)
+            }
+            Err(e) => {
+                cleanup_files(socket.as_deref(), &lock);
+                return Err(e).with_context(|| format!("Failed to bind TCP address {}", addr));
+            }
+        },
+        None => None,
+    };
+    let mut stream = std::pin::pin!(listener::incoming(unix_listener, tcp_listener));
 
     // Detect hardware
     let hardware_profile = hardware::HardwareDetector::detect();
@@ -701,123 +2901,209 @@ async fn main() -> Result<()> {
     );
 
     // Select and load model (env overrides for testing)
-    let model_config = model::ModelConfig {
-        local_model: std::env::var("TSUKU_LLM_MODEL").ok().filter(|s| !s.is_empty()),
-        local_backend: std::env::var("TSUKU_LLM_BACKEND").ok().filter(|s| !s.is_empty()),
-    };
-    let selector = model::ModelSelector::with_config(model_config);
+    let manifest = load_manifest().context("Failed to load model manifest")?;
+    let selector =
+        model::ModelSelector::with_manifest_and_config(manifest.clone(), model_config_from_env());
     let model_spec = selector
         .select(&hardware_profile)
         .context("Model selection failed")?;
-    let model_name = model_spec.name.clone();
+    let mut model_name = model_spec.name.clone();
     info!(
         "Selected model: {} (backend: {:?})",
         model_name, model_spec.backend
     );
 
+    // Refuse up front, before any download, if the model can't fit the budget
+    if let Some(budget_mb) = serve_args.max_memory_mb {
+        memory::check_model_fits(model_spec.size_bytes, budget_mb)
+            .context("Selected model exceeds the memory budget")?;
+    }
+
     // Get models directory
-    let models_dir = std::env::var("TSUKU_HOME")
-        .ok()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| {
-            dirs::home_dir()
-                .expect("Could not determine home directory")
-                .join(".tsuku")
-        })
-        .join("models");
+    let models_dir = models_dir();
 
-    // Ensure model is available - check for SIGTERM during download
-    let model_manager = models::ModelManager::new(models_dir.clone());
-    let model_path = model_manager.model_path(&model_name);
+    // Ensure model is available - check for SIGTERM/SIGINT during download
+    let model_manager = models::ModelManager::with_manifest(models_dir.clone(), manifest)
+        .with_download_limits(download_limits_from_env())
+        .with_hf_auth(models::HfAuth::from_env());
+    let mut model_path = model_manager.model_path(&model_name);
+    let mut degraded = false;
 
     if !model_manager.is_available(&model_name).await {
         info!("Model not found locally, downloading...");
-        let download_future = model_manager.download(&model_name, |progress| {
-            info!("Download progress: {} bytes", progress.bytes_downloaded);
+
+        let downloaded = download_during_startup(
+            &model_manager,
+            &model_name,
+            stream.as_mut(),
+            serve_args.tcp_token.clone(),
+            &mut signals,
+            socket.as_deref(),
+            &lock,
+        )
+        .await;
+        if let Err(e) = downloaded {
+            model_path = fallback_model(e, serve_args.fallback_model.as_deref())?;
+            model_name = model_path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            degraded = true;
+        }
+    }
+
+    // Load the model and create the first context, stepping down to a
+    // smaller model once if the context doesn't fit
+    let mut downgraded = false;
+    let (model, load_time, chat_template, n_ctx, context_params, mut context) = loop {
+        info!("Loading model from {:?}", model_path);
+
+        // Load model (blocking operation, run in spawn_blocking)
+        // Check for SIGTERM/SIGINT during model loading
+        let model_params = serve_args.model_params(param_overrides.clone());
+        let load_start = std::time::Instant::now();
+        let load_future = tokio::task::spawn_blocking({
+            let path = model_path.clone();
+            move || LlamaModel::load_from_file(&path, model_params)
         });
 
-        tokio::select! {
-            result = download_future => {
-                result.context("Failed to download model")?;
+        let model = tokio::select! {
+            result = load_future => {
+                match result.context("Model loading task panicked")? {
+                    Ok(m) => m,
+                    Err(e) => {
+                        // Model loading failed -- this is where a compiled-in GPU backend
+                        // fails to initialize (e.g., Vulkan loader missing, CUDA driver
+                        // incompatible). Write a structured error to stderr.
+                        let compiled = hardware::compiled_backend();
+                        let error_msg = hardware::format_backend_init_error(
+                            compiled, &hardware_profile,
+                        );
+                        eprintln!("{}", error_msg);
+                        error!("Model loading failed: {}", e);
+                        cleanup_files(socket.as_deref(), &lock);
+                        std::process::exit(1);
+                    }
+                }
             }
-            _ = sigterm.recv() => {
-                info!("SIGTERM received during model download, cleaning up");
-                cleanup_files(&socket, &lock);
-                info!("Server shutdown complete (reason: SIGTERM during startup)");
+            signal = signals.recv() => {
+                info!("{} received during model loading, cleaning up", signal);
+                cleanup_files(socket.as_deref(), &lock);
+                info!("Server shutdown complete (reason: {} during startup)", signal);
                 std::process::exit(0);
             }
-        }
-    }
+        };
 
-    info!("Loading model from {:?}", model_path);
+        let model = Arc::new(model);
+        let load_time = load_start.elapsed();
+        info!("Model loaded successfully in {:?}", load_time);
+
+        // The manifest doesn't describe the fallback model
+        let entry = selector.manifest().get(&model_name).filter(|_| !degraded);
+        let chat_template = chat_template_for(entry, &model_path);
+        info!("Using {:?} chat template", chat_template);
+
+        // Create inference context with a VRAM-aware context window.
+        let context_params = match serve_args.fitted_context_params(&model, param_overrides.clone())
+        {
+            Ok(params) => params,
+            Err(e) => {
+                eprintln!("ERROR: {}", e);
+                error!("Memory budget exceeded: {}", e);
+                cleanup_files(socket.as_deref(), &lock);
+                std::process::exit(1);
+            }
+        };
+        let n_ctx = context_params.n_ctx;
 
-    // Load model (blocking operation, run in spawn_blocking)
-    // Check for SIGTERM during model loading
-    let model_params = ModelParams::for_gpu();
-    let load_future = tokio::task::spawn_blocking({
-        let path = model_path.clone();
-        move || LlamaModel::load_from_file(&path, model_params)
-    });
+        // Pin threads before the context spawns its compute threads
+        if let Some(cores) = &cpu_affinity {
+            match affinity::set_cpu_affinity(cores) {
+                Ok(()) => info!("Pinned to CPU cores {:?}", cores),
+                Err(e) => warn!("Failed to set CPU affinity: {}", e),
+            }
+        }
 
-    let model = tokio::select! {
-        result = load_future => {
-            match result.context("Model loading task panicked")? {
-                Ok(m) => m,
-                Err(e) => {
-                    // Model loading failed -- this is where a compiled-in GPU backend
-                    // fails to initialize (e.g., Vulkan loader missing, CUDA driver
-                    // incompatible). Write a structured error to stderr.
-                    let compiled = hardware::compiled_backend();
-                    let error_msg = hardware::format_backend_init_error(
-                        compiled, &hardware_profile,
+        match LlamaContext::new(model.clone(), context_params.clone()) {
+            Ok(ctx) => break (model, load_time, chat_template, n_ctx, context_params, ctx),
+            Err(e) => {
+                // Near the VRAM threshold the KV cache may not fit next to the
+                // model; step down to the next smaller model once
+                let smaller = (!downgraded && !degraded)
+                    .then(|| selector.select_smaller(&model_name, &hardware_profile))
+                    .flatten();
+                if let Some(smaller) = smaller {
+                    warn!(
+                        "Context creation failed for {}: {}; falling back to {}",
+                        model_name, e, smaller
                     );
-                    eprintln!("{}", error_msg);
-                    error!("Model loading failed: {}", e);
-                    cleanup_files(&socket, &lock);
-                    std::process::exit(1);
+                    drop(model);
+                    if !model_manager.is_available(&smaller).await {
+                        info!("Model not found locally, downloading...");
+                        let downloaded = download_during_startup(
+                            &model_manager,
+                            &smaller,
+                            stream.as_mut(),
+                            serve_args.tcp_token.clone(),
+                            &mut signals,
+                            socket.as_deref(),
+                            &lock,
+                        )
+                        .await;
+                        if let Err(e) = downloaded {
+                            error!("Failed to download {}: {}", smaller, e);
+                            cleanup_files(socket.as_deref(), &lock);
+                            std::process::exit(1);
+                        }
+                    }
+                    model_path = model_manager.model_path(&smaller);
+                    model_name = smaller;
+                    downgraded = true;
+                    continue;
                 }
+
+                // Context creation can also fail when the GPU backend can't initialize
+                // (e.g., insufficient VRAM, unsupported GPU API version).
+                let compiled = hardware::compiled_backend();
+                let error_msg = hardware::format_backend_init_error(compiled, &hardware_profile);
+                eprintln!("{}", error_msg);
+                error!("Context creation failed: {}", e);
+                cleanup_files(socket.as_deref(), &lock);
+                std::process::exit(1);
             }
         }
-        _ = sigterm.recv() => {
-            info!("SIGTERM received during model loading, cleaning up");
-            cleanup_files(&socket, &lock);
-            info!("Server shutdown complete (reason: SIGTERM during startup)");
-            std::process::exit(0);
-        }
     };
 
-    let model = Arc::new(model);
-    info!("Model loaded successfully");
-
-    // Create inference context with a VRAM-aware context window.
-    // Recipe generation prompts can reach ~27K tokens. We cap context size
-    // rather than using n_ctx_train() because larger models have huge training
-    // contexts (14B has 128K) that would exhaust GPU VRAM on KV cache alone.
-    //
-    // Context budget: 14B Q4_K_M model = 8.1 GB, KV cache = ~0.19 GB/K tokens.
-    // On a 16 GB GPU (~13.7 GB free), 32K context would need 6 GB KV = 14.1 GB total (OOM).
-    // 24K context needs 4.5 GB KV = 12.6 GB total (fits with headroom).
-    const MAX_CTX: u32 = 24576;
-    let n_ctx = model.n_ctx_train().min(MAX_CTX);
-    let context_params = ContextParams {
-        n_ctx,
-        n_batch: n_ctx,
-        ..Default::default()
-    };
-    let context = match LlamaContext::new(model.clone(), context_params) {
-        Ok(ctx) => ctx,
-        Err(e) => {
-            // Context creation can also fail when the GPU backend can't initialize
-            // (e.g., insufficient VRAM, unsupported GPU API version).
-            let compiled = hardware::compiled_backend();
-            let error_msg = hardware::format_backend_init_error(compiled, &hardware_profile);
-            eprintln!("{}", error_msg);
-            error!("Context creation failed: {}", e);
-            cleanup_files(&socket, &lock);
-            std::process::exit(1);
-        }
+    // Kernels are compiled per process, so warming one context is enough
+    let warmup_tokens = serve_args.warmup_tokens(n_ctx);
+    let warmup_start = std::time::Instant::now();
+    match warmup(&mut context, warmup_tokens) {
+        Ok(()) if warmup_tokens > 0 => info!(
+            "Warmup decoded {} tokens in {:?}",
+            warmup_tokens,
+            warmup_start.elapsed()
+        ),
+        Ok(()) => {}
+        Err(e) => warn!("Warmup decode failed: {}", e),
+    }
+
+    // Each extra context lets one more completion run concurrently, at the
+    // cost of another KV cache.
+    let n_contexts = context_count(&serve_args, &hardware_profile, &model, n_ctx);
+    let contexts = create_contexts(context, &model, &context_params, n_contexts);
+
+    // The fallback model isn't in the manifest, so it's described by its file
+    let (model_size_bytes, quantization) = if degraded {
+        let size = std::fs::metadata(&model_path).map_or(0, |m| m.len());
+        (size, String::new())
+    } else {
+        let quantization = selector
+            .manifest()
+            .get(&model_name)
+            .map(|entry| entry.quantization.clone())
+            .unwrap_or_default();
+        (model_manager.size_on_disk(&model_name), quantization)
     };
-    info!("Inference context created");
 
     // Create shutdown channel
     let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
@@ -825,6 +3111,14 @@ async fn main() -> Result<()> {
     // Create activity channel for idle timeout reset
     let (activity_tx, mut activity_rx) = mpsc::channel::<()>(16);
 
+    let reloader = Reloader {
+        serve_args: serve_args.clone(),
+        param_overrides,
+        selector,
+        model_manager,
+        hardware_profile: hardware_profile.clone(),
+    };
+
     // Create the server
     let server = LlmServer::new(
         model_name,
@@ -832,62 +3126,85 @@ async fn main() -> Result<()> {
         shutdown_tx.clone(),
         activity_tx,
         model,
-        context,
-    );
+        contexts,
+        chat_template,
+    )
+    .with_sampling_preset(serve_args.sampling_preset)
+    .with_request_limits(RequestLimits {
+        max_messages: serve_args.max_messages,
+        max_tools: serve_args.max_tools,
+        max_samples: serve_args.max_samples,
+    })
+    .with_degraded(degraded)
+    .with_status_resets_idle(serve_args.status_resets_idle)
+    .with_trace_next(serve_args.trace_next.clone())
+    .with_model_file(model_size_bytes, quantization)
+    .with_model_load_time(load_time)
+    .with_reloader(reloader);
     let shutting_down = server.shutting_down();
+    let paused = server.paused();
     let in_flight = server.in_flight();
 
+    // SIGUSR1 pauses the server for maintenance, SIGUSR2 resumes it
+    #[cfg(unix)]
+    tokio::spawn({
+        let paused = paused.clone();
+        async move {
+            loop {
+                tokio::select! {
+                    _ = sigusr1.recv() => {
+                        info!("SIGUSR1 received, pausing (inference requests will be rejected)");
+                        paused.store(true, Ordering::SeqCst);
+                    }
+                    _ = sigusr2.recv() => {
+                        info!("SIGUSR2 received, resuming");
+                        paused.store(false, Ordering::SeqCst);
+                    }
+                }
+            }
+        }
+    });
+
     info!("Server ready, waiting for connections...");
 
     // Run the server with graceful shutdown
     let server_future = tonic::transport::Server::builder()
-        .add_service(InferenceServiceServer::new(server))
+        .add_service(InferenceServiceServer::with_interceptor(
+            server,
+            listener::TcpAuth::new(serve_args.tcp_token),
+        ))
         .serve_with_incoming_shutdown(stream, async {
             shutdown_rx.recv().await;
             info!("Shutdown signal received");
         });
 
     // Main event loop with activity-based idle timeout.
-    // The idle timeout resets whenever there's activity (request starts).
-    let shutdown_reason: &str;
-    let mut idle_deadline = tokio::time::Instant::now() + idle_timeout;
-
-    // Pin the server future so we can poll it in a loop
-    tokio::pin!(server_future);
-
-    loop {
-        tokio::select! {
-            result = &mut server_future => {
-                result.context("Server error")?;
-                shutdown_reason = "server stopped";
-                break;
-            }
-            _ = tokio::time::sleep_until(idle_deadline) => {
-                info!("Idle timeout reached, initiating shutdown");
-                shutdown_reason = "idle timeout";
-                break;
-            }
-            _ = sigterm.recv() => {
-                info!("SIGTERM received, initiating graceful shutdown");
-                shutdown_reason = "SIGTERM";
-                break;
-            }
-            _ = activity_rx.recv() => {
-                // Activity received, reset the idle deadline
-                idle_deadline = tokio::time::Instant::now() + idle_timeout;
-            }
+    // The idle timeout resets whenever there's activity (a request starts or
+    // a stream chunk is delivered).
+    let shutdown_reason = tokio::select! {
+        result = server_future => {
+            result.context("Server error")?;
+            "server stopped"
         }
-    }
+        _ = wait_for_idle(&mut activity_rx, idle_timeout, &paused) => {
+            info!("Idle timeout reached, initiating shutdown");
+            "idle timeout"
+        }
+        signal = signals.recv() => {
+            info!("{} received, initiating graceful shutdown", signal);
+            signal
+        }
+    };
 
     // Mark server as shutting down
     shutting_down.store(true, Ordering::SeqCst);
 
     // Wait for in-flight requests with grace period
-    // Pass sigterm so we can detect a second signal during grace period
-    let _interrupted = wait_for_in_flight(&in_flight, SHUTDOWN_GRACE_PERIOD, &mut sigterm).await;
+    // Pass the signals so we can detect a second one during grace period
+    let _interrupted = wait_for_in_flight(&in_flight, SHUTDOWN_GRACE_PERIOD, &mut signals).await;
 
     // Clean up files
-    cleanup_files(&socket, &lock);
+    cleanup_files(socket.as_deref(), &lock);
 
     info!("Server shutdown complete (reason: {})", shutdown_reason);
 
@@ -923,6 +3240,17 @@ mod scopeguard {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use generation::testing::MockContext;
+
+    fn test_config() -> GenerationConfig {
+        GenerationConfig {
+            max_tokens: 100,
+            timeout: Duration::from_secs(60),
+            length_penalty: 0.0,
+            logit_bias: Vec::new(),
+            repetition_penalty: 1.0,
+        }
+    }
 
     #[test]
     fn test_parse_duration_seconds() {
@@ -948,6 +3276,54 @@ mod tests {
         assert_eq!(parse_duration("2m30s").unwrap(), Duration::from_secs(150));
     }
 
+    #[test]
+    fn test_parse_duration_days_and_weeks() {
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86_400));
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(1_209_600)
+        );
+        assert_eq!(
+            parse_duration("1d6h").unwrap(),
+            Duration::from_secs(108_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        let err = parse_duration("99999999999999d").unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+        assert!(parse_duration("99999999999999w").is_err());
+        assert!(parse_duration("10000000000000000000s").is_err());
+        assert!(parse_duration("18446744073709551615s1s").is_err());
+
+        assert_eq!(parse_duration("36500d").unwrap(), MAX_DURATION);
+        assert!(parse_duration("36500d1ns").is_err());
+    }
+
+    #[test]
+    fn test_deadline_after_huge_timeout() {
+        // Too far off for an Instant, so capped instead of panicking
+        let before = tokio::time::Instant::now();
+        assert!(deadline_after(Duration::MAX) >= before + MAX_DURATION);
+    }
+
+    #[test]
+    fn test_parse_duration_sub_second() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(
+            parse_duration("1500ms").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(parse_duration("750ms").unwrap(), Duration::from_millis(750));
+        assert_eq!(
+            parse_duration("1s750ms").unwrap(),
+            Duration::from_millis(1750)
+        );
+        assert_eq!(parse_duration("250us").unwrap(), Duration::from_micros(250));
+        assert_eq!(parse_duration("10ns").unwrap(), Duration::from_nanos(10));
+    }
+
     #[test]
     fn test_parse_duration_bare_number() {
         // Bare number treated as seconds
@@ -960,4 +3336,1462 @@ mod tests {
         assert!(parse_duration("0s").is_err()); // Zero duration not allowed
         assert!(parse_duration("abc").is_err());
     }
+
+    #[test]
+    fn test_check_tsuku_home_creates_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("nested").join(".tsuku");
+        check_tsuku_home(&home).unwrap();
+        assert!(home.is_dir());
+        assert_eq!(std::fs::read_dir(&home).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_check_tsuku_home_rejects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        std::fs::write(&home, b"").unwrap();
+        let err = check_tsuku_home(&home).unwrap_err();
+        assert!(err.to_string().contains("is not a directory"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_tsuku_home_rejects_uncreatable_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file");
+        std::fs::write(&file, b"").unwrap();
+        let err = check_tsuku_home(&file.join(".tsuku")).unwrap_err();
+        assert!(err.to_string().contains("can't be created"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_list_models_reports_downloaded_models() {
+        let entry = |url: &str| model::ModelEntry {
+            quantization: "q4_k_m".to_string(),
+            size_bytes: 5,
+            sha256: String::new(),
+            download_url: url.to_string(),
+            split_count: 1,
+            part_sha256: Vec::new(),
+            part_size_bytes: Vec::new(),
+            mirror_urls: Vec::new(),
+            supported_backends: vec![model::Backend::Cuda, model::Backend::Vulkan],
+            chat_template: None,
+        };
+        let manifest = model::ModelManifest {
+            models: [
+                ("fetched", entry("https://example.com/fetched.gguf")),
+                ("missing", entry("https://example.com/missing.gguf")),
+            ]
+            .into_iter()
+            .map(|(name, entry)| (name.to_string(), entry))
+            .collect(),
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = models::ModelManager::with_manifest(dir.path().to_path_buf(), manifest);
+        std::fs::write(manager.model_path("fetched"), b"hello").unwrap();
+
+        let listings = list_models(&manager).await;
+        let available: Vec<_> = listings
+            .iter()
+            .map(|l| (l.name.as_str(), l.available))
+            .collect();
+        assert_eq!(available, vec![("fetched", true), ("missing", false)]);
+        assert_eq!(
+            listings[0].supported_backends,
+            vec![model::Backend::Cuda, model::Backend::Vulkan]
+        );
+
+        let json = serde_json::to_value(&listings).unwrap();
+        assert_eq!(
+            json[0]["supported_backends"],
+            serde_json::json!(["cuda", "vulkan"])
+        );
+        assert_eq!(json[1]["available"], false);
+    }
+
+    fn download_error() -> models::ModelError {
+        models::ModelError::NotInManifest("gone".to_string())
+    }
+
+    #[test]
+    fn test_fallback_model_used_when_download_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let fallback = dir.path().join("tiny.gguf");
+        std::fs::write(&fallback, b"GGUF").unwrap();
+
+        let path = fallback_model(download_error(), Some(&fallback)).unwrap();
+        assert_eq!(path, fallback);
+    }
+
+    #[test]
+    fn test_fallback_model_missing_keeps_download_error() {
+        let err = fallback_model(download_error(), None).unwrap_err();
+        assert!(
+            format!("{:#}", err).starts_with("Failed to download model"),
+            "{:#}",
+            err
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("tiny.gguf");
+        let err = fallback_model(download_error(), Some(&missing)).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("not found"), "{}", message);
+        assert!(message.contains("Failed to download model"), "{}", message);
+    }
+
+    #[test]
+    fn test_download_target_explicit_and_selected() {
+        let selector = model::ModelSelector::with_manifest_and_config(
+            model::ModelManifest::default(),
+            model::ModelConfig::default(),
+        );
+        let profile = hardware::HardwareProfile {
+            gpu_backend: hardware::GpuBackend::Cuda,
+            vram_bytes: 24 * 1024 * 1024 * 1024,
+            gpu_name: None,
+            ram_bytes: 32 * 1024 * 1024 * 1024,
+            cpu_features: Default::default(),
+        };
+
+        let name = download_target(
+            Some("qwen2.5-7b-instruct-q4".to_string()),
+            &selector,
+            &profile,
+        );
+        assert_eq!(name.unwrap(), "qwen2.5-7b-instruct-q4");
+        assert!(download_target(Some("nope".to_string()), &selector, &profile).is_err());
+
+        let selected = selector.select(&profile).unwrap().name;
+        assert_eq!(
+            download_target(None, &selector, &profile).unwrap(),
+            selected
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let progress = models::DownloadProgress {
+            bytes_downloaded: 250,
+            total_bytes: 1000,
+            bytes_per_second: 50.0,
+            eta_seconds: Some(15),
+            part: None,
+        };
+        assert_eq!(
+            progress_bar(&progress, 8),
+            "[==>     ]  25% 250 B / 1000 B at 50 B/s, ETA 15s"
+        );
+
+        let done = models::DownloadProgress {
+            bytes_downloaded: 1000,
+            eta_seconds: Some(0),
+            ..progress
+        };
+        assert!(progress_bar(&done, 8).starts_with("[========] 100% "));
+    }
+
+    #[test]
+    fn test_serve_args_default_matches_flag_defaults() {
+        let args = ServeArgs::default();
+        assert_eq!(args.idle_timeout, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_serve_args_max_memory_flag() {
+        let cli = Cli::parse_from(["tsuku-llm", "serve", "--max-memory-mb", "12000"]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(args.max_memory_mb, Some(12000));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_abstract_socket_name_is_exclusive() {
+        let name = format!("tsuku-llm-test-{}", std::process::id());
+        let first = bind_abstract_socket(&name).unwrap();
+        assert!(bind_abstract_socket(&name).is_err());
+
+        // The name is free again once the first instance is gone
+        drop(first);
+        assert!(bind_abstract_socket(&name).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_socket_and_lock_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let socket = dir.path().join("llm.sock");
+        let _listener = bind_socket(&socket).unwrap();
+        assert_eq!(mode(&socket), 0o600);
+        std::os::unix::net::UnixStream::connect(&socket).unwrap();
+
+        // The private directory used while binding is gone
+        let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+
+        let lock = dir.path().join("llm.sock.lock");
+        std::fs::write(&lock, b"").unwrap();
+        std::fs::set_permissions(&lock, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let _lock_file = acquire_lock(&lock).unwrap();
+        assert_eq!(mode(&lock), 0o600);
+    }
+
+    #[test]
+    fn test_serve_args_listen_alias() {
+        let cli = Cli::parse_from(["tsuku-llm", "serve", "--listen", "0.0.0.0:50051"]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(args.tcp_addr, Some("0.0.0.0:50051".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_serve_args_tcp_needs_token_off_loopback() {
+        let serve_args = |extra: &[&str]| {
+            let cli = Cli::parse_from(["tsuku-llm", "serve"].iter().chain(extra));
+            let Some(Commands::Serve(args)) = cli.command else {
+                panic!("expected serve command");
+            };
+            args
+        };
+        assert!(serve_args(&["--tcp-addr", "0.0.0.0:50051"])
+            .check_tcp_auth()
+            .is_err());
+        assert!(
+            serve_args(&["--tcp-addr", "0.0.0.0:50051", "--tcp-token", "s3cret"])
+                .check_tcp_auth()
+                .is_ok()
+        );
+        assert!(serve_args(&["--tcp-addr", "127.0.0.1:50051"])
+            .check_tcp_auth()
+            .is_ok());
+        assert!(serve_args(&[]).check_tcp_auth().is_ok());
+    }
+
+    #[test]
+    fn test_serve_args_gpu_layers_and_threads() {
+        let cli = Cli::parse_from(["tsuku-llm", "serve", "--gpu-layers", "20", "--threads", "6"]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(
+            args.model_params(ParamOverrides::default()).n_gpu_layers,
+            20
+        );
+        let params = args.context_params(4096, ParamOverrides::default());
+        assert_eq!((params.n_ctx, params.n_batch), (4096, 4096));
+        assert_eq!((params.n_threads, params.n_threads_batch), (6, 6));
+
+        let defaults = ServeArgs::default();
+        assert_eq!(
+            defaults
+                .model_params(ParamOverrides::default())
+                .n_gpu_layers,
+            -1
+        );
+        let params = defaults.context_params(4096, ParamOverrides::default());
+        assert_eq!(params.n_threads, ContextParams::default().n_threads);
+
+        assert!(Cli::try_parse_from(["tsuku-llm", "serve", "--threads", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_serve_args_flash_attn_and_kv_cache_type() {
+        let cli = Cli::parse_from([
+            "tsuku-llm",
+            "serve",
+            "--flash-attn",
+            "--kv-cache-type",
+            "q8_0",
+        ]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        let raw = args
+            .context_params(4096, ParamOverrides::default())
+            .into_raw();
+        assert_eq!(raw.flash_attn_type, 1);
+        assert_eq!((raw.type_k, raw.type_v), (8, 8));
+
+        // Without the flags, llama.cpp's defaults stand
+        let raw = ServeArgs::default()
+            .context_params(4096, ParamOverrides::default())
+            .into_raw();
+        let defaults = ContextParams::default().into_raw();
+        assert_eq!(raw.flash_attn_type, defaults.flash_attn_type);
+        assert_eq!((raw.type_k, raw.type_v), (1, 1));
+
+        // A quantized V cache needs flash attention
+        assert!(Cli::try_parse_from(["tsuku-llm", "serve", "--kv-cache-type", "q4_0"]).is_err());
+        assert!(Cli::try_parse_from(["tsuku-llm", "serve", "--kv-cache-type", "f16"]).is_ok());
+    }
+
+    #[test]
+    fn test_check_logit_bias_rejects_non_finite() {
+        let request = |bias: f32| CompletionRequest {
+            logit_bias: [(5, bias)].into(),
+            ..Default::default()
+        };
+        assert!(check_logit_bias(&request(-100.0)).is_none());
+        for bias in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            let status = check_logit_bias(&request(bias)).unwrap();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_request_limits() {
+        let limits = RequestLimits {
+            max_messages: 3,
+            max_tools: 2,
+            max_samples: 4,
+        };
+        let request = |messages: usize, tools: usize| CompletionRequest {
+            messages: vec![proto::Message::default(); messages],
+            tools: vec![proto::ToolDef::default(); tools],
+            ..Default::default()
+        };
+
+        assert!(limits.check(&request(3, 2)).is_none());
+
+        let status = limits.check(&request(4, 0)).unwrap();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(
+            status.message().contains("messages"),
+            "{}",
+            status.message()
+        );
+
+        let status = limits.check(&request(1, 3)).unwrap();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("tools"), "{}", status.message());
+
+        let samples = |n: u32| CompletionRequest { n, ..request(1, 0) };
+        assert!(limits.check(&samples(4)).is_none());
+        let status = limits.check(&samples(5)).unwrap();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(
+            status.message().contains("completions"),
+            "{}",
+            status.message()
+        );
+    }
+
+    #[test]
+    fn test_greedy_samples_warning() {
+        let greedy = Sampler::greedy();
+        assert_eq!(samples_warning(1, &greedy), None);
+        let warning = samples_warning(3, &greedy).unwrap();
+        assert!(warning.contains("identical"), "{}", warning);
+
+        assert_eq!(samples_warning(3, &Sampler::with_temperature(0.8)), None);
+    }
+
+    #[test]
+    fn test_requested_context_size_defaults_to_capped_trained() {
+        assert_eq!(requested_context_size(None, 131072, false), MAX_CTX);
+        assert_eq!(requested_context_size(None, 8192, false), 8192);
+    }
+
+    #[test]
+    fn test_requested_context_size_clamps_without_yarn() {
+        assert_eq!(requested_context_size(Some(65536), 32768, false), 32768);
+    }
+
+    #[test]
+    fn test_requested_context_size_beyond_trained_enables_yarn() {
+        let n_ctx = requested_context_size(Some(65536), 32768, true);
+        assert_eq!(n_ctx, 65536);
+
+        let params = ContextParams::with_context_size(n_ctx).with_yarn_scaling(32768);
+        assert_eq!(params.rope_scaling, llama::RopeScaling::Yarn);
+        assert_eq!(params.rope_freq_scale, 0.5);
+    }
+
+    #[test]
+    fn test_rope_params_reach_raw_params() {
+        let raw = ContextParams {
+            rope_scaling: llama::RopeScaling::Linear,
+            rope_freq_base: 1_000_000.0,
+            rope_freq_scale: 0.25,
+            ..Default::default()
+        }
+        .into_raw();
+        assert_eq!(raw.rope_scaling_type, 1);
+        assert_eq!(
+            (raw.rope_freq_base, raw.rope_freq_scale),
+            (1_000_000.0, 0.25)
+        );
+
+        // Unset, the model's trained values apply
+        let raw = ContextParams::default().into_raw();
+        assert_eq!(raw.rope_scaling_type, -1);
+        assert_eq!((raw.rope_freq_base, raw.rope_freq_scale), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_serve_args_llama_param_repeatable() {
+        let cli = Cli::parse_from([
+            "tsuku-llm",
+            "serve",
+            "--llama-param",
+            "rope_freq_base=1e6",
+            "--llama-param",
+            "yarn_ext_factor=1.0,n_ubatch=256",
+        ]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(
+            args.llama_params,
+            vec!["rope_freq_base=1e6", "yarn_ext_factor=1.0", "n_ubatch=256"]
+        );
+    }
+
+    #[test]
+    fn test_stream_completion_emits_deltas_then_terminal_chunk() {
+        let mut ctx = MockContext::new(vec![5, 6], 63);
+        let mut chunks = Vec::new();
+        stream_completion(
+            &mut ctx,
+            &Sampler::greedy(),
+            3,
+            &test_config(),
+            &CompletionRequest::default(),
+            |c| {
+                chunks.push(c);
+                true
+            },
+        );
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].content, "<5>");
+        assert_eq!(chunks[1].content, "<6>");
+        assert_eq!(chunks[2].content, "");
+        assert_eq!(chunks[2].stop_reason, "eos");
+        assert!(chunks[2].error.is_empty());
+        let usage = chunks[2].usage.unwrap();
+        assert_eq!(usage.input_tokens, 3);
+        assert_eq!(usage.output_tokens, 2);
+    }
+
+    #[test]
+    fn test_stream_completion_mid_stream_decode_error_ends_stream() {
+        let mut ctx = MockContext::new(vec![5, 6, 7, 8], 63);
+        ctx.fail_decode_at = Some(3);
+        let mut chunks = Vec::new();
+        stream_completion(
+            &mut ctx,
+            &Sampler::greedy(),
+            3,
+            &test_config(),
+            &CompletionRequest::default(),
+            |c| {
+                chunks.push(c);
+                true
+            },
+        );
+
+        // Three deltas went out before the failing decode.
+        let terminal = chunks.last().unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(terminal.stop_reason, "decode_error");
+        assert!(terminal.error.contains("Decode failed"));
+        assert_eq!(terminal.usage.unwrap().output_tokens, 3);
+    }
+
+    #[test]
+    fn test_completion_keeps_partial_output_on_decode_error() {
+        let mut ctx = MockContext::new(vec![5, 6, 7, 8, 9, 10], 63);
+        // The decode of the 4th token fails, before the 5th can be sampled
+        ctx.fail_decode_at = Some(4);
+        let generation =
+            generation::generate(&mut ctx, &Sampler::greedy(), 3, &test_config(), |_, _| true);
+
+        let response =
+            completion_response(&ctx, &generation, 3, &CompletionRequest::default()).unwrap();
+        assert_eq!(response.content, "<5><6><7><8>");
+        assert_eq!(response.stop_reason, "decode_error");
+        assert!(response.error.contains("Decode failed"));
+        assert_eq!(response.usage.unwrap().output_tokens, 4);
+    }
+
+    #[test]
+    fn test_samples_stop_independently() {
+        let mut ctx = MockContext::new(vec![9, 5, 6, 7, 8], 63);
+        // The second sample ends after one token
+        ctx.branches = vec![vec![9, 5]];
+        let prompt = [1, 2, 3];
+        ctx.decode(&prompt, 0).unwrap();
+        let config = GenerationConfig {
+            max_tokens: 3,
+            ..test_config()
+        };
+        let generations = generation::generate_samples(
+            &mut ctx,
+            &Sampler::greedy(),
+            &prompt,
+            2,
+            &config,
+            |_, _| true,
+        );
+
+        let req = CompletionRequest {
+            n: 2,
+            ..Default::default()
+        };
+        let response = samples_response(&ctx, &generations, 3, &req).unwrap();
+        assert_eq!(response.completions.len(), 2);
+        assert_eq!(response.completions[0].content, "<5><6><7>");
+        assert_eq!(response.completions[0].stop_reason, "max_tokens");
+        assert_eq!(response.completions[1].content, "<5>");
+        assert_eq!(response.completions[1].stop_reason, "eos");
+        assert_eq!(response.content, response.completions[0].content);
+        assert_eq!(response.usage.unwrap().output_tokens, 4);
+    }
+
+    #[test]
+    fn test_prefill_starts_the_content() {
+        let prefill = "{\"name\":";
+        let req = CompletionRequest {
+            assistant_prefill: prefill.to_string(),
+            ..Default::default()
+        };
+        let mut ctx = MockContext::new(vec![5, 6], 63);
+        let generation =
+            generation::generate(&mut ctx, &Sampler::greedy(), 3, &test_config(), |_, _| true);
+        let response = completion_response(&ctx, &generation, 3, &req).unwrap();
+        assert_eq!(response.content, "{\"name\":<5><6>");
+
+        let mut ctx = MockContext::new(vec![5, 6], 63);
+        let mut chunks = Vec::new();
+        stream_completion(&mut ctx, &Sampler::greedy(), 3, &test_config(), &req, |c| {
+            chunks.push(c);
+            true
+        });
+        assert_eq!(chunks[0].content, prefill);
+        assert_eq!(chunks[1].content, "<5>");
+        assert_eq!(chunks.last().unwrap().stop_reason, "eos");
+    }
+
+    #[test]
+    fn test_prefill_is_kept_apart_from_the_template_markup() {
+        let req = CompletionRequest {
+            assistant_prefill: "<|im_end|>".to_string(),
+            messages: vec![proto::Message {
+                role: proto::Role::User as i32,
+                content: "hi".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let prompt = build_prompt(ChatTemplate::ChatMl, &req, &req.messages);
+        assert!(prompt.suffix.ends_with("<|im_start|>assistant\n"));
+        assert_eq!(prompt.prefill, "<|im_end|>");
+    }
+
+    fn overflowing_request() -> CompletionRequest {
+        let message = |role: proto::Role, content: String| proto::Message {
+            role: role as i32,
+            content,
+            ..Default::default()
+        };
+        CompletionRequest {
+            system_prompt: "SYSTEM".to_string(),
+            messages: vec![
+                message(proto::Role::User, "old question ".repeat(20)),
+                message(proto::Role::Assistant, "old answer ".repeat(20)),
+                message(proto::Role::User, "latest question".to_string()),
+                message(proto::Role::Tool, "tool output".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    /// Counts characters as tokens.
+    fn prompt_chars(prompt: &Prompt) -> usize {
+        prompt.prefix.len() + prompt.suffix.len() + prompt.prefill.len()
+    }
+
+    #[test]
+    fn test_fit_prompt_drops_oldest_messages() {
+        let mut req = overflowing_request();
+        let (prompt, truncated) = fit_prompt(ChatTemplate::ChatMl, &mut req, 200, prompt_chars);
+
+        assert!(truncated);
+        assert!(prompt_chars(&prompt) <= 200);
+        assert!(prompt.prefix.contains("SYSTEM"));
+        assert!(!prompt.suffix.contains("old "));
+        assert!(prompt.suffix.contains("latest question"));
+        assert!(prompt.suffix.contains("tool output"));
+        assert_eq!(req.messages.len(), 2);
+    }
+
+    #[test]
+    fn test_fit_prompt_never_drops_system_or_latest_user_message() {
+        let mut req = overflowing_request();
+        let (prompt, truncated) = fit_prompt(ChatTemplate::ChatMl, &mut req, 10, prompt_chars);
+
+        assert!(truncated);
+        assert!(prompt.prefix.contains("SYSTEM"));
+        assert_eq!(req.messages.len(), 1);
+        assert_eq!(req.messages[0].content, "latest question");
+    }
+
+    #[test]
+    fn test_fit_prompt_keeps_prompt_within_budget() {
+        let mut req = overflowing_request();
+        let (_, truncated) = fit_prompt(ChatTemplate::ChatMl, &mut req, 4096, prompt_chars);
+        assert!(!truncated);
+        assert_eq!(req.messages.len(), 4);
+
+        // The whole output budget is reserved
+        assert_eq!(prompt_budget(4096, 512), 3584);
+        assert_eq!(prompt_budget(4096, 8192), 0);
+    }
+
+    #[test]
+    fn test_fit_prompt_tokenizes_each_message_once() {
+        let mut req = overflowing_request();
+        req.messages = (0..100)
+            .map(|i| proto::Message {
+                role: proto::Role::User as i32,
+                content: format!("message {}", i),
+                ..Default::default()
+            })
+            .collect();
+        let calls = std::cell::Cell::new(0);
+        let (prompt, truncated) = fit_prompt(ChatTemplate::ChatMl, &mut req, 500, |prompt| {
+            calls.set(calls.get() + 1);
+            prompt_chars(prompt)
+        });
+
+        assert!(truncated);
+        assert!(prompt_chars(&prompt) <= 500);
+        assert!(prompt.suffix.contains("message 99"));
+        // One count per message, plus the empty and full prompts and the recount
+        assert!(calls.get() <= 100 + 4, "{} counts", calls.get());
+    }
+
+    #[test]
+    fn test_default_max_tokens_leaves_room_for_the_prompt() {
+        let req = CompletionRequest::default();
+        assert_eq!(
+            generation_config(&req, 32768).max_tokens,
+            DEFAULT_MAX_TOKENS
+        );
+        assert_eq!(generation_config(&req, 2048).max_tokens, 1024);
+
+        let req = CompletionRequest {
+            max_tokens: 3000,
+            ..Default::default()
+        };
+        assert_eq!(generation_config(&req, 2048).max_tokens, 3000);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_prefill_is_continued() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let prefill = "{\"name\":";
+        let response = server
+            .complete(Request::new(CompletionRequest {
+                messages: vec![proto::Message {
+                    role: proto::Role::User as i32,
+                    content: "Describe a tool called fetch as a JSON object.".to_string(),
+                    ..Default::default()
+                }],
+                max_tokens: 32,
+                assistant_prefill: prefill.to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(
+            response.content.starts_with(prefill),
+            "{}",
+            response.content
+        );
+        // The model continues the object instead of opening a new one
+        let rest = response.content[prefill.len()..].trim_start();
+        assert!(
+            !rest.starts_with('{') && !rest.starts_with("```"),
+            "{}",
+            response.content
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_enum_response_schema_returns_label() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let labels = vec!["positive".to_string(), "negative".to_string()];
+        let response = server
+            .complete(Request::new(CompletionRequest {
+                system_prompt: "Classify the sentiment of the user's message.".to_string(),
+                messages: vec![proto::Message {
+                    role: proto::Role::User as i32,
+                    content: "I love this, it works perfectly!".to_string(),
+                    ..Default::default()
+                }],
+                max_tokens: 8,
+                response_schema: Some(proto::ResponseSchema {
+                    enum_values: labels.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let result = response.structured_result.and_then(|r| r.value);
+        match result {
+            Some(proto::structured_result::Value::Label(label)) => {
+                assert!(labels.contains(&label), "{}", label)
+            }
+            other => panic!("expected a label, got {:?} ({})", other, response.content),
+        }
+    }
+
+    #[test]
+    fn test_stream_completion_stops_when_receiver_gone() {
+        let mut ctx = MockContext::new(vec![5, 6, 7, 8], 63);
+        let mut sent = 0;
+        stream_completion(
+            &mut ctx,
+            &Sampler::greedy(),
+            3,
+            &test_config(),
+            &CompletionRequest::default(),
+            |_| {
+                sent += 1;
+                sent < 2
+            },
+        );
+
+        // No terminal chunk is attempted after the receiver drops.
+        assert_eq!(sent, 2);
+    }
+
+    #[tokio::test]
+    async fn test_generation_stops_when_request_dropped() {
+        // Far more tokens than can be produced before the request is dropped
+        let mut ctx = MockContext::new(vec![5; 100_000], 63);
+        let config = GenerationConfig {
+            max_tokens: 100_000,
+            ..test_config()
+        };
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+
+        let request = run_until_disconnect(move |cancelled| {
+            let out = generation::generate(&mut ctx, &Sampler::greedy(), 1, &config, |_, _| {
+                std::thread::sleep(Duration::from_millis(1));
+                !cancelled.load(Ordering::Relaxed)
+            });
+            done_tx.send(out).unwrap();
+        });
+        // The client goes away while generation is running
+        let _ = tokio::time::timeout(Duration::from_millis(50), request).await;
+
+        let out = done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("generation should stop after the request is dropped");
+        assert_eq!(out.stop, StopCause::Cancelled);
+        assert!(
+            out.tokens.len() < 1000,
+            "generated {} tokens",
+            out.tokens.len()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_stream_chunks_keep_daemon_alive() {
+        let idle_timeout = Duration::from_millis(200);
+        let (activity_tx, mut activity_rx) = mpsc::channel(16);
+        let activity = Activity::new(activity_tx);
+        let (tx, mut rx) = mpsc::channel(32);
+
+        // A stream lasting twice the idle timeout, with a chunk every 50ms
+        let stream = tokio::task::spawn_blocking(move || {
+            for _ in 0..8 {
+                std::thread::sleep(Duration::from_millis(50));
+                assert!(send_stream_chunk(
+                    &tx,
+                    &activity,
+                    CompletionResponse::default()
+                ));
+            }
+        });
+        let client = tokio::spawn(async move { while rx.recv().await.is_some() {} });
+
+        let paused = AtomicBool::new(false);
+        let idle = wait_for_idle(&mut activity_rx, idle_timeout, &paused);
+        tokio::pin!(idle);
+        tokio::select! {
+            _ = &mut idle => panic!("daemon idled out during an active stream"),
+            result = stream => result.unwrap(),
+        }
+        client.await.unwrap();
+
+        // Once the stream ends the idle timer runs out as usual
+        tokio::time::timeout(idle_timeout * 2, idle)
+            .await
+            .expect("daemon should idle out after the stream ends");
+    }
+
+    #[tokio::test]
+    async fn test_second_signal_cuts_grace_period_short() {
+        let (tx, rx) = mpsc::channel(1);
+        let mut signals = ShutdownSignals { rx };
+        let in_flight = Arc::new(AtomicUsize::new(1));
+
+        tx.send("SIGINT").await.unwrap();
+        let interrupted = tokio::time::timeout(
+            Duration::from_secs(5),
+            wait_for_in_flight(&in_flight, Duration::from_secs(60), &mut signals),
+        )
+        .await
+        .expect("the signal should end the wait");
+        assert!(interrupted);
+
+        // Without a signal the wait runs until the grace period is over
+        drop(tx);
+        let interrupted =
+            wait_for_in_flight(&in_flight, Duration::from_millis(150), &mut signals).await;
+        assert!(!interrupted);
+    }
+
+    #[tokio::test]
+    async fn test_paused_daemon_does_not_idle_out() {
+        let idle_timeout = Duration::from_millis(50);
+        let (_activity_tx, mut activity_rx) = mpsc::channel(16);
+        let paused = AtomicBool::new(true);
+
+        let idle = wait_for_idle(&mut activity_rx, idle_timeout, &paused);
+        tokio::pin!(idle);
+        assert!(tokio::time::timeout(idle_timeout * 4, &mut idle)
+            .await
+            .is_err());
+
+        paused.store(false, Ordering::SeqCst);
+        tokio::time::timeout(idle_timeout * 4, idle)
+            .await
+            .expect("daemon should idle out once resumed");
+    }
+
+    /// A server with one small inference context on the model at `path`.
+    fn test_server(path: &std::path::Path) -> LlmServer {
+        let model = LlamaModel::load_from_file(path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let params = ContextParams {
+            n_ctx: 512,
+            n_batch: 512,
+            ..Default::default()
+        };
+        let context = LlamaContext::new(model.clone(), params).unwrap();
+        let (shutdown_tx, _shutdown_rx) = mpsc::channel(1);
+        let (activity_tx, _activity_rx) = mpsc::channel(16);
+        LlmServer::new(
+            "test".to_string(),
+            hardware::HardwareDetector::detect(),
+            shutdown_tx,
+            activity_tx,
+            model,
+            vec![context],
+            ChatTemplate::default(),
+        )
+    }
+
+    /// A server with no model loaded, as after a failed reload.
+    fn modelless_server() -> LlmServer {
+        let (shutdown_tx, _shutdown_rx) = mpsc::channel(1);
+        let (activity_tx, _activity_rx) = mpsc::channel(16);
+        LlmServer::with_slot(
+            hardware::HardwareProfile::default(),
+            shutdown_tx,
+            activity_tx,
+            ModelSlot::empty(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pause_rejects_inference_until_resumed() {
+        let server = modelless_server();
+        let complete = || {
+            server.complete(Request::new(CompletionRequest {
+                messages: vec![proto::Message {
+                    role: proto::Role::User as i32,
+                    content: "Say hi".to_string(),
+                    ..Default::default()
+                }],
+                max_tokens: 4,
+                ..Default::default()
+            }))
+        };
+        let embed = || {
+            server.embed(Request::new(EmbedRequest {
+                text: "hi".to_string(),
+            }))
+        };
+        let status = || async {
+            let response = server.get_status(Request::new(StatusRequest {})).await;
+            response.unwrap().into_inner()
+        };
+
+        server.paused().store(true, Ordering::SeqCst);
+        for err in [complete().await.unwrap_err(), embed().await.unwrap_err()] {
+            assert_eq!(err.code(), tonic::Code::Unavailable);
+            assert_eq!(err.message(), "Server is paused");
+        }
+        assert!(status().await.paused);
+
+        // Past the pause check, requests reach the empty model slot instead
+        server.paused().store(false, Ordering::SeqCst);
+        for err in [complete().await.unwrap_err(), embed().await.unwrap_err()] {
+            assert_eq!(err.code(), tonic::Code::Unavailable);
+            assert!(err.message().starts_with("No model is loaded"), "{}", err);
+        }
+        assert!(!status().await.paused);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_complete_rejected_while_paused() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let request = || {
+            Request::new(CompletionRequest {
+                messages: vec![proto::Message {
+                    role: proto::Role::User as i32,
+                    content: "Say hi".to_string(),
+                    ..Default::default()
+                }],
+                max_tokens: 4,
+                ..Default::default()
+            })
+        };
+
+        server.paused().store(true, Ordering::SeqCst);
+        let status = server.complete(request()).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        let status = server.get_status(Request::new(StatusRequest {})).await;
+        assert!(status.unwrap().into_inner().paused);
+
+        server.paused().store(false, Ordering::SeqCst);
+        server.complete(request()).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_get_status_reports_model_file() {
+        let path = llama::test_model_path();
+        let size = std::fs::metadata(&path).unwrap().len();
+        let server = test_server(&path).with_model_file(size, "q4_k_m".to_string());
+
+        let status = server
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(status.model_size_bytes, size as i64);
+        assert_eq!(status.quantization, "q4_k_m");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_get_status_answers_while_contexts_busy() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        // Stands in for a long generation holding the only context
+        let held = server.loaded.get().unwrap().contexts.acquire().await;
+
+        let status = tokio::time::timeout(
+            Duration::from_millis(500),
+            server.get_status(Request::new(StatusRequest {})),
+        )
+        .await
+        .expect("get_status should not wait for a context");
+        assert!(status.is_ok());
+
+        // Tokenizing uses the model, not a context
+        let counted = tokio::time::timeout(
+            Duration::from_millis(500),
+            server.count_tokens(Request::new(CountTokensRequest::default())),
+        )
+        .await
+        .expect("count_tokens should not wait for a context");
+        assert!(counted.is_ok());
+        drop(held);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_count_tokens_matches_tokenized_prompt() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let message = proto::Message {
+            role: proto::Role::User as i32,
+            content: "Which asset is the Linux x86_64 build?".to_string(),
+            ..Default::default()
+        };
+        let counted = server
+            .count_tokens(Request::new(CountTokensRequest {
+                system_prompt: "You are a release asset analyst.".to_string(),
+                messages: vec![message.clone()],
+                tools: Vec::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let loaded = server.loaded.get().unwrap();
+        let ctx = loaded.contexts.acquire().await;
+        let prompt =
+            loaded
+                .chat_template
+                .render("You are a release asset analyst.", &[message], &[]);
+        let tokenized = [&prompt.prefix, &prompt.suffix]
+            .iter()
+            .map(|text| ctx.tokenize(text, false, true).unwrap().len())
+            .sum::<usize>();
+        assert_eq!(counted.token_count as usize, tokenized);
+        assert_eq!(counted.n_ctx, ctx.n_ctx() as i32);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_tokenize_detokenize_round_trip() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let text = "Download tsuku-linux-amd64.tar.gz, then run ./install.sh";
+        let tokens = server
+            .tokenize(Request::new(TokenizeRequest {
+                text: text.to_string(),
+                add_special: false,
+                parse_special: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .tokens;
+        assert!(!tokens.is_empty());
+
+        let detokenized = server
+            .detokenize(Request::new(DetokenizeRequest { tokens }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(detokenized.text, text);
+
+        let err = server
+            .detokenize(Request::new(DetokenizeRequest { tokens: vec![-1] }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_tokenize_special_token_flags() {
+        let path = llama::test_model_path();
+        let server = test_server(&path);
+        let count = |text: &str, add_special, parse_special| {
+            let request = Request::new(TokenizeRequest {
+                text: text.to_string(),
+                add_special,
+                parse_special,
+            });
+            let server = &server;
+            async move {
+                server
+                    .tokenize(request)
+                    .await
+                    .unwrap()
+                    .into_inner()
+                    .tokens
+                    .len()
+            }
+        };
+
+        // A turn marker is one token when parsed, several as plain text.
+        // Older Mistral vocabularies have no [INST] token, so skip those.
+        let marker = match server.loaded.get().unwrap().chat_template {
+            ChatTemplate::ChatMl => Some("<|im_start|>"),
+            ChatTemplate::Llama3 => Some("<|start_header_id|>"),
+            ChatTemplate::Gemma => Some("<start_of_turn>"),
+            ChatTemplate::Mistral => None,
+        };
+        if let Some(marker) = marker {
+            assert_eq!(count(marker, false, true).await, 1);
+            assert!(count(marker, false, false).await > 1);
+        }
+
+        // add_special only ever adds tokens (BOS, for models that use it)
+        let plain = count("hello", false, false).await;
+        assert!(count("hello", true, false).await >= plain);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_server_ping_does_not_reset_idle_timeout() {
+        let path = llama::test_model_path();
+        let (activity_tx, mut activity_rx) = mpsc::channel(16);
+        let server = LlmServer {
+            activity: Activity::new(activity_tx),
+            ..test_server(&path)
+        }
+        .with_status_resets_idle(false);
+
+        let ping = server.ping(Request::new(PingRequest {})).await.unwrap();
+        assert!(ping.into_inner().ready);
+        server
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .unwrap();
+        assert!(activity_rx.try_recv().is_err());
+
+        let request = CompletionRequest {
+            messages: vec![proto::Message {
+                role: proto::Role::User as i32,
+                content: "Say hi".to_string(),
+                ..Default::default()
+            }],
+            max_tokens: 4,
+            ..Default::default()
+        };
+        server.complete(Request::new(request)).await.unwrap();
+        assert!(activity_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_send_stream_chunk_reports_disconnect() {
+        let (activity_tx, mut activity_rx) = mpsc::channel(16);
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+
+        assert!(!send_stream_chunk(
+            &tx,
+            &Activity::new(activity_tx),
+            CompletionResponse::default()
+        ));
+        assert!(activity_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_ping_does_not_reset_idle_timeout() {
+        let (activity_tx, mut activity_rx) = mpsc::channel(16);
+        let mut activity = Activity::new(activity_tx);
+
+        activity.record(RequestKind::Ping);
+        assert!(activity_rx.try_recv().is_err());
+        activity.record(RequestKind::Status);
+        assert!(activity_rx.try_recv().is_ok());
+        activity.record(RequestKind::Work);
+        assert!(activity_rx.try_recv().is_ok());
+
+        activity.status_resets_idle = false;
+        activity.record(RequestKind::Status);
+        assert!(activity_rx.try_recv().is_err());
+        activity.record(RequestKind::Work);
+        assert!(activity_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_warmup_decodes_requested_tokens() {
+        let mut ctx = MockContext::new(vec![], 63);
+        warmup(&mut ctx, 512).unwrap();
+        assert_eq!(ctx.decoded_tokens, 512);
+        assert!(ctx.last_batch.iter().all(|&t| t == MockContext::BOS_TOKEN));
+        assert_eq!(ctx.kv_clears, 1);
+
+        let mut ctx = MockContext::new(vec![], 63);
+        warmup(&mut ctx, 0).unwrap();
+        assert_eq!(ctx.decoded_tokens, 0);
+    }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_warmup_real_model() {
+        let path = llama::test_model_path();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let params = ContextParams {
+            n_ctx: 1024,
+            n_batch: 1024,
+            ..Default::default()
+        };
+        let mut ctx = LlamaContext::new(model, params).unwrap();
+        warmup(&mut ctx, 512).expect("warmup decode should succeed");
+        assert_eq!(ctx.n_ctx(), 1024);
+        assert_eq!(ctx.kv_cache_used_cells(), 0);
+    }
+
+    #[test]
+    fn test_no_warmup_flag_disables_warmup() {
+        let defaults = ServeArgs::default();
+        assert_eq!(defaults.warmup_tokens(4096), 1);
+
+        let cli = Cli::parse_from(["tsuku-llm", "serve", "--no-warmup"]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(args.warmup_tokens(4096), 0);
+
+        let cli = Cli::parse_from(["tsuku-llm", "serve", "--warmup-tokens", "8192"]);
+        let Some(Commands::Serve(args)) = cli.command else {
+            panic!("expected serve command");
+        };
+        assert_eq!(args.warmup_tokens(4096), 4096);
+
+        assert!(Cli::try_parse_from([
+            "tsuku-llm",
+            "serve",
+            "--no-warmup",
+            "--warmup-tokens",
+            "512"
+        ])
+        .is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    async fn test_get_status_over_unix_and_tcp() {
+        let path = llama::test_model_path();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let (shutdown_tx, _shutdown_rx) = mpsc::channel(1);
+        let (activity_tx, _activity_rx) = mpsc::channel(16);
+        let server = LlmServer::new(
+            "test".to_string(),
+            hardware::HardwareDetector::detect(),
+            shutdown_tx,
+            activity_tx,
+            model,
+            vec![],
+            ChatTemplate::default(),
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("llm.sock");
+        let unix = UnixListener::bind(&socket).unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tcp_addr = tcp.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(InferenceServiceServer::new(server))
+                .serve_with_incoming(listener::incoming(unix, Some(tcp))),
+        );
+
+        let unix_channel = tonic::transport::Endpoint::from_static("http://localhost")
+            .connect_with_connector(tower::service_fn(move |_| {
+                let socket = socket.clone();
+                async move {
+                    let stream = tokio::net::UnixStream::connect(socket).await?;
+                    Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+                }
+            }))
+            .await
+            .unwrap();
+        let tcp_channel = tonic::transport::Endpoint::from_shared(format!("http://{}", tcp_addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+
+        for channel in [unix_channel, tcp_channel] {
+            let mut grpc = tonic::client::Grpc::new(channel);
+            grpc.ready().await.unwrap();
+            let codec: tonic::codec::ProstCodec<StatusRequest, StatusResponse> = Default::default();
+            let status = grpc
+                .unary(
+                    Request::new(StatusRequest {}),
+                    tonic::codegen::http::uri::PathAndQuery::from_static(
+                        "/tsuku.llm.v1.InferenceService/GetStatus",
+                    ),
+                    codec,
+                )
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(status.ready);
+            assert_eq!(status.model_name, "test");
+        }
+    }
+
+    fn call_names(calls: &[proto::ToolCall]) -> Vec<&str> {
+        calls.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_tool_calls_single_object() {
+        let calls = LlmServer::parse_tool_calls(
+            "Sure: {\"name\": \"fetch\", \"arguments\": {\"url\": \"a}b\"}} done",
+        );
+        assert_eq!(call_names(&calls), vec!["fetch"]);
+        assert_eq!(calls[0].arguments_json, r#"{"url":"a}b"}"#);
+    }
+
+    #[test]
+    fn test_parse_tool_calls_array() {
+        let calls = LlmServer::parse_tool_calls(
+            r#"[{"name": "fetch", "arguments": {}}, {"name": "inspect", "arguments": {"n": 1}}]"#,
+        );
+        assert_eq!(call_names(&calls), vec!["fetch", "inspect"]);
+        assert_ne!(calls[0].id, calls[1].id);
+    }
+
+    #[test]
+    fn test_parse_tool_calls_back_to_back_objects() {
+        let calls = LlmServer::parse_tool_calls(
+            "{\"name\": \"fetch\", \"arguments\": {}}\n{\"name\": \"inspect\", \"arguments\": {}}",
+        );
+        assert_eq!(call_names(&calls), vec!["fetch", "inspect"]);
+    }
+
+    #[test]
+    fn test_parse_tool_calls_fenced_object() {
+        // The example in the prose must not be taken for the call
+        let content = "Calls look like {\"name\": \"example\", \"arguments\": {}}.\n\
+                       ```json\n{\"name\": \"fetch\", \"arguments\": {\"url\": \"x\"}}\n```\n";
+        let calls = LlmServer::parse_tool_calls(content);
+        assert_eq!(call_names(&calls), vec!["fetch"]);
+    }
+
+    #[test]
+    fn test_parse_tool_calls_fenced_array() {
+        let content = "Here you go:\n```\n[{\"name\": \"fetch\", \"arguments\": {}}, \
+                       {\"name\": \"inspect\", \"arguments\": {}}]\n```";
+        let calls = LlmServer::parse_tool_calls(content);
+        assert_eq!(call_names(&calls), vec!["fetch", "inspect"]);
+    }
+
+    #[test]
+    fn test_parse_tool_calls_unfenced_object() {
+        let calls = LlmServer::parse_tool_calls(r#"{"name": "fetch", "arguments": {}}"#);
+        assert_eq!(call_names(&calls), vec!["fetch"]);
+
+        // A fence without JSON falls back to scanning the whole text
+        let calls = LlmServer::parse_tool_calls(
+            "```\nls -la\n```\n{\"name\": \"fetch\", \"arguments\": {}}",
+        );
+        assert_eq!(call_names(&calls), vec!["fetch"]);
+    }
+
+    fn tool(name: &str, schema: &str) -> proto::ToolDef {
+        proto::ToolDef {
+            name: name.to_string(),
+            description: String::new(),
+            parameters_schema: schema.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_stop_tool_use() {
+        let tools = [tool("fetch", ""), tool("inspect", "")];
+        let content =
+            r#"[{"name": "fetch", "arguments": {}}, {"name": "inspect", "arguments": {}}]"#;
+        let (stop_reason, calls) = LlmServer::resolve_stop(content, &StopCause::Eog, &tools);
+        assert_eq!(stop_reason, "tool_use");
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_stop_without_tool_call() {
+        // Offered tools, but finished without calling one
+        let tools = [tool("fetch", "")];
+        let (stop_reason, calls) =
+            LlmServer::resolve_stop("no tools {here", &StopCause::Eog, &tools);
+        assert_eq!(stop_reason, "eos");
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_stop_without_tools() {
+        let (stop_reason, calls) = LlmServer::resolve_stop("done", &StopCause::Eog, &[]);
+        assert_eq!(stop_reason, "eos");
+        assert!(calls.is_empty());
+
+        let (stop_reason, _) = LlmServer::resolve_stop("do", &StopCause::MaxTokens, &[]);
+        assert_eq!(stop_reason, "max_tokens");
+
+        // Running out of tokens mid-call isn't a tool call
+        let tools = [tool("fetch", "")];
+        let content = r#"{"name": "fetch", "arguments": {}}"#;
+        let (stop_reason, calls) = LlmServer::resolve_stop(content, &StopCause::MaxTokens, &tools);
+        assert_eq!(stop_reason, "max_tokens");
+        assert!(calls.is_empty());
+    }
+
+    const FETCH_SCHEMA: &str = r#"{"type": "object",
+        "properties": {"url": {"type": "string"}, "retries": {"type": "integer"}},
+        "required": ["url"]}"#;
+
+    #[test]
+    fn test_resolve_stop_accepts_valid_arguments() {
+        let tools = [tool("fetch", FETCH_SCHEMA)];
+        let content = r#"{"name": "fetch", "arguments": {"url": "https://x", "retries": 2}}"#;
+        let (stop_reason, calls) = LlmServer::resolve_stop(content, &StopCause::Eog, &tools);
+        assert_eq!(stop_reason, "tool_use");
+        assert_eq!(calls.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_stop_rejects_missing_required_argument() {
+        let tools = [tool("fetch", FETCH_SCHEMA)];
+        let content = r#"{"name": "fetch", "arguments": {"retries": 2}}"#;
+        let (stop_reason, calls) = LlmServer::resolve_stop(content, &StopCause::Eog, &tools);
+        assert_eq!(stop_reason, "eos");
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_stop_rejects_wrong_argument_type() {
+        let tools = [tool("fetch", FETCH_SCHEMA)];
+        let content = r#"{"name": "fetch", "arguments": {"url": "https://x", "retries": "two"}}"#;
+        let (stop_reason, calls) = LlmServer::resolve_stop(content, &StopCause::Eog, &tools);
+        assert_eq!(stop_reason, "eos");
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_l2_normalize() {
+        let mut v = [3.0, 4.0];
+        l2_normalize(&mut v);
+        assert_eq!(v, [0.6, 0.8]);
+
+        let mut zero = [0.0; 3];
+        l2_normalize(&mut zero);
+        assert_eq!(zero, [0.0; 3]);
+    }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_embed_text_real_model() {
+        let path = llama::test_model_path();
+        let model = LlamaModel::load_from_file(&path, ModelParams::default())
+            .map(Arc::new)
+            .unwrap();
+        let mut ctx = LlamaContext::new(model.clone(), embedding_context_params()).unwrap();
+
+        let (embedding, input_tokens) = embed_text(&mut ctx, "hello world").unwrap();
+        assert!(input_tokens > 0);
+        assert_eq!(embedding.len(), model.n_embd() as usize);
+        assert!(embedding.iter().all(|x| x.is_finite()));
+        let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-3, "norm {}", norm);
+    }
 }
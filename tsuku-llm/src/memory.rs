@@ -0,0 +1,261 @@
+//! Memory budgeting for model and context allocation.
+//!
+//! Estimates the daemon's footprint (model weights plus KV cache) so that an
+//! operator-configured ceiling can be enforced before llama.cpp allocates.
+
+use thiserror::Error;
+
+use crate::llama::{KvCacheType, LlamaModel};
+
+const MB: u64 = 1024 * 1024;
+
+/// Smallest context we're willing to shrink to when fitting a budget.
+/// Below this, tool-calling prompts don't fit and the daemon is useless.
+pub const MIN_CONTEXT_TOKENS: u32 = 2048;
+
+/// Upper bound on the default number of pooled inference contexts.
+pub const MAX_DEFAULT_CONTEXTS: usize = 4;
+
+/// Least memory left free when sizing the context pool, for llama.cpp's
+/// compute buffers, the embeddings context created on first use, and
+/// whatever else shares the device. A tenth of the memory if that's more.
+const MIN_CONTEXT_HEADROOM: u64 = 512 * MB;
+
+/// Size of one block of a KV cache row, as (bytes, elements). The
+/// quantized types store 32 elements per block with an f16 scale.
+fn kv_block_size(kv_type: KvCacheType) -> (u64, u64) {
+    match kv_type {
+        KvCacheType::F16 => (2, 1),
+        KvCacheType::Q8_0 => (34, 32),
+        KvCacheType::Q4_0 => (18, 32),
+    }
+}
+
+/// Errors when a configuration can't fit in the memory budget.
+#[derive(Error, Debug, PartialEq)]
+pub enum BudgetError {
+    #[error("model needs {model_mb} MB but the memory budget is {budget_mb} MB")]
+    ModelExceedsBudget { model_mb: u64, budget_mb: u64 },
+
+    #[error(
+        "memory budget of {budget_mb} MB leaves room for only {n_ctx} context tokens \
+         (minimum {min})"
+    )]
+    ContextTooSmall {
+        budget_mb: u64,
+        n_ctx: u32,
+        min: u32,
+    },
+}
+
+/// Shape of a model's KV cache, used to estimate its size.
+#[derive(Debug, Clone, Copy)]
+pub struct KvCacheShape {
+    /// Number of transformer layers.
+    pub n_layer: u64,
+    /// Width of the K (and V) projection per token (head_dim * n_head_kv).
+    pub n_embd_kv: u64,
+    /// Element type of the K and V caches (`--kv-cache-type`).
+    pub kv_type: KvCacheType,
+}
+
+impl KvCacheShape {
+    /// Read the KV cache shape from a loaded model whose caches hold
+    /// `kv_type` elements.
+    pub fn from_model(model: &LlamaModel, kv_type: KvCacheType) -> Self {
+        let n_head = model.n_head().max(1) as u64;
+        let head_dim = model.n_embd() as u64 / n_head;
+        Self {
+            n_layer: model.n_layer() as u64,
+            n_embd_kv: head_dim * model.n_head_kv() as u64,
+            kv_type,
+        }
+    }
+
+    /// KV cache bytes needed per context token (K and V across all layers).
+    pub fn bytes_per_token(&self) -> u64 {
+        let (block_bytes, block_elements) = kv_block_size(self.kv_type);
+        let row_bytes = self.n_embd_kv.div_ceil(block_elements) * block_bytes;
+        2 * self.n_layer * row_bytes
+    }
+}
+
+/// `budget_mb` in bytes. A budget too large to count in bytes is no limit.
+fn budget_bytes(budget_mb: u64) -> u64 {
+    budget_mb.saturating_mul(MB)
+}
+
+/// Fail fast if the model alone can't fit in the budget.
+pub fn check_model_fits(model_bytes: u64, budget_mb: u64) -> Result<(), BudgetError> {
+    if model_bytes > budget_bytes(budget_mb) {
+        return Err(BudgetError::ModelExceedsBudget {
+            model_mb: model_bytes.div_ceil(MB),
+            budget_mb,
+        });
+    }
+    Ok(())
+}
+
+/// Pick the largest context size up to `requested` that fits the budget.
+///
+/// Returns `requested` unchanged when it fits, a smaller size (rounded down
+/// to a multiple of 256) when it doesn't, and an error when even
+/// `MIN_CONTEXT_TOKENS` would exceed the budget.
+pub fn fit_context(
+    requested: u32,
+    model_bytes: u64,
+    kv: KvCacheShape,
+    budget_mb: u64,
+) -> Result<u32, BudgetError> {
+    check_model_fits(model_bytes, budget_mb)?;
+
+    let per_token = kv.bytes_per_token().max(1);
+    let available = budget_bytes(budget_mb) - model_bytes;
+    let max_tokens = (available / per_token).min(u32::MAX as u64) as u32;
+
+    if requested <= max_tokens {
+        return Ok(requested);
+    }
+
+    let n_ctx = max_tokens / 256 * 256;
+    if n_ctx < MIN_CONTEXT_TOKENS {
+        return Err(BudgetError::ContextTooSmall {
+            budget_mb,
+            n_ctx,
+            min: MIN_CONTEXT_TOKENS,
+        });
+    }
+
+    Ok(n_ctx)
+}
+
+/// How many `n_ctx` KV caches fit in `memory_bytes` next to the model,
+/// after leaving headroom for everything else. Always at least 1.
+pub fn max_context_count(
+    memory_bytes: u64,
+    model_bytes: u64,
+    n_ctx: u32,
+    kv: KvCacheShape,
+) -> usize {
+    let headroom = (memory_bytes / 10).max(MIN_CONTEXT_HEADROOM);
+    let per_context = (n_ctx as u64 * kv.bytes_per_token()).max(1);
+    let fits = memory_bytes
+        .saturating_sub(model_bytes)
+        .saturating_sub(headroom)
+        / per_context;
+    fits.clamp(1, usize::MAX as u64) as usize
+}
+
+/// Default number of inference contexts: as many as fit in `memory_bytes`
+/// (see [`max_context_count`]), up to `MAX_DEFAULT_CONTEXTS`.
+pub fn default_context_count(
+    memory_bytes: u64,
+    model_bytes: u64,
+    n_ctx: u32,
+    kv: KvCacheShape,
+) -> usize {
+    max_context_count(memory_bytes, model_bytes, n_ctx, kv).min(MAX_DEFAULT_CONTEXTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Qwen 2.5 14B: 48 layers, 8 KV heads of dim 128.
+    fn qwen14b() -> KvCacheShape {
+        KvCacheShape {
+            n_layer: 48,
+            n_embd_kv: 1024,
+            kv_type: KvCacheType::F16,
+        }
+    }
+
+    #[test]
+    fn test_kv_bytes_per_token() {
+        // ~0.19 GB per 1K tokens, matching the sizing notes in main.rs
+        assert_eq!(qwen14b().bytes_per_token(), 196_608);
+    }
+
+    #[test]
+    fn test_quantized_kv_cache_is_smaller() {
+        let shape = |kv_type| KvCacheShape {
+            kv_type,
+            ..qwen14b()
+        };
+        assert_eq!(shape(KvCacheType::Q8_0).bytes_per_token(), 104_448);
+        assert_eq!(shape(KvCacheType::Q4_0).bytes_per_token(), 55_296);
+
+        // The same budget fits a longer context with a quantized cache
+        let fit = |kv_type| fit_context(24576, 8 * 1024 * MB, shape(kv_type), 9 * 1024);
+        assert_eq!(fit(KvCacheType::F16), Ok(5376));
+        assert_eq!(fit(KvCacheType::Q8_0), Ok(10240));
+        assert_eq!(fit(KvCacheType::Q4_0), Ok(19200));
+    }
+
+    #[test]
+    fn test_fit_context_keeps_request_within_budget() {
+        let n_ctx = fit_context(8192, 8 * 1024 * MB, qwen14b(), 16 * 1024).unwrap();
+        assert_eq!(n_ctx, 8192);
+    }
+
+    #[test]
+    fn test_fit_context_downsizes_oversized_request() {
+        // 1 GB of headroom fits ~5461 tokens of KV cache
+        let n_ctx = fit_context(24576, 8 * 1024 * MB, qwen14b(), 9 * 1024).unwrap();
+        assert_eq!(n_ctx, 5376);
+        assert!(n_ctx as u64 * qwen14b().bytes_per_token() <= 1024 * MB);
+    }
+
+    #[test]
+    fn test_fit_context_rejects_when_minimum_does_not_fit() {
+        let result = fit_context(24576, 8 * 1024 * MB, qwen14b(), 8 * 1024 + 100);
+        assert!(matches!(result, Err(BudgetError::ContextTooSmall { .. })));
+    }
+
+    #[test]
+    fn test_huge_budget_does_not_overflow() {
+        assert!(check_model_fits(9 * 1024 * MB, u64::MAX).is_ok());
+        assert_eq!(
+            fit_context(8192, 8 * 1024 * MB, qwen14b(), u64::MAX),
+            Ok(8192)
+        );
+    }
+
+    #[test]
+    fn test_check_model_fits_rejects_oversized_model() {
+        let result = check_model_fits(9 * 1024 * MB, 8 * 1024);
+        assert_eq!(
+            result,
+            Err(BudgetError::ModelExceedsBudget {
+                model_mb: 9 * 1024,
+                budget_mb: 8 * 1024,
+            })
+        );
+        assert!(check_model_fits(7 * 1024 * MB, 8 * 1024).is_ok());
+    }
+
+    #[test]
+    fn test_default_context_count() {
+        // 8 GB model + 8192-token contexts (1.5 GB each)
+        let count = |memory_gb: u64| {
+            default_context_count(memory_gb * 1024 * MB, 8 * 1024 * MB, 8192, qwen14b())
+        };
+        assert_eq!(count(13), 2);
+        assert_eq!(count(48), MAX_DEFAULT_CONTEXTS);
+        // Always at least one, even when nothing fits
+        assert_eq!(count(8), 1);
+        assert_eq!(count(0), 1);
+    }
+
+    #[test]
+    fn test_context_count_leaves_headroom() {
+        // 1.5 GB contexts next to a 1 GB model
+        let count = |memory_mb: u64| max_context_count(memory_mb * MB, 1024 * MB, 8192, qwen14b());
+        // 3 GB next to the model would hold two contexts with nothing to
+        // spare; keeping at least 512 MB free costs one of them
+        assert_eq!(count(4 * 1024), 1);
+        assert_eq!(count(4 * 1024 + 512), 2);
+        // Large budgets keep a tenth free: 63 GB would hold 42 contexts
+        assert_eq!(count(64 * 1024), 37);
+    }
+}
@@ -0,0 +1,235 @@
+//! Inference metrics, exported in the Prometheus text format.
+
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the output-length buckets, in tokens.
+const OUTPUT_TOKEN_BUCKETS: &[u64] = &[16, 32, 64, 128, 256, 512, 1024, 2048, 4096];
+
+/// A histogram with fixed bucket bounds.
+pub struct Histogram {
+    bounds: &'static [u64],
+    /// Observations per bucket (not cumulative); the last is the +Inf bucket.
+    counts: Vec<AtomicU64>,
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [u64]) -> Self {
+        Self {
+            bounds,
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, value: u64) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Cumulative counts per bucket, as Prometheus expects (`le` buckets).
+    pub fn cumulative_counts(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0, |total, count| {
+                *total += count.load(Ordering::Relaxed);
+                Some(*total)
+            })
+            .collect()
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let counts = self.cumulative_counts();
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bound, count) in self.bounds.iter().zip(&counts) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+        }
+        let total = counts.last().copied().unwrap_or(0);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, total);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum.load(Ordering::Relaxed));
+        let _ = writeln!(out, "{}_count {}", name, total);
+    }
+}
+
+/// A gauge holding an `f64`, stored as its bits.
+#[derive(Default)]
+struct Gauge(AtomicU64);
+
+impl Gauge {
+    fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Write one counter or gauge sample with its help and type lines.
+fn render_value(out: &mut String, name: &str, kind: &str, help: &str, value: impl Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Metrics collected over the life of the server.
+pub struct Metrics {
+    /// Generated tokens per completed request.
+    output_tokens: Histogram,
+    completions: AtomicU64,
+    input_tokens_total: AtomicU64,
+    output_tokens_total: AtomicU64,
+    /// Generation speed of the most recent completion.
+    tokens_per_second: Gauge,
+    /// How long loading the served model took.
+    model_load_seconds: Gauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            output_tokens: Histogram::new(OUTPUT_TOKEN_BUCKETS),
+            completions: AtomicU64::new(0),
+            input_tokens_total: AtomicU64::new(0),
+            output_tokens_total: AtomicU64::new(0),
+            tokens_per_second: Gauge::default(),
+            model_load_seconds: Gauge::default(),
+        }
+    }
+}
+
+impl Metrics {
+    /// Record a completed request: its prompt and output lengths, and how
+    /// long generating the output took.
+    pub fn record_completion(&self, input_tokens: usize, output_tokens: usize, elapsed: Duration) {
+        self.completions.fetch_add(1, Ordering::Relaxed);
+        self.input_tokens_total
+            .fetch_add(input_tokens as u64, Ordering::Relaxed);
+        self.output_tokens_total
+            .fetch_add(output_tokens as u64, Ordering::Relaxed);
+        self.record_output_tokens(output_tokens);
+        if !elapsed.is_zero() {
+            self.tokens_per_second
+                .set(output_tokens as f64 / elapsed.as_secs_f64());
+        }
+    }
+
+    /// Record a completed request's output length.
+    fn record_output_tokens(&self, tokens: usize) {
+        self.output_tokens.observe(tokens as u64);
+    }
+
+    /// Record how long loading the served model took.
+    pub fn set_model_load_time(&self, elapsed: Duration) {
+        self.model_load_seconds.set(elapsed.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text exposition format, along
+    /// with the number of requests currently in flight.
+    pub fn render(&self, in_flight: usize) -> String {
+        let mut out = String::new();
+        render_value(
+            &mut out,
+            "tsuku_llm_completions_total",
+            "counter",
+            "Completed completion requests.",
+            self.completions.load(Ordering::Relaxed),
+        );
+        render_value(
+            &mut out,
+            "tsuku_llm_input_tokens_total",
+            "counter",
+            "Prompt tokens of completed requests.",
+            self.input_tokens_total.load(Ordering::Relaxed),
+        );
+        render_value(
+            &mut out,
+            "tsuku_llm_output_tokens_total",
+            "counter",
+            "Tokens generated for completed requests.",
+            self.output_tokens_total.load(Ordering::Relaxed),
+        );
+        render_value(
+            &mut out,
+            "tsuku_llm_tokens_per_second",
+            "gauge",
+            "Generation speed of the most recent completion.",
+            self.tokens_per_second.get(),
+        );
+        render_value(
+            &mut out,
+            "tsuku_llm_in_flight_requests",
+            "gauge",
+            "Requests currently being served.",
+            in_flight,
+        );
+        render_value(
+            &mut out,
+            "tsuku_llm_model_load_seconds",
+            "gauge",
+            "Time taken to load the served model.",
+            self.model_load_seconds.get(),
+        );
+        self.output_tokens.render(
+            &mut out,
+            "tsuku_llm_output_tokens",
+            "Generated tokens per completed request.",
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_tokens_fill_expected_buckets() {
+        let metrics = Metrics::default();
+        for tokens in [10, 16, 17, 300, 5000] {
+            metrics.record_output_tokens(tokens);
+        }
+
+        let counts = metrics.output_tokens.cumulative_counts();
+        // le=16 holds 10 and 16, le=32 adds 17, le=512 adds 300, +Inf adds 5000
+        assert_eq!(counts, vec![2, 3, 3, 3, 3, 4, 4, 4, 4, 5]);
+    }
+
+    #[test]
+    fn test_completions_update_counters_and_speed() {
+        let metrics = Metrics::default();
+        for _ in 0..3 {
+            metrics.record_completion(100, 50, Duration::from_millis(500));
+        }
+        metrics.set_model_load_time(Duration::from_millis(2500));
+
+        let text = metrics.render(2);
+        assert!(text.contains("# TYPE tsuku_llm_completions_total counter\n"));
+        assert!(text.contains("tsuku_llm_completions_total 3\n"));
+        assert!(text.contains("tsuku_llm_input_tokens_total 300\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_total 150\n"));
+        assert!(text.contains("tsuku_llm_tokens_per_second 100\n"));
+        assert!(text.contains("tsuku_llm_in_flight_requests 2\n"));
+        assert!(text.contains("tsuku_llm_model_load_seconds 2.5\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_count 3\n"));
+    }
+
+    #[test]
+    fn test_render_prometheus_histogram() {
+        let metrics = Metrics::default();
+        metrics.record_output_tokens(20);
+        metrics.record_output_tokens(100);
+
+        let text = metrics.render(0);
+        assert!(text.contains("# TYPE tsuku_llm_output_tokens histogram\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_bucket{le=\"16\"} 0\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_bucket{le=\"128\"} 2\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_sum 120\n"));
+        assert!(text.contains("tsuku_llm_output_tokens_count 2\n"));
+    }
+}
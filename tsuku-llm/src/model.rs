@@ -3,19 +3,28 @@
 //! Maps detected hardware profiles to appropriate model configurations,
 //! balancing inference quality against resource constraints.
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::hardware::{GpuBackend, HardwareProfile};
+use crate::template::ChatTemplate;
 
 /// Inference backend for model execution.
 ///
 /// GPU acceleration is required — CPU-only inference is not supported
 /// because models below 7B (the minimum for acceptable quality) are
 /// too slow on CPU to be practical.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum Backend {
     /// NVIDIA CUDA acceleration
     Cuda,
+    /// AMD ROCm/HIP acceleration
+    Rocm,
     /// Apple Metal acceleration
     Metal,
     /// Vulkan acceleration (AMD, Intel, NVIDIA fallback)
@@ -26,6 +35,7 @@ impl std::fmt::Display for Backend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Backend::Cuda => write!(f, "cuda"),
+            Backend::Rocm => write!(f, "rocm"),
             Backend::Metal => write!(f, "metal"),
             Backend::Vulkan => write!(f, "vulkan"),
         }
@@ -38,6 +48,7 @@ impl std::str::FromStr for Backend {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "cuda" => Ok(Backend::Cuda),
+            "rocm" => Ok(Backend::Rocm),
             "metal" => Ok(Backend::Metal),
             "vulkan" => Ok(Backend::Vulkan),
             _ => Err(format!("unknown backend: {} (gpu required, cpu not supported)", s)),
@@ -45,6 +56,20 @@ impl std::str::FromStr for Backend {
     }
 }
 
+impl TryFrom<String> for Backend {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Backend> for String {
+    fn from(backend: Backend) -> Self {
+        backend.to_string()
+    }
+}
+
 /// Complete specification for a model to load.
 #[derive(Debug, Clone)]
 pub struct ModelSpec {
@@ -60,6 +85,8 @@ pub struct ModelSpec {
     pub sha256: String,
     /// CDN download URL
     pub download_url: String,
+    /// Prompt format from the manifest; detected from the model when unset
+    pub chat_template: Option<ChatTemplate>,
 }
 
 /// Error during model selection.
@@ -67,11 +94,15 @@ pub struct ModelSpec {
 pub enum SelectionError {
     /// No GPU detected — GPU acceleration is required
     NoGpuDetected,
-    /// GPU doesn't have enough VRAM for the minimum model (7B)
+    /// GPU doesn't have enough VRAM for the smallest model
     InsufficientVram {
         vram_gb: f64,
         minimum_gb: f64,
     },
+    /// No manifest model can be auto-selected for the backend
+    NoSuitableModel {
+        backend: String,
+    },
     /// Config specifies unknown model name
     InvalidConfigModel {
         name: String,
@@ -81,6 +112,13 @@ pub enum SelectionError {
         backend: String,
         reason: String,
     },
+    /// The explicitly configured model has no variant in the requested
+    /// quantization that supports the backend
+    QuantizationUnavailable {
+        model: String,
+        quantization: String,
+        available: Vec<String>,
+    },
 }
 
 impl std::fmt::Display for SelectionError {
@@ -95,41 +133,201 @@ impl std::fmt::Display for SelectionError {
             SelectionError::InsufficientVram { vram_gb, minimum_gb } => {
                 write!(
                     f,
-                    "insufficient VRAM: {:.1} GB available, {:.1} GB required for minimum model (7B)",
+                    "insufficient VRAM: {:.1} GB available, {:.1} GB required for the smallest model",
                     vram_gb, minimum_gb
                 )
             }
+            SelectionError::NoSuitableModel { backend } => {
+                write!(
+                    f,
+                    "no {} model in the manifest supports backend '{}'",
+                    DEFAULT_QUANTIZATION, backend
+                )
+            }
             SelectionError::InvalidConfigModel { name } => {
                 write!(f, "unknown model in config: {}", name)
             }
             SelectionError::InvalidConfigBackend { backend, reason } => {
                 write!(f, "invalid backend '{}': {}", backend, reason)
             }
+            SelectionError::QuantizationUnavailable {
+                model,
+                quantization,
+                available,
+            } => {
+                write!(
+                    f,
+                    "quantization '{}' is not available for model '{}' (available: {})",
+                    quantization,
+                    model,
+                    available.join(", ")
+                )
+            }
         }
     }
 }
 
 impl std::error::Error for SelectionError {}
 
+/// Error loading a manifest file.
+#[derive(Debug)]
+pub enum ManifestError {
+    /// The file couldn't be read
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The file isn't a valid manifest (bad JSON, missing field, unknown backend)
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// An entry is unusable
+    InvalidEntry {
+        model: String,
+        reason: String,
+    },
+    /// A signature was required and is missing, malformed or doesn't match
+    Signature {
+        path: PathBuf,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestError::Io { path, source } => {
+                write!(f, "failed to read manifest {}: {}", path.display(), source)
+            }
+            ManifestError::Parse { path, source } => {
+                write!(f, "invalid manifest {}: {}", path.display(), source)
+            }
+            ManifestError::InvalidEntry { model, reason } => {
+                write!(f, "invalid manifest entry '{}': {}", model, reason)
+            }
+            ManifestError::Signature { path, reason } => {
+                write!(f, "untrusted manifest {}: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::Io { source, .. } => Some(source),
+            ManifestError::Parse { source, .. } => Some(source),
+            ManifestError::InvalidEntry { .. } | ManifestError::Signature { .. } => None,
+        }
+    }
+}
+
 /// Entry in the model manifest.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelEntry {
     /// Quantization level
     pub quantization: String,
     /// Expected file size in bytes (total across all split files)
     pub size_bytes: u64,
-    /// SHA256 checksum (of the first split file, or the single file)
+    /// SHA256 checksum (of the first split file, or the single file).
+    /// For `.zst` downloads this is the checksum of the decompressed file.
     pub sha256: String,
-    /// Download URL (first split file URL, or single file URL)
+    /// Download URL (first split file URL, or single file URL). A `.zst`
+    /// URL is decompressed while downloading.
     pub download_url: String,
     /// Number of split GGUF files (1 = single file, >1 = split)
     pub split_count: u32,
+    /// SHA256 checksum of each split file, in part order (empty = not verified)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub part_sha256: Vec<String>,
+    /// Size in bytes of each split file, in part order (empty = only the
+    /// total is known)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub part_size_bytes: Vec<u64>,
+    /// Other URLs serving the same file, tried in order when `download_url`
+    /// fails. For split models each one is the first part's URL, like
+    /// `download_url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirror_urls: Vec<String>,
     /// Supported backends for this model
     pub supported_backends: Vec<Backend>,
+    /// Prompt format (e.g. "chatml", "llama3"); detected from the model when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_template: Option<ChatTemplate>,
+}
+
+impl ModelEntry {
+    /// Expected checksum of a split part, if the manifest has one.
+    pub fn part_checksum(&self, index: usize) -> Option<&str> {
+        self.part_sha256
+            .get(index)
+            .map(String::as_str)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// `download_url` followed by the mirrors, in the order they are tried.
+    pub fn download_urls(&self) -> impl Iterator<Item = &str> {
+        let mirrors = self.mirror_urls.iter().map(String::as_str);
+        std::iter::once(self.download_url.as_str()).chain(mirrors)
+    }
+
+    /// Bytes a download of the model fetches: the sum of the part sizes
+    /// when the manifest lists them, otherwise `size_bytes`.
+    pub fn total_download_size(&self) -> u64 {
+        if self.part_size_bytes.is_empty() {
+            self.size_bytes
+        } else {
+            self.part_size_bytes.iter().sum()
+        }
+    }
+
+    /// Render the entry as manifest JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("ModelEntry is always serializable")
+    }
+
+    /// Check that the entry can actually be downloaded.
+    fn validate(&self, model: &str) -> Result<(), ManifestError> {
+        let invalid = |reason: &str| ManifestError::InvalidEntry {
+            model: model.to_string(),
+            reason: reason.to_string(),
+        };
+        if self.download_url.trim().is_empty() {
+            return Err(invalid("download_url is empty"));
+        }
+        if self.mirror_urls.iter().any(|url| url.trim().is_empty()) {
+            return Err(invalid("mirror_urls contains an empty URL"));
+        }
+        if self.split_count == 0 {
+            return Err(invalid("split_count must be at least 1"));
+        }
+        if self.supported_backends.is_empty() {
+            return Err(invalid("supported_backends is empty"));
+        }
+        if !self.part_sha256.is_empty() && self.part_sha256.len() != self.split_count as usize {
+            return Err(invalid("part_sha256 must have one checksum per split file"));
+        }
+        let parts = self.split_count as usize;
+        if !self.part_size_bytes.is_empty() && self.part_size_bytes.len() != parts {
+            return Err(invalid("part_size_bytes must have one size per split file"));
+        }
+        if self.split_count > 1 {
+            for url in self.download_urls() {
+                crate::models::split_file_urls(url, self.split_count)
+                    .map_err(|e| invalid(&e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Manifest of available models.
-#[derive(Debug, Clone)]
+///
+/// Serialized as a JSON object mapping model names to entries, the same
+/// shape `tsuku-llm manifest add` prints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct ModelManifest {
     pub models: HashMap<String, ModelEntry>,
 }
@@ -155,7 +353,11 @@ impl ModelManifest {
                 download_url: "https://huggingface.co/Qwen/Qwen2.5-14B-Instruct-GGUF/resolve/main/qwen2.5-14b-instruct-q4_k_m-00001-of-00003.gguf"
                     .to_string(),
                 split_count: 3,
-                supported_backends: vec![Backend::Cuda, Backend::Metal, Backend::Vulkan],
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda, Backend::Rocm, Backend::Metal, Backend::Vulkan],
+                chat_template: Some(ChatTemplate::ChatMl),
             },
         );
 
@@ -169,13 +371,73 @@ impl ModelManifest {
                 download_url: "https://huggingface.co/Qwen/Qwen2.5-7B-Instruct-GGUF/resolve/main/qwen2.5-7b-instruct-q4_k_m-00001-of-00002.gguf"
                     .to_string(),
                 split_count: 2,
-                supported_backends: vec![Backend::Cuda, Backend::Metal, Backend::Vulkan],
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda, Backend::Rocm, Backend::Metal, Backend::Vulkan],
+                chat_template: Some(ChatTemplate::ChatMl),
+            },
+        );
+
+        // Qwen 2.5 7B Instruct Q8 (split into 3 files, ~8.1 GB total). Only
+        // selected on request (TSUKU_LLM_QUANTIZATION=q8).
+        models.insert(
+            "qwen2.5-7b-instruct-q8".to_string(),
+            ModelEntry {
+                quantization: "q8_0".to_string(),
+                size_bytes: 8_098_525_888,
+                sha256: "".to_string(), // TODO: compute after download validation
+                download_url: "https://huggingface.co/Qwen/Qwen2.5-7B-Instruct-GGUF/resolve/main/qwen2.5-7b-instruct-q8_0-00001-of-00003.gguf"
+                    .to_string(),
+                split_count: 3,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda, Backend::Rocm, Backend::Metal, Backend::Vulkan],
+                chat_template: Some(ChatTemplate::ChatMl),
             },
         );
 
         Self { models }
     }
 
+    /// Load a manifest from a JSON file.
+    ///
+    /// With a `public_key` (minisign, base64), the file must be signed by it:
+    /// the detached signature is read from `<path>.minisig`, and a manifest
+    /// without a valid one is refused. This keeps a compromised mirror from
+    /// swapping in its own URLs and matching checksums.
+    pub fn from_path(path: &Path, public_key: Option<&str>) -> Result<Self, ManifestError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| ManifestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if let Some(public_key) = public_key {
+            verify_signature(path, contents.as_bytes(), public_key)?;
+        }
+        let manifest: Self =
+            serde_json::from_str(&contents).map_err(|source| ManifestError::Parse {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        for (name, entry) in &manifest.models {
+            entry.validate(name)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Load the manifest at `path` if it exists, otherwise the built-in one.
+    ///
+    /// The built-in manifest ships with the binary, so it's trusted whether
+    /// or not a `public_key` is given.
+    pub fn load_or_default(path: &Path, public_key: Option<&str>) -> Result<Self, ManifestError> {
+        if path.exists() {
+            Self::from_path(path, public_key)
+        } else {
+            Ok(Self::new())
+        }
+    }
+
     /// Get a model entry by name.
     pub fn get(&self, name: &str) -> Option<&ModelEntry> {
         self.models.get(name)
@@ -187,6 +449,29 @@ impl ModelManifest {
     }
 }
 
+/// Check the minisign signature of the manifest at `path`, whose contents
+/// are `contents`.
+fn verify_signature(path: &Path, contents: &[u8], public_key: &str) -> Result<(), ManifestError> {
+    let untrusted = |reason: String| ManifestError::Signature {
+        path: path.to_path_buf(),
+        reason,
+    };
+    let public_key = minisign_verify::PublicKey::from_base64(public_key)
+        .map_err(|e| untrusted(format!("invalid public key: {}", e)))?;
+
+    let mut signature_path = path.as_os_str().to_os_string();
+    signature_path.push(".minisig");
+    let signature_path = PathBuf::from(signature_path);
+    let signature = std::fs::read_to_string(&signature_path)
+        .map_err(|e| untrusted(format!("can't read {}: {}", signature_path.display(), e)))?;
+    let signature = minisign_verify::Signature::decode(&signature)
+        .map_err(|e| untrusted(format!("invalid signature file: {}", e)))?;
+
+    public_key
+        .verify(contents, &signature, false)
+        .map_err(|e| untrusted(format!("signature check failed: {}", e)))
+}
+
 /// Configuration overrides for model selection.
 #[derive(Debug, Clone, Default)]
 pub struct ModelConfig {
@@ -194,6 +479,8 @@ pub struct ModelConfig {
     pub local_model: Option<String>,
     /// Override automatic backend selection
     pub local_backend: Option<String>,
+    /// Preferred quantization (e.g. "q8_0", or "q8" for any Q8 variant)
+    pub quantization: Option<String>,
 }
 
 /// Selects appropriate models based on hardware capabilities.
@@ -202,15 +489,18 @@ pub struct ModelSelector {
     config: ModelConfig,
 }
 
-// VRAM thresholds in bytes.
+// VRAM in bytes. A GPU needs at least MINIMUM_VRAM to be used at all.
 // Model VRAM usage (Q4_K_M with 24K context, approximate):
 //   14B: ~12.6 GB (model 8.1 GB + KV cache 4.5 GB)
 //   7B:  ~6.5 GB  (model 4.7 GB + KV cache 1.5 GB + compute 0.3 GB)
 const GB: u64 = 1_000_000_000;
-const VRAM_THRESHOLD_14B: u64 = 14 * GB;
-const VRAM_THRESHOLD_7B: u64 = 8 * GB;
+const MINIMUM_VRAM: u64 = 8 * GB;
 const MINIMUM_VRAM_GB: f64 = 8.0;
 
+/// Quantization of the models picked automatically; other variants are only
+/// used on request.
+const DEFAULT_QUANTIZATION: &str = "q4";
+
 impl ModelSelector {
     /// Create a new selector with the default manifest.
     pub fn new() -> Self {
@@ -221,6 +511,7 @@ impl ModelSelector {
     }
 
     /// Create a new selector with custom config.
+    #[cfg(test)]
     pub fn with_config(config: ModelConfig) -> Self {
         Self {
             manifest: ModelManifest::new(),
@@ -228,7 +519,7 @@ impl ModelSelector {
         }
     }
 
-    /// Create a new selector with custom manifest and config (for testing).
+    /// Create a new selector with custom manifest and config.
     pub fn with_manifest_and_config(manifest: ModelManifest, config: ModelConfig) -> Self {
         Self { manifest, config }
     }
@@ -248,8 +539,8 @@ impl ModelSelector {
             return Err(SelectionError::NoGpuDetected);
         }
 
-        // Require minimum VRAM for 7B model
-        if profile.vram_bytes < VRAM_THRESHOLD_7B {
+        // Require minimum VRAM
+        if profile.vram_bytes < MINIMUM_VRAM {
             let vram_gb = profile.vram_bytes as f64 / GB as f64;
             return Err(SelectionError::InsufficientVram {
                 vram_gb,
@@ -257,22 +548,146 @@ impl ModelSelector {
             });
         }
 
-        let model_name = self.select_model_for_hardware(profile);
         let backend = self.select_backend(profile)?;
+        let mut model_name = self.select_model_for_hardware(profile, backend)?;
+
+        // A quantization preference is best-effort for auto-selected models,
+        // and only taken when that variant also fits in VRAM
+        if let Some(ref quantization) = self.config.quantization {
+            match self.variant_with_quantization(&model_name, quantization, backend) {
+                Some(variant)
+                    if vram_needed(&self.manifest.models[&variant]) <= profile.vram_bytes =>
+                {
+                    model_name = variant
+                }
+                Some(variant) => warn!(
+                    "{} needs more than the {:.1} GB of VRAM available, using {}",
+                    variant,
+                    profile.vram_bytes as f64 / GB as f64,
+                    model_name
+                ),
+                None => warn!(
+                    "No {} variant of {} for {}, using {}",
+                    quantization,
+                    model_family(&model_name),
+                    backend,
+                    model_name
+                ),
+            }
+        }
 
         self.build_spec(&model_name, backend)
     }
 
-    /// Select model based on VRAM. Caller must ensure GPU is present and
-    /// has at least VRAM_THRESHOLD_7B.
-    fn select_model_for_hardware(&self, profile: &HardwareProfile) -> String {
-        if profile.vram_bytes >= VRAM_THRESHOLD_14B {
-            "qwen2.5-14b-instruct-q4".to_string()
-        } else {
-            "qwen2.5-7b-instruct-q4".to_string()
+    /// The spec for a model picked by name, checking that it's in the
+    /// manifest and supports this machine's backend.
+    pub fn select_named(
+        &self,
+        model_name: &str,
+        profile: &HardwareProfile,
+    ) -> Result<ModelSpec, SelectionError> {
+        self.build_spec_from_override(model_name, profile)
+    }
+
+    /// The next smaller manifest model after `current` with the same
+    /// quantization that supports this machine's backend.
+    ///
+    /// Returns `None` for the smallest one, for models that aren't in the
+    /// manifest, and when no backend can be selected.
+    pub fn select_smaller(&self, current: &str, profile: &HardwareProfile) -> Option<String> {
+        let current = self.manifest.get(current)?;
+        let backend = self.select_backend(profile).ok()?;
+        self.manifest
+            .models
+            .iter()
+            .filter(|(_, entry)| {
+                entry.quantization == current.quantization
+                    && entry.size_bytes < current.size_bytes
+                    && entry.supported_backends.contains(&backend)
+            })
+            .max_by_key(|(name, entry)| (entry.size_bytes, Reverse(name.as_str())))
+            .map(|(name, _)| name.clone())
+    }
+
+    /// The largest default-quantization model for `backend` that fits in
+    /// the GPU's VRAM. Caller must ensure GPU is present.
+    fn select_model_for_hardware(
+        &self,
+        profile: &HardwareProfile,
+        backend: Backend,
+    ) -> Result<String, SelectionError> {
+        let mut candidates: Vec<(&String, &ModelEntry)> = self
+            .manifest
+            .models
+            .iter()
+            .filter(|(_, entry)| {
+                quantization_matches(&entry.quantization, DEFAULT_QUANTIZATION)
+                    && entry.supported_backends.contains(&backend)
+            })
+            .collect();
+        candidates.sort_by_key(|(name, entry)| (Reverse(entry.size_bytes), name.as_str()));
+
+        let smallest = candidates.last().ok_or_else(|| SelectionError::NoSuitableModel {
+            backend: backend.to_string(),
+        })?;
+        match candidates
+            .iter()
+            .find(|(_, entry)| vram_needed(entry) <= profile.vram_bytes)
+        {
+            Some((name, _)) => Ok(name.to_string()),
+            None => Err(SelectionError::InsufficientVram {
+                vram_gb: profile.vram_bytes as f64 / GB as f64,
+                minimum_gb: vram_needed(smallest.1) as f64 / GB as f64,
+            }),
         }
     }
 
+    /// The manifest entry in `model_name`'s family (the same name up to the
+    /// quantization suffix) with the given quantization that supports
+    /// `backend`.
+    fn variant_with_quantization(
+        &self,
+        model_name: &str,
+        quantization: &str,
+        backend: Backend,
+    ) -> Option<String> {
+        let family = model_family(model_name);
+        let mut variants: Vec<(&String, &ModelEntry)> = self
+            .manifest
+            .models
+            .iter()
+            .filter(|(name, entry)| {
+                model_family(name) == family
+                    && quantization_matches(&entry.quantization, quantization)
+                    && entry.supported_backends.contains(&backend)
+            })
+            .collect();
+        // Prefer the named model itself, then an exact quantization match
+        variants.sort_by_key(|(name, entry)| {
+            (
+                name.as_str() != model_name,
+                !entry.quantization.eq_ignore_ascii_case(quantization),
+                name.as_str(),
+            )
+        });
+        variants.first().map(|(name, _)| name.to_string())
+    }
+
+    /// Quantizations available in `model_name`'s family, for error messages.
+    fn family_quantizations(&self, model_name: &str) -> Vec<String> {
+        let family = model_family(model_name);
+        let mut quantizations: Vec<String> = self
+            .manifest
+            .models
+            .iter()
+            .filter(|(name, _)| model_family(name) == family)
+            .map(|(_, entry)| entry.quantization.clone())
+            .collect();
+        quantizations.sort();
+        quantizations.dedup();
+        quantizations
+    }
+
     /// Select backend based on hardware and config.
     fn select_backend(&self, profile: &HardwareProfile) -> Result<Backend, SelectionError> {
         // Check for config override
@@ -280,7 +695,7 @@ impl ModelSelector {
             let backend: Backend = backend_str.parse().map_err(|_| {
                 SelectionError::InvalidConfigBackend {
                     backend: backend_str.clone(),
-                    reason: "must be one of: cuda, metal, vulkan".to_string(),
+                    reason: "must be one of: cuda, rocm, metal, vulkan".to_string(),
                 }
             })?;
 
@@ -291,6 +706,7 @@ impl ModelSelector {
         // Auto-select based on detected GPU
         match profile.gpu_backend {
             GpuBackend::Cuda => Ok(Backend::Cuda),
+            GpuBackend::Rocm => Ok(Backend::Rocm),
             GpuBackend::Metal => Ok(Backend::Metal),
             GpuBackend::Vulkan => Ok(Backend::Vulkan),
             GpuBackend::None => Err(SelectionError::NoGpuDetected),
@@ -310,6 +726,14 @@ impl ModelSelector {
                     });
                 }
             }
+            Backend::Rocm => {
+                if profile.gpu_backend != GpuBackend::Rocm {
+                    return Err(SelectionError::InvalidConfigBackend {
+                        backend: backend_str,
+                        reason: "ROCm not available on this system".to_string(),
+                    });
+                }
+            }
             Backend::Metal => {
                 if profile.gpu_backend != GpuBackend::Metal {
                     return Err(SelectionError::InvalidConfigBackend {
@@ -319,7 +743,10 @@ impl ModelSelector {
                 }
             }
             Backend::Vulkan => {
-                if profile.gpu_backend != GpuBackend::Vulkan && profile.gpu_backend != GpuBackend::Cuda {
+                if !matches!(
+                    profile.gpu_backend,
+                    GpuBackend::Vulkan | GpuBackend::Cuda | GpuBackend::Rocm
+                ) {
                     return Err(SelectionError::InvalidConfigBackend {
                         backend: backend_str,
                         reason: "Vulkan not available on this system".to_string(),
@@ -345,6 +772,22 @@ impl ModelSelector {
 
         let backend = self.select_backend(profile)?;
 
+        // For an explicitly chosen model, a missing quantization is an error
+        let (model_name, entry) = match self.config.quantization {
+            Some(ref quantization) => {
+                let variant = self
+                    .variant_with_quantization(model_name, quantization, backend)
+                    .ok_or_else(|| SelectionError::QuantizationUnavailable {
+                        model: model_name.to_string(),
+                        quantization: quantization.clone(),
+                        available: self.family_quantizations(model_name),
+                    })?;
+                let entry = &self.manifest.models[&variant];
+                (variant, entry)
+            }
+            None => (model_name.to_string(), entry),
+        };
+
         // Validate the model supports the selected backend
         if !entry.supported_backends.contains(&backend) {
             return Err(SelectionError::InvalidConfigBackend {
@@ -363,6 +806,7 @@ impl ModelSelector {
             size_bytes: entry.size_bytes,
             sha256: entry.sha256.clone(),
             download_url: entry.download_url.clone(),
+            chat_template: entry.chat_template,
         })
     }
 
@@ -381,6 +825,7 @@ impl ModelSelector {
             size_bytes: entry.size_bytes,
             sha256: entry.sha256.clone(),
             download_url: entry.download_url.clone(),
+            chat_template: entry.chat_template,
         })
     }
 
@@ -396,6 +841,26 @@ impl Default for ModelSelector {
     }
 }
 
+/// VRAM a model needs: its weights plus about half again for the KV cache
+/// and compute buffers.
+fn vram_needed(entry: &ModelEntry) -> u64 {
+    entry.size_bytes.saturating_add(entry.size_bytes / 2)
+}
+
+/// A model's name without its quantization suffix, e.g. "qwen2.5-7b-instruct"
+/// for "qwen2.5-7b-instruct-q4".
+fn model_family(name: &str) -> &str {
+    name.rsplit_once('-').map_or(name, |(family, _)| family)
+}
+
+/// Whether an entry's quantization satisfies a requested one: equal, or the
+/// request names a family of quantizations ("q4" matches "q4_k_m").
+fn quantization_matches(quantization: &str, requested: &str) -> bool {
+    let quantization = quantization.to_ascii_lowercase();
+    let requested = requested.to_ascii_lowercase();
+    quantization == requested || quantization.starts_with(&format!("{}_", requested))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -405,6 +870,7 @@ mod tests {
         HardwareProfile {
             gpu_backend: gpu,
             vram_bytes: vram_gb * GB,
+            gpu_name: None,
             ram_bytes: ram_gb * GB,
             cpu_features: CpuFeatures::default(),
         }
@@ -432,6 +898,35 @@ mod tests {
         assert_eq!(spec.backend, Backend::Cuda);
     }
 
+    #[test]
+    fn test_gpu_below_14b_threshold_selects_7b() {
+        let selector = ModelSelector::new();
+        let profile = make_profile(GpuBackend::Cuda, 12, 32);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.name, "qwen2.5-7b-instruct-q4");
+    }
+
+    #[test]
+    fn test_rocm_16gb_vram_selects_14b() {
+        let selector = ModelSelector::new();
+        let profile = make_profile(GpuBackend::Rocm, 16, 32);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.name, "qwen2.5-14b-instruct-q4");
+        assert_eq!(spec.backend, Backend::Rocm);
+    }
+
+    #[test]
+    fn test_rocm_8gb_vram_selects_7b() {
+        let selector = ModelSelector::new();
+        let profile = make_profile(GpuBackend::Rocm, 8, 16);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.name, "qwen2.5-7b-instruct-q4");
+        assert_eq!(spec.backend, Backend::Rocm);
+    }
+
     #[test]
     fn test_gpu_low_vram_returns_error() {
         let selector = ModelSelector::new();
@@ -450,6 +945,112 @@ mod tests {
         assert!(matches!(result, Err(SelectionError::NoGpuDetected)));
     }
 
+    fn custom_entry(size_bytes: u64) -> ModelEntry {
+        ModelEntry {
+            size_bytes,
+            ..ModelManifest::new().models["qwen2.5-7b-instruct-q4"].clone()
+        }
+    }
+
+    #[test]
+    fn test_selection_uses_manifest_models() {
+        let mut manifest = ModelManifest::new();
+        manifest
+            .models
+            .insert("big-32b-instruct-q4".to_string(), custom_entry(20 * GB));
+        let selector = ModelSelector::with_manifest_and_config(manifest, ModelConfig::default());
+
+        let spec = selector
+            .select(&make_profile(GpuBackend::Cuda, 40, 64))
+            .unwrap();
+        assert_eq!(spec.name, "big-32b-instruct-q4");
+        let spec = selector
+            .select(&make_profile(GpuBackend::Cuda, 16, 32))
+            .unwrap();
+        assert_eq!(spec.name, "qwen2.5-14b-instruct-q4");
+        assert_eq!(
+            selector
+                .select_smaller(
+                    "big-32b-instruct-q4",
+                    &make_profile(GpuBackend::Cuda, 16, 32)
+                )
+                .as_deref(),
+            Some("qwen2.5-14b-instruct-q4")
+        );
+    }
+
+    #[test]
+    fn test_selection_fails_when_no_manifest_model_fits() {
+        let mut models = HashMap::new();
+        models.insert("big-32b-instruct-q4".to_string(), custom_entry(20 * GB));
+        let manifest = ModelManifest { models };
+        let selector = ModelSelector::with_manifest_and_config(manifest, ModelConfig::default());
+
+        let result = selector.select(&make_profile(GpuBackend::Cuda, 16, 32));
+        assert!(matches!(
+            result,
+            Err(SelectionError::InsufficientVram { minimum_gb, .. }) if minimum_gb == 30.0
+        ));
+    }
+
+    #[test]
+    fn test_select_smaller_steps_down_the_ladder() {
+        let selector = ModelSelector::new();
+        let profile = make_profile(GpuBackend::Cuda, 16, 32);
+        assert_eq!(
+            selector
+                .select_smaller("qwen2.5-14b-instruct-q4", &profile)
+                .as_deref(),
+            Some("qwen2.5-7b-instruct-q4")
+        );
+        assert_eq!(
+            selector.select_smaller("qwen2.5-7b-instruct-q4", &profile),
+            None
+        );
+        assert_eq!(selector.select_smaller("custom-model", &profile), None);
+    }
+
+    #[test]
+    fn test_select_smaller_skips_models_missing_from_manifest() {
+        let mut manifest = ModelManifest::new();
+        manifest.models.remove("qwen2.5-7b-instruct-q4");
+        let selector = ModelSelector::with_manifest_and_config(manifest, ModelConfig::default());
+        let profile = make_profile(GpuBackend::Cuda, 16, 32);
+        assert_eq!(
+            selector.select_smaller("qwen2.5-14b-instruct-q4", &profile),
+            None
+        );
+    }
+
+    #[test]
+    fn test_select_smaller_skips_models_without_the_backend() {
+        let mut manifest = ModelManifest::new();
+        manifest
+            .models
+            .get_mut("qwen2.5-7b-instruct-q4")
+            .unwrap()
+            .supported_backends = vec![Backend::Metal];
+        manifest
+            .models
+            .insert("tiny-3b-instruct-q4".to_string(), custom_entry(2 * GB));
+        let selector = ModelSelector::with_manifest_and_config(manifest, ModelConfig::default());
+
+        let profile = make_profile(GpuBackend::Cuda, 16, 32);
+        assert_eq!(
+            selector
+                .select_smaller("qwen2.5-14b-instruct-q4", &profile)
+                .as_deref(),
+            Some("tiny-3b-instruct-q4")
+        );
+        let profile = make_profile(GpuBackend::Metal, 16, 32);
+        assert_eq!(
+            selector
+                .select_smaller("qwen2.5-14b-instruct-q4", &profile)
+                .as_deref(),
+            Some("qwen2.5-7b-instruct-q4")
+        );
+    }
+
     // Config override tests
 
     #[test]
@@ -457,6 +1058,7 @@ mod tests {
         let config = ModelConfig {
             local_model: Some("qwen2.5-7b-instruct-q4".to_string()),
             local_backend: None,
+            quantization: None,
         };
         let selector = ModelSelector::with_config(config);
         let profile = make_profile(GpuBackend::Cuda, 16, 32);
@@ -471,6 +1073,7 @@ mod tests {
         let config = ModelConfig {
             local_model: Some("nonexistent-model".to_string()),
             local_backend: None,
+            quantization: None,
         };
         let selector = ModelSelector::with_config(config);
         let profile = make_profile(GpuBackend::Cuda, 8, 16);
@@ -487,6 +1090,7 @@ mod tests {
         let config = ModelConfig {
             local_model: None,
             local_backend: Some("invalid-backend".to_string()),
+            quantization: None,
         };
         let selector = ModelSelector::with_config(config);
         let profile = make_profile(GpuBackend::Cuda, 8, 16);
@@ -503,6 +1107,7 @@ mod tests {
         let config = ModelConfig {
             local_model: None,
             local_backend: Some("cuda".to_string()),
+            quantization: None,
         };
         let selector = ModelSelector::with_config(config);
         let profile = make_profile(GpuBackend::None, 0, 16);
@@ -512,6 +1117,142 @@ mod tests {
         assert!(matches!(result, Err(SelectionError::NoGpuDetected)));
     }
 
+    #[test]
+    fn test_rocm_backend_override_requires_rocm() {
+        let config = ModelConfig {
+            local_model: None,
+            local_backend: Some("rocm".to_string()),
+            quantization: None,
+        };
+        let selector = ModelSelector::with_config(config.clone());
+        let profile = make_profile(GpuBackend::Cuda, 8, 16);
+
+        let result = selector.select(&profile);
+        assert!(matches!(
+            result,
+            Err(SelectionError::InvalidConfigBackend { .. })
+        ));
+
+        let selector = ModelSelector::with_config(config);
+        let profile = make_profile(GpuBackend::Rocm, 8, 16);
+        assert_eq!(selector.select(&profile).unwrap().backend, Backend::Rocm);
+    }
+
+    #[test]
+    fn test_vulkan_backend_override_on_rocm() {
+        let config = ModelConfig {
+            local_model: None,
+            local_backend: Some("vulkan".to_string()),
+            quantization: None,
+        };
+        let selector = ModelSelector::with_config(config);
+        let profile = make_profile(GpuBackend::Rocm, 8, 16);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.backend, Backend::Vulkan);
+    }
+
+    // Quantization preference tests
+
+    #[test]
+    fn test_quantization_preference_selects_q8() {
+        let config = ModelConfig {
+            quantization: Some("q8".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_config(config);
+        // Enough for 7B Q8 (~12.2 GB) but not 14B Q4 (~13.7 GB)
+        let profile = make_profile(GpuBackend::Cuda, 13, 32);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.name, "qwen2.5-7b-instruct-q8");
+        assert_eq!(spec.quantization, "q8_0");
+    }
+
+    #[test]
+    fn test_quantization_preference_must_fit_vram() {
+        let config = ModelConfig {
+            quantization: Some("q8".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_config(config);
+
+        for vram_gb in [8, 12] {
+            let profile = make_profile(GpuBackend::Cuda, vram_gb, 32);
+            let spec = selector.select(&profile).unwrap();
+            assert_eq!(spec.name, "qwen2.5-7b-instruct-q4", "{} GB", vram_gb);
+        }
+    }
+
+    #[test]
+    fn test_quantization_preference_falls_back_to_q4() {
+        let config = ModelConfig {
+            quantization: Some("q8_0".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_config(config);
+        // 14B has no Q8 variant in the manifest
+        let profile = make_profile(GpuBackend::Cuda, 16, 32);
+
+        let spec = selector.select(&profile).unwrap();
+        assert_eq!(spec.name, "qwen2.5-14b-instruct-q4");
+        assert_eq!(spec.quantization, "q4_k_m");
+    }
+
+    #[test]
+    fn test_quantization_for_configured_model() {
+        let config = ModelConfig {
+            local_model: Some("qwen2.5-7b-instruct-q4".to_string()),
+            quantization: Some("Q8_0".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_config(config);
+        let profile = make_profile(GpuBackend::Cuda, 16, 32);
+        assert_eq!(
+            selector.select(&profile).unwrap().name,
+            "qwen2.5-7b-instruct-q8"
+        );
+
+        let config = ModelConfig {
+            local_model: Some("qwen2.5-14b-instruct-q4".to_string()),
+            quantization: Some("q8".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_config(config);
+        let err = selector.select(&profile).unwrap_err();
+        assert!(matches!(
+            err,
+            SelectionError::QuantizationUnavailable { ref available, .. }
+                if available == &["q4_k_m".to_string()]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "quantization 'q8' is not available for model 'qwen2.5-14b-instruct-q4' \
+             (available: q4_k_m)"
+        );
+    }
+
+    #[test]
+    fn test_quantization_variant_must_support_backend() {
+        let mut manifest = ModelManifest::new();
+        manifest
+            .models
+            .get_mut("qwen2.5-7b-instruct-q8")
+            .unwrap()
+            .supported_backends = vec![Backend::Cuda];
+        let config = ModelConfig {
+            quantization: Some("q8".to_string()),
+            ..ModelConfig::default()
+        };
+        let selector = ModelSelector::with_manifest_and_config(manifest, config);
+
+        let profile = make_profile(GpuBackend::Vulkan, 12, 32);
+        assert_eq!(
+            selector.select(&profile).unwrap().name,
+            "qwen2.5-7b-instruct-q4"
+        );
+    }
+
     // Backend selection tests
 
     #[test]
@@ -526,6 +1267,7 @@ mod tests {
     #[test]
     fn test_backend_from_str() {
         assert_eq!("cuda".parse::<Backend>().unwrap(), Backend::Cuda);
+        assert_eq!("rocm".parse::<Backend>().unwrap(), Backend::Rocm);
         assert_eq!("metal".parse::<Backend>().unwrap(), Backend::Metal);
         assert_eq!("vulkan".parse::<Backend>().unwrap(), Backend::Vulkan);
         assert_eq!("CUDA".parse::<Backend>().unwrap(), Backend::Cuda); // case insensitive
@@ -553,4 +1295,141 @@ mod tests {
         assert!(spec.size_bytes > 0);
         assert!(!spec.download_url.is_empty());
     }
+
+    #[test]
+    fn test_manifest_round_trip_with_split_entry() {
+        let mut manifest = ModelManifest::new();
+        manifest.models.insert(
+            "mirror-7b".to_string(),
+            ModelEntry {
+                quantization: "q5_k_m".to_string(),
+                size_bytes: 5_400_000_000,
+                sha256: "aa".repeat(32),
+                download_url: "https://mirror.internal/mirror-7b-00001-of-00002.gguf".to_string(),
+                split_count: 2,
+                part_sha256: vec!["aa".repeat(32), "bb".repeat(32)],
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda, Backend::Vulkan],
+                chat_template: None,
+            },
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("models.json");
+        std::fs::write(&path, serde_json::to_string_pretty(&manifest).unwrap()).unwrap();
+
+        let loaded = ModelManifest::from_path(&path, None).unwrap();
+        assert_eq!(loaded.models.len(), manifest.models.len());
+        let entry = loaded.get("mirror-7b").unwrap();
+        assert_eq!(entry.split_count, 2);
+        assert_eq!(entry.part_sha256.len(), 2);
+        assert_eq!(entry.supported_backends, vec![Backend::Cuda, Backend::Vulkan]);
+        assert_eq!(
+            loaded.get("qwen2.5-7b-instruct-q4").unwrap().download_url,
+            manifest.get("qwen2.5-7b-instruct-q4").unwrap().download_url
+        );
+    }
+
+    #[test]
+    fn test_manifest_from_path_rejects_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("models.json");
+
+        std::fs::write(
+            &path,
+            r#"{"m": {"quantization": "q4", "size_bytes": 1, "sha256": "",
+                "download_url": "", "split_count": 1, "supported_backends": ["cuda"]}}"#,
+        )
+        .unwrap();
+        let err = ModelManifest::from_path(&path, None).unwrap_err();
+        assert!(matches!(err, ManifestError::InvalidEntry { .. }), "{}", err);
+
+        std::fs::write(
+            &path,
+            r#"{"m": {"quantization": "q4", "size_bytes": 1, "sha256": "",
+                "download_url": "https://x/m.gguf", "mirror_urls": [""], "split_count": 1,
+                "supported_backends": ["cuda"]}}"#,
+        )
+        .unwrap();
+        let err = ModelManifest::from_path(&path, None).unwrap_err();
+        assert!(matches!(err, ManifestError::InvalidEntry { .. }), "{}", err);
+
+        std::fs::write(
+            &path,
+            r#"{"m": {"quantization": "q4", "size_bytes": 1, "sha256": "",
+                "download_url": "https://x/m-00001-of-00003.gguf", "split_count": 2,
+                "supported_backends": ["cuda"]}}"#,
+        )
+        .unwrap();
+        let err = ModelManifest::from_path(&path, None).unwrap_err();
+        assert!(err.to_string().contains("split_count is 2"), "{}", err);
+
+        std::fs::write(
+            &path,
+            r#"{"m": {"quantization": "q4", "size_bytes": 1, "sha256": "",
+                "download_url": "https://x/m.gguf", "split_count": 1,
+                "supported_backends": ["cpu"]}}"#,
+        )
+        .unwrap();
+        let err = ModelManifest::from_path(&path, None).unwrap_err();
+        assert!(matches!(err, ManifestError::Parse { .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_manifest_load_or_default_falls_back_to_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = ModelManifest::load_or_default(&dir.path().join("models.json"), None).unwrap();
+        assert!(manifest.get("qwen2.5-7b-instruct-q4").is_some());
+    }
+
+    /// A manifest signed with minisign, and the key that signed it.
+    const SIGNED_MANIFEST: &str = r#"{"signed": {"quantization": "q4", "size_bytes": 1, "sha256": "", "download_url": "https://x/signed.gguf", "split_count": 1, "supported_backends": ["cuda"]}}"#;
+    const MANIFEST_SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RUR0c3VrdWxsbUDodm98QtXI6HKVd8RXGm3ZLYa3ltbyLhm9hd2f0Zk5SQdeq8mQGQKDpc2ot9Bc53gyV4Q9XORGSjj+Pj78dw4=
+trusted comment: tsuku-llm test manifest
+RlpkwByUIgr3K0UN8fnIzP51NO7rP4ey25h7ZWidGc4nivymmpelpcTb1Gqpoa5kKzPVjlNff1Nr+A4In1FVBA==
+";
+    const MANIFEST_PUBLIC_KEY: &str = "RWR0c3VrdWxsbQOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4";
+
+    /// Write `contents` as models.json, with the test signature next to it.
+    fn write_signed(dir: &Path, contents: &str) -> PathBuf {
+        let path = dir.join("models.json");
+        std::fs::write(&path, contents).unwrap();
+        std::fs::write(dir.join("models.json.minisig"), MANIFEST_SIGNATURE).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_manifest_with_valid_signature_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_signed(dir.path(), SIGNED_MANIFEST);
+
+        let manifest = ModelManifest::from_path(&path, Some(MANIFEST_PUBLIC_KEY)).unwrap();
+        assert_eq!(manifest.get("signed").unwrap().download_url, "https://x/signed.gguf");
+    }
+
+    #[test]
+    fn test_manifest_with_bad_or_missing_signature_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let tampered = SIGNED_MANIFEST.replace("https://x/", "https://evil/");
+        let path = write_signed(dir.path(), &tampered);
+        let err = ModelManifest::from_path(&path, Some(MANIFEST_PUBLIC_KEY)).unwrap_err();
+        assert!(matches!(err, ManifestError::Signature { .. }), "{}", err);
+
+        std::fs::remove_file(dir.path().join("models.json.minisig")).unwrap();
+        std::fs::write(&path, SIGNED_MANIFEST).unwrap();
+        let err = ModelManifest::from_path(&path, Some(MANIFEST_PUBLIC_KEY)).unwrap_err();
+        assert!(matches!(err, ManifestError::Signature { .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_manifest_signature_not_checked_without_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let tampered = SIGNED_MANIFEST.replace("https://x/", "https://evil/");
+        let path = write_signed(dir.path(), &tampered);
+
+        let manifest = ModelManifest::from_path(&path, None).unwrap();
+        assert_eq!(manifest.get("signed").unwrap().download_url, "https://evil/signed.gguf");
+    }
 }
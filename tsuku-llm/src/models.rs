@@ -3,16 +3,25 @@
 //! Handles downloading GGUF model files from CDN with progress display
 //! and SHA256 verification.
 
+use std::collections::{HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
+use futures_util::stream::FuturesUnordered;
 use futures_util::StreamExt;
+use reqwest::header::{AUTHORIZATION, CONTENT_ENCODING, CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
 use sha2::{Digest, Sha256};
 use thiserror::Error;
-use tokio::fs::{self, File};
+use tokio::fs::{self, File, OpenOptions};
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
 use tracing::{debug, info, warn};
 
-use crate::model::{ModelManifest, ModelSpec};
+use crate::gguf::{GgufError, GgufMetadata};
+use crate::model::{Backend, ModelEntry, ModelManifest};
+use crate::template::ChatTemplate;
 
 /// Errors that can occur during model operations.
 #[derive(Error, Debug)]
@@ -33,6 +42,18 @@ pub enum ModelError {
         actual: String,
     },
 
+    #[error("size mismatch: expected {expected} bytes, got {actual}")]
+    SizeMismatch { expected: u64, actual: u64 },
+
+    #[error("invalid GGUF file: {0}")]
+    Gguf(#[from] GgufError),
+
+    #[error("insufficient disk space: need {needed} bytes, {available} available")]
+    InsufficientDiskSpace { needed: u64, available: u64 },
+
+    #[error("invalid split model URL '{url}': {reason}")]
+    InvalidSplitUrl { url: String, reason: String },
+
     #[error("download failed after {attempts} attempts: {last_error}")]
     DownloadFailed {
         attempts: u32,
@@ -40,6 +61,150 @@ pub enum ModelError {
     },
 }
 
+impl ModelError {
+    /// Short machine-readable category, used as a structured log field.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ModelError::NotInManifest(_) => "not_in_manifest",
+            ModelError::Network(e) if e.is_timeout() => "timeout",
+            ModelError::Network(e) if e.is_status() => "http_status",
+            ModelError::Network(_) => "network",
+            ModelError::Io(_) => "io",
+            ModelError::ChecksumMismatch { .. } => "checksum_mismatch",
+            ModelError::SizeMismatch { .. } => "size_mismatch",
+            ModelError::Gguf(_) => "gguf",
+            ModelError::InsufficientDiskSpace { .. } => "disk_space",
+            ModelError::InvalidSplitUrl { .. } => "invalid_split_url",
+            ModelError::DownloadFailed { .. } => "download_failed",
+        }
+    }
+
+    /// Whether a download failing with this error may succeed if retried.
+    ///
+    /// Connection failures, timeouts, server errors, and the 408 and 429
+    /// client errors are transient. Other 4xx responses (e.g. 404 or 401)
+    /// won't change on a retry. A corrupt file is fetched again from scratch.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            ModelError::Network(e) => e.status().is_none_or(|status| {
+                !status.is_client_error()
+                    || matches!(status, StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS)
+            }),
+            ModelError::Io(_)
+            | ModelError::ChecksumMismatch { .. }
+            | ModelError::SizeMismatch { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the downloaded file is complete but wrong, so a retry must
+    /// start over rather than resume it.
+    fn is_corrupt_download(&self) -> bool {
+        matches!(
+            self,
+            ModelError::ChecksumMismatch { .. } | ModelError::SizeMismatch { .. }
+        )
+    }
+}
+
+/// Attempts made for each file before a download fails.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Attempts made for a file served from `urls`, one of which is picked per
+/// attempt by `Mirrors::next_url`. Every URL gets at least one attempt.
+fn download_attempts(urls: &[String]) -> u32 {
+    DOWNLOAD_ATTEMPTS.max(urls.len() as u32)
+}
+
+/// The URLs a file is served from, tried in turn: the primary first, then
+/// each mirror, wrapping around.
+struct Mirrors {
+    urls: Vec<String>,
+    /// Index of the URL to try next; kept on the same URL when an earlier
+    /// one is removed
+    next: usize,
+}
+
+impl Mirrors {
+    fn new(urls: Vec<String>) -> Self {
+        Self { urls, next: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// The URL to fetch on the next attempt.
+    fn next_url(&mut self) -> String {
+        let index = self.next % self.urls.len();
+        self.next = index + 1;
+        self.urls[index].clone()
+    }
+
+    /// Stop trying `url`.
+    fn remove(&mut self, url: &str) {
+        if let Some(index) = self.urls.iter().position(|u| u == url) {
+            self.urls.remove(index);
+            if index < self.next {
+                self.next -= 1;
+            }
+        }
+    }
+}
+
+/// Record a failed download attempt at `url` and get ready for the next
+/// one. Returns false if there is nothing left to try.
+///
+/// Emits one structured event per retry (fields `attempt`, `url`,
+/// `error_kind`, `backoff_ms`) so JSON logs can be filtered on them. The
+/// backoff doubles from one second. An error retrying can't fix (see
+/// `ModelError::is_retriable`) drops `url` from `mirrors` instead, leaving
+/// only the other mirrors to try, without waiting. After the last of
+/// `attempts`, or once no URLs are left, a final event is logged instead.
+async fn retry_after_failure(
+    attempt: u32,
+    attempts: u32,
+    mirrors: &mut Mirrors,
+    url: &str,
+    error: &ModelError,
+) -> bool {
+    if !error.is_retriable() {
+        mirrors.remove(url);
+        if !mirrors.is_empty() && attempt < attempts {
+            warn!(
+                attempt,
+                url,
+                error_kind = error.kind(),
+                error = %error,
+                "download failed, trying the next mirror"
+            );
+            return true;
+        }
+    }
+    if attempt >= attempts || mirrors.is_empty() {
+        warn!(
+            attempts = attempt,
+            url,
+            error_kind = error.kind(),
+            error = %error,
+            "download failed, giving up"
+        );
+        return false;
+    }
+
+    let backoff = Duration::from_secs(1 << (attempt - 1));
+    warn!(
+        attempt,
+        url,
+        error_kind = error.kind(),
+        backoff_ms = backoff.as_millis() as u64,
+        error = %error,
+        "download attempt failed, retrying"
+    );
+    tokio::time::sleep(backoff).await;
+    true
+}
+
 /// Progress information during download.
 #[derive(Debug, Clone)]
 pub struct DownloadProgress {
@@ -47,6 +212,295 @@ pub struct DownloadProgress {
     pub bytes_downloaded: u64,
     /// Total bytes to download (from Content-Length)
     pub total_bytes: u64,
+    /// Recent download rate over the last few seconds
+    pub bytes_per_second: f64,
+    /// Estimated seconds remaining (None when the total or rate is unknown)
+    pub eta_seconds: Option<u64>,
+    /// For split models, the part this update is about. The totals above
+    /// still cover the whole model.
+    pub part: Option<PartProgress>,
+}
+
+/// Progress of one part of a split model download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartProgress {
+    /// Which part, starting at 1
+    pub index: usize,
+    /// Number of parts in the model
+    pub count: usize,
+    /// Bytes of this part downloaded so far
+    pub bytes_downloaded: u64,
+    /// Size of this part (from Content-Length)
+    pub total_bytes: u64,
+}
+
+impl DownloadProgress {
+    fn new(bytes_downloaded: u64, total_bytes: u64, bytes_per_second: f64) -> Self {
+        let remaining = total_bytes.saturating_sub(bytes_downloaded);
+        let eta_seconds = (total_bytes > 0 && bytes_per_second > 0.0)
+            .then(|| (remaining as f64 / bytes_per_second).ceil() as u64);
+        Self {
+            bytes_downloaded,
+            total_bytes,
+            bytes_per_second,
+            eta_seconds,
+            part: None,
+        }
+    }
+}
+
+impl std::fmt::Display for DownloadProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format_bytes(self.bytes_downloaded as f64))?;
+        if self.total_bytes > 0 {
+            write!(f, " / {}", format_bytes(self.total_bytes as f64))?;
+        }
+        write!(f, " at {}/s", format_bytes(self.bytes_per_second))?;
+        if let Some(eta) = self.eta_seconds {
+            write!(f, ", ETA {}", format_eta(eta))?;
+        }
+        if let Some(part) = self.part {
+            write!(f, " (part {} of {})", part.index, part.count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Format a byte count with a binary unit suffix (e.g., "1.5 GB").
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Format a duration in seconds as e.g. "1h05m", "3m20s" or "45s".
+fn format_eta(seconds: u64) -> String {
+    match seconds {
+        s if s >= 3600 => format!("{}h{:02}m", s / 3600, (s % 3600) / 60),
+        s if s >= 60 => format!("{}m{:02}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Window over which the download rate is averaged.
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Measures download throughput over a rolling window.
+struct ThroughputMeter {
+    /// (time, bytes downloaded) samples, oldest first
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl ThroughputMeter {
+    /// Start measuring from `start_bytes` already on disk.
+    fn new(start_bytes: u64) -> Self {
+        Self {
+            samples: VecDeque::from([(Instant::now(), start_bytes)]),
+        }
+    }
+
+    /// Record the current byte count and return the windowed rate.
+    fn record(&mut self, bytes_downloaded: u64) -> f64 {
+        let now = Instant::now();
+        self.samples.push_back((now, bytes_downloaded));
+
+        // Keep one sample at or beyond the window edge as the baseline
+        while self.samples.len() > 2 && now.duration_since(self.samples[1].0) >= RATE_WINDOW {
+            self.samples.pop_front();
+        }
+
+        let (start, start_bytes) = self.samples[0];
+        let elapsed = now.duration_since(start).as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        bytes_downloaded.saturating_sub(start_bytes) as f64 / elapsed
+    }
+
+    /// Record the current byte count and build a progress report.
+    fn progress(&mut self, bytes_downloaded: u64, total_bytes: u64) -> DownloadProgress {
+        let rate = self.record(bytes_downloaded);
+        DownloadProgress::new(bytes_downloaded, total_bytes, rate)
+    }
+}
+
+/// Incoming chunks are collected into a buffer of this size before being
+/// written and hashed, so fast links don't cost a syscall per small chunk.
+const WRITE_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Minimum time between progress callbacks during a download.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Free space required on top of the model size, so a download that just
+/// fits doesn't leave the filesystem completely full.
+const DISK_SPACE_MARGIN: u64 = 256 * 1024 * 1024;
+
+/// Maximum number of split model parts downloaded at the same time.
+const SPLIT_DOWNLOAD_CONCURRENCY: usize = 3;
+
+/// Maximum number of split parts hashed at once during verification.
+const VERIFY_CONCURRENCY: usize = 4;
+
+/// Combines per-part progress of a split download into a single total.
+struct SplitProgress {
+    /// (bytes_downloaded, total_bytes, bytes_per_second) for each part
+    parts: Mutex<Vec<(u64, u64, f64)>>,
+    /// Expected size of the whole model from the manifest
+    expected_total: u64,
+}
+
+impl SplitProgress {
+    fn new(part_count: usize, expected_total: u64) -> Self {
+        Self {
+            parts: Mutex::new(vec![(0, 0, 0.0); part_count]),
+            expected_total,
+        }
+    }
+
+    /// Record progress for one part and return the aggregate, tagged with
+    /// that part.
+    ///
+    /// Rates of parts downloading in parallel add up to the overall rate.
+    fn update(&self, index: usize, part: &DownloadProgress) -> DownloadProgress {
+        let mut parts = self.parts.lock().unwrap_or_else(|e| e.into_inner());
+        parts[index] = (part.bytes_downloaded, part.total_bytes, part.bytes_per_second);
+        let (downloaded, total, rate) = parts
+            .iter()
+            .fold((0, 0, 0.0), |(d, t, r), &(pd, pt, pr)| (d + pd, t + pt, r + pr));
+        DownloadProgress {
+            part: Some(PartProgress {
+                index: index + 1,
+                count: parts.len(),
+                bytes_downloaded: part.bytes_downloaded,
+                total_bytes: part.total_bytes,
+            }),
+            ..DownloadProgress::new(downloaded, total.max(self.expected_total), rate)
+        }
+    }
+}
+
+/// Paces reads so downloads sharing it stay under a byte rate.
+#[derive(Debug)]
+struct Bandwidth {
+    bytes_per_second: u64,
+    /// When the bytes let through so far will have taken their share of
+    /// the rate
+    next: Mutex<Option<tokio::time::Instant>>,
+}
+
+impl Bandwidth {
+    /// Wait until `bytes` more fit in the rate.
+    ///
+    /// Time spent idle isn't banked, so a download resuming after a pause
+    /// doesn't burst.
+    async fn pace(&self, bytes: usize) {
+        let until = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = tokio::time::Instant::now();
+            let start = next.map_or(now, |next| next.max(now));
+            let share = bytes as f64 / self.bytes_per_second as f64;
+            let until = start + Duration::from_secs_f64(share);
+            *next = Some(until);
+            until
+        };
+        tokio::time::sleep_until(until).await;
+    }
+}
+
+/// Caps on the network use of downloads.
+///
+/// Clones share their budgets, so managers given the same limits are capped
+/// together.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadLimits {
+    bandwidth: Option<Arc<Bandwidth>>,
+    /// Permits for models downloading at once
+    slots: Option<Arc<Semaphore>>,
+}
+
+impl DownloadLimits {
+    /// Limit downloads to `bytes_per_second` in total (split parts fetched
+    /// in parallel share it), and to `max_concurrent` models at a time.
+    pub fn new(bytes_per_second: Option<u64>, max_concurrent: Option<usize>) -> Self {
+        Self {
+            bandwidth: bytes_per_second.filter(|&rate| rate > 0).map(|bytes_per_second| {
+                Arc::new(Bandwidth {
+                    bytes_per_second,
+                    next: Mutex::new(None),
+                })
+            }),
+            slots: max_concurrent
+                .filter(|&n| n > 0)
+                .map(|n| Arc::new(Semaphore::new(n))),
+        }
+    }
+}
+
+/// A Hugging Face access token, for downloads from gated or private repos.
+///
+/// The token is only sent to `huggingface.co` and its `hf.co` short domain,
+/// never to other mirrors.
+#[derive(Clone)]
+pub struct HfAuth {
+    token: String,
+    hosts: Vec<String>,
+}
+
+impl HfAuth {
+    pub fn new(token: String) -> Self {
+        Self {
+            token,
+            hosts: vec!["huggingface.co".to_string(), "hf.co".to_string()],
+        }
+    }
+
+    /// The token in HF_TOKEN, or HUGGING_FACE_HUB_TOKEN as the Hugging Face
+    /// tools also accept.
+    pub fn from_env() -> Option<Self> {
+        ["HF_TOKEN", "HUGGING_FACE_HUB_TOKEN"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|token| !token.trim().is_empty())
+            .map(|token| Self::new(token.trim().to_string()))
+    }
+
+    /// Also send the token to `host`, e.g. a local mock server in tests.
+    #[cfg(test)]
+    fn with_host(mut self, host: &str) -> Self {
+        self.hosts.push(host.to_string());
+        self
+    }
+
+    /// Whether requests to `url` should carry the token.
+    fn applies_to(&self, url: &str) -> bool {
+        reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .is_some_and(|host| self.hosts.iter().any(|h| h.eq_ignore_ascii_case(&host)))
+    }
+}
+
+// Keep the token out of logs
+impl std::fmt::Debug for HfAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HfAuth").field("hosts", &self.hosts).finish_non_exhaustive()
+    }
+}
+
+/// The client managers use unless given one, shared so they share its
+/// connection pool.
+fn default_client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new).clone()
 }
 
 /// Manages model downloads and storage.
@@ -57,6 +511,10 @@ pub struct ModelManager {
     manifest: ModelManifest,
     /// HTTP client for downloads
     client: reqwest::Client,
+    /// Bandwidth and concurrency caps for downloads
+    limits: DownloadLimits,
+    /// Token for gated Hugging Face downloads
+    hf_auth: Option<HfAuth>,
 }
 
 impl ModelManager {
@@ -64,20 +522,53 @@ impl ModelManager {
     ///
     /// # Arguments
     /// * `models_dir` - Directory to store downloaded models
+    #[cfg(test)]
     pub fn new(models_dir: PathBuf) -> Self {
-        Self {
-            models_dir,
-            manifest: ModelManifest::new(),
-            client: reqwest::Client::new(),
-        }
+        Self::with_manifest(models_dir, ModelManifest::new())
     }
 
     /// Create a new ModelManager with a custom manifest (for testing).
     pub fn with_manifest(models_dir: PathBuf, manifest: ModelManifest) -> Self {
+        Self::with_client(models_dir, manifest, default_client())
+    }
+
+    /// Create a new ModelManager that downloads with `client`, e.g. one
+    /// configured with timeouts or a proxy, or shared with other managers.
+    pub fn with_client(
+        models_dir: PathBuf,
+        manifest: ModelManifest,
+        client: reqwest::Client,
+    ) -> Self {
         Self {
             models_dir,
             manifest,
-            client: reqwest::Client::new(),
+            client,
+            limits: DownloadLimits::default(),
+            hf_auth: None,
+        }
+    }
+
+    /// Apply `limits` to this manager's downloads.
+    pub fn with_download_limits(mut self, limits: DownloadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Authenticate downloads from Hugging Face with `auth`.
+    pub fn with_hf_auth(mut self, auth: Option<HfAuth>) -> Self {
+        self.hf_auth = auth;
+        self
+    }
+
+    /// A GET request for `url`, carrying the Hugging Face token if it
+    /// applies to the URL's host.
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        match &self.hf_auth {
+            Some(auth) if auth.applies_to(url) => {
+                request.header(AUTHORIZATION, format!("Bearer {}", auth.token))
+            }
+            _ => request,
         }
     }
 
@@ -90,9 +581,7 @@ impl ModelManager {
     pub fn model_path(&self, model_name: &str) -> PathBuf {
         if let Some(entry) = self.manifest.get(model_name) {
             if entry.split_count > 1 {
-                if let Some(filename) = entry.download_url.rsplit('/').next() {
-                    return self.models_dir.join(filename);
-                }
+                return self.models_dir.join(local_file_name(&entry.download_url));
             }
         }
         self.models_dir.join(format!("{}.gguf", model_name))
@@ -102,7 +591,10 @@ impl ModelManager {
     fn all_model_paths(&self, model_name: &str) -> Vec<PathBuf> {
         if let Some(entry) = self.manifest.get(model_name) {
             if entry.split_count > 1 {
-                return split_file_paths(&self.models_dir, &entry.download_url, entry.split_count);
+                // Manifests are validated on load, so only a bad built-in
+                // entry lands here; its first part is all there is to find
+                return split_file_paths(&self.models_dir, &entry.download_url, entry.split_count)
+                    .unwrap_or_else(|_| vec![self.model_path(model_name)]);
             }
         }
         vec![self.models_dir.join(format!("{}.gguf", model_name))]
@@ -118,6 +610,85 @@ impl ModelManager {
         self.download_dir().join(format!("{}.gguf.part", model_name))
     }
 
+    /// Get the temporary path for an in-progress split part download.
+    fn split_temp_path(&self, path: &Path) -> PathBuf {
+        self.download_dir().join(format!(
+            "{}.part",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ))
+    }
+
+    /// All temporary paths a download of the model may leave behind.
+    fn partial_paths(&self, model_name: &str, entry: &ModelEntry) -> Vec<PathBuf> {
+        let mut paths = vec![self.temp_path(model_name)];
+        if entry.split_count > 1 {
+            let parts = split_file_paths(&self.models_dir, &entry.download_url, entry.split_count)
+                .unwrap_or_default();
+            paths.extend(parts.iter().map(|p| self.split_temp_path(p)));
+        }
+        paths
+    }
+
+    /// Remove the partial files of an abandoned download.
+    ///
+    /// Completed split parts are kept; only `.part` files are removed.
+    pub async fn remove_partial_downloads(&self, model_name: &str) {
+        let Some(entry) = self.manifest.get(model_name) else {
+            return;
+        };
+        for path in self.partial_paths(model_name, entry) {
+            match fs::remove_file(&path).await {
+                Ok(()) => info!("Removed partial download {:?}", path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to remove partial download {:?}: {}", path, e),
+            }
+        }
+    }
+
+    /// Model files that don't belong to any of the `keep` models: GGUF files
+    /// in the models directory (including other models' split parts) and
+    /// partial downloads.
+    pub async fn prunable(&self, keep: &[&str]) -> Result<Vec<PathBuf>, ModelError> {
+        let mut kept = HashSet::new();
+        for &name in keep {
+            kept.extend(self.all_model_paths(name));
+            kept.insert(self.temp_path(name));
+            if let Some(entry) = self.manifest.get(name) {
+                kept.extend(self.partial_paths(name, entry));
+            }
+        }
+
+        let mut stale = Vec::new();
+        for (dir, extension) in [(self.models_dir.clone(), "gguf"), (self.download_dir(), "part")] {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_file()
+                    && path.extension().is_some_and(|ext| ext == extension)
+                    && !kept.contains(&path)
+                {
+                    stale.push(path);
+                }
+            }
+        }
+        stale.sort();
+        Ok(stale)
+    }
+
+    /// Remove the files `prunable` reports, returning the removed paths.
+    pub async fn prune(&self, keep: &[&str]) -> Result<Vec<PathBuf>, ModelError> {
+        let stale = self.prunable(keep).await?;
+        for path in &stale {
+            fs::remove_file(path).await?;
+            info!("Removed {:?}", path);
+        }
+        Ok(stale)
+    }
+
     /// Check if a model exists and has valid checksum.
     pub async fn is_available(&self, model_name: &str) -> bool {
         // For split models, check all parts exist
@@ -138,10 +709,30 @@ impl ModelManager {
         }
     }
 
+    /// Total size in bytes of a model's files on disk, summing every part of
+    /// a split model. Missing files count as empty.
+    pub fn size_on_disk(&self, model_name: &str) -> u64 {
+        self.all_model_paths(model_name)
+            .iter()
+            .filter_map(|path| std::fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Total size in bytes a download of the model fetches, across all its
+    /// parts. 0 if the model isn't in the manifest.
+    pub fn total_download_size(&self, model_name: &str) -> u64 {
+        self.manifest
+            .get(model_name)
+            .map_or(0, ModelEntry::total_download_size)
+    }
+
     /// Verify the SHA256 checksum of an existing model file.
     ///
     /// For models with empty checksums (not yet computed), verification
-    /// is skipped and the model is assumed valid if the file exists.
+    /// is skipped and the model is assumed valid if the file exists. Split
+    /// models with per-part checksums have every part checked, and corrupt
+    /// parts are deleted so the next download fetches them again.
     pub async fn verify(&self, model_name: &str) -> Result<bool, ModelError> {
         let entry = self
             .manifest
@@ -153,6 +744,10 @@ impl ModelManager {
             return Ok(false);
         }
 
+        if entry.split_count > 1 && !entry.part_sha256.is_empty() {
+            return self.verify_split_parts(model_name, entry).await;
+        }
+
         // Skip verification when checksum is not yet computed
         if entry.sha256.is_empty() {
             debug!("Skipping checksum verification for {} (no checksum in manifest)", model_name);
@@ -173,11 +768,73 @@ impl ModelManager {
         Ok(true)
     }
 
+    /// Verify each part of a split model against its manifest checksum.
+    ///
+    /// Parts are independent files, so up to `VERIFY_CONCURRENCY` of them
+    /// are hashed at once. The model is valid only if every part is.
+    async fn verify_split_parts(
+        &self,
+        model_name: &str,
+        entry: &ModelEntry,
+    ) -> Result<bool, ModelError> {
+        let paths = split_file_paths(&self.models_dir, &entry.download_url, entry.split_count)?;
+        let mut checks = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| self.verify_split_part(model_name, entry, i, path));
+
+        let mut in_flight: FuturesUnordered<_> = checks.by_ref().take(VERIFY_CONCURRENCY).collect();
+        let mut valid = true;
+        while let Some(result) = in_flight.next().await {
+            if !result? {
+                valid = false;
+            }
+            if let Some(next) = checks.next() {
+                in_flight.push(next);
+            }
+        }
+
+        Ok(valid)
+    }
+
+    /// Check one split part, deleting it if its checksum doesn't match.
+    async fn verify_split_part(
+        &self,
+        model_name: &str,
+        entry: &ModelEntry,
+        index: usize,
+        path: &Path,
+    ) -> Result<bool, ModelError> {
+        if !path.exists() {
+            return Ok(false);
+        }
+        let Some(expected_hash) = entry.part_checksum(index) else {
+            return Ok(true);
+        };
+
+        let actual_hash = compute_file_sha256(path).await?;
+        if actual_hash != expected_hash {
+            warn!(
+                "Checksum mismatch for {} split {}/{}: expected {}, got {}; removing it",
+                model_name,
+                index + 1,
+                entry.split_count,
+                expected_hash,
+                actual_hash
+            );
+            fs::remove_file(path).await?;
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
     /// Download a model with progress callback.
     ///
     /// The progress callback is called periodically during download with
-    /// the current progress information. For split models, downloads all
-    /// parts sequentially.
+    /// the current progress information. For split models, downloads up to
+    /// `SPLIT_DOWNLOAD_CONCURRENCY` parts at once and reports the combined
+    /// progress of all parts.
     ///
     /// # Arguments
     /// * `model_name` - Name of the model to download
@@ -211,67 +868,66 @@ impl ModelManager {
         fs::create_dir_all(&self.models_dir).await?;
         fs::create_dir_all(&self.download_dir()).await?;
 
-        if entry.split_count > 1 {
-            // Download all split files
-            let urls = split_file_urls(&entry.download_url, entry.split_count);
-            let paths = split_file_paths(&self.models_dir, &entry.download_url, entry.split_count);
-
-            for (i, (url, path)) in urls.iter().zip(paths.iter()).enumerate() {
-                if path.exists() {
-                    info!("Split file {}/{} already exists, skipping", i + 1, entry.split_count);
-                    continue;
-                }
-
-                let temp_path = self.download_dir().join(
-                    format!("{}.part", path.file_name().unwrap_or_default().to_string_lossy())
-                );
+        self.check_disk_space(model_name, &entry).await?;
 
-                info!("Downloading split {}/{} from {}", i + 1, entry.split_count, url);
+        let _slot = match &self.limits.slots {
+            Some(slots) => Some(
+                slots
+                    .acquire()
+                    .await
+                    .expect("download slot semaphore is never closed"),
+            ),
+            None => None,
+        };
 
-                // Retry with exponential backoff
-                let mut last_error = String::new();
-                let mut downloaded = false;
-                for attempt in 1..=3 {
-                    match self
-                        .download_file(url, &temp_path, &progress)
-                        .await
-                    {
-                        Ok(()) => {
-                            fs::rename(&temp_path, path).await?;
-                            info!("Split {}/{} downloaded", i + 1, entry.split_count);
-                            downloaded = true;
-                            break;
-                        }
-                        Err(e) => {
-                            last_error = e.to_string();
-                            warn!(
-                                "Download attempt {} failed for split {}/{}: {}",
-                                attempt, i + 1, entry.split_count, e
-                            );
-                            let _ = fs::remove_file(&temp_path).await;
-                            if attempt < 3 {
-                                let delay = std::time::Duration::from_secs(1 << (attempt - 1));
-                                tokio::time::sleep(delay).await;
-                            }
-                        }
+        if entry.split_count > 1 {
+            // Download split files in parallel, bounded by the semaphore
+            let source_urls = entry
+                .download_urls()
+                .map(|url| split_file_urls(url, entry.split_count))
+                .collect::<Result<Vec<_>, _>>()?;
+            let paths = split_file_paths(&self.models_dir, &entry.download_url, entry.split_count)?;
+            let tracker = SplitProgress::new(paths.len(), entry.total_download_size());
+            let semaphore = Semaphore::new(SPLIT_DOWNLOAD_CONCURRENCY);
+
+            let parts = paths.iter().enumerate().map(|(i, path)| {
+                // This part's URL at each source, primary first
+                let urls: Vec<String> =
+                    source_urls.iter().map(|urls| urls[i].clone()).collect();
+                let tracker = &tracker;
+                let semaphore = &semaphore;
+                let progress = &progress;
+                let entry = &entry;
+                async move {
+                    if path.exists() {
+                        info!("Split file {}/{} already exists, skipping", i + 1, entry.split_count);
+                        let len = fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+                        tracker.update(i, &DownloadProgress::new(len, len, 0.0));
+                        return Ok(());
                     }
-                }
 
-                if !downloaded {
-                    return Err(ModelError::DownloadFailed {
-                        attempts: 3,
-                        last_error,
-                    });
+                    let _permit = semaphore
+                        .acquire()
+                        .await
+                        .expect("split download semaphore is never closed");
+                    let part_progress = |p: DownloadProgress| {
+                        progress(tracker.update(i, &p));
+                    };
+                    self.download_split_part(&urls, path, entry, i, &part_progress)
+                        .await
                 }
-            }
+            });
+            futures_util::future::try_join_all(parts).await?;
 
             Ok(final_path)
         } else {
             // Single file download with checksum verification
             let temp_path = self.temp_path(model_name);
-            let url = &entry.download_url;
+            let urls: Vec<String> = entry.download_urls().map(str::to_string).collect();
+            let attempts = download_attempts(&urls);
+            let mut mirrors = Mirrors::new(urls);
             let expected_sha256 = &entry.sha256;
-            let expected_size = entry.size_bytes;
+            let expected_size = entry.total_download_size();
 
             // Clean up existing invalid file
             if final_path.exists() {
@@ -279,13 +935,15 @@ impl ModelManager {
                 fs::remove_file(&final_path).await?;
             }
 
-            info!("Downloading model {} from {}", model_name, url);
+            info!("Downloading model {} from {}", model_name, entry.download_url);
 
-            let mut last_error = String::new();
-            for attempt in 1..=3 {
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let url = mirrors.next_url();
                 match self
                     .download_with_verification(
-                        url,
+                        &url,
                         &temp_path,
                         expected_sha256,
                         expected_size,
@@ -294,138 +952,295 @@ impl ModelManager {
                     .await
                 {
                     Ok(()) => {
-                        fs::rename(&temp_path, &final_path).await?;
-                        info!("Model {} downloaded and verified", model_name);
+                        durable_rename(&temp_path, &final_path).await?;
+                        info!("Model {} downloaded from {} and verified", model_name, url);
                         return Ok(final_path);
                     }
                     Err(e) => {
-                        last_error = e.to_string();
-                        warn!(
-                            "Download attempt {} failed for {}: {}",
-                            attempt, model_name, e
-                        );
-                        let _ = fs::remove_file(&temp_path).await;
-                        if attempt < 3 {
-                            let delay = std::time::Duration::from_secs(1 << (attempt - 1));
-                            tokio::time::sleep(delay).await;
+                        // A partial file can be resumed, but a complete file with
+                        // the wrong checksum or size is corrupt and must start over.
+                        if e.is_corrupt_download() {
+                            let _ = fs::remove_file(&temp_path).await;
+                        }
+                        if !retry_after_failure(attempt, attempts, &mut mirrors, &url, &e).await {
+                            return Err(ModelError::DownloadFailed {
+                                attempts: attempt,
+                                last_error: e.to_string(),
+                            });
                         }
                     }
                 }
             }
-
-            Err(ModelError::DownloadFailed {
-                attempts: 3,
-                last_error,
-            })
         }
     }
 
-    /// Download a file with streaming SHA256 verification.
-    async fn download_with_verification<F>(
+    /// Fail before downloading if the models directory can't hold the model.
+    ///
+    /// Bytes already on disk (completed split parts and partial downloads
+    /// that will be resumed) are subtracted from the model size.
+    async fn check_disk_space(
         &self,
-        url: &str,
-        temp_path: &Path,
-        expected_sha256: &str,
-        expected_size: u64,
-        progress: &F,
-    ) -> Result<(), ModelError>
-    where
-        F: Fn(DownloadProgress),
-    {
-        let response = self.client.get(url).send().await?.error_for_status()?;
-
-        let total_bytes = response
-            .content_length()
-            .unwrap_or(expected_size);
-
-        let mut file = File::create(temp_path).await?;
-        let mut hasher = Sha256::new();
-        let mut bytes_downloaded: u64 = 0;
-
-        let mut stream = response.bytes_stream();
-
-        while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result?;
-
-            // Write to file
-            file.write_all(&chunk).await?;
-
-            // Update hash
-            hasher.update(&chunk);
-
-            // Update progress
-            bytes_downloaded += chunk.len() as u64;
-            progress(DownloadProgress {
-                bytes_downloaded,
-                total_bytes,
-            });
+        model_name: &str,
+        entry: &ModelEntry,
+    ) -> Result<(), ModelError> {
+        let mut existing_paths = if entry.split_count > 1 {
+            split_file_paths(&self.models_dir, &entry.download_url, entry.split_count)?
+        } else {
+            Vec::new()
+        };
+        existing_paths.extend(self.partial_paths(model_name, entry));
+
+        let mut on_disk = 0;
+        for path in &existing_paths {
+            if let Ok(meta) = fs::metadata(path).await {
+                on_disk += meta.len();
+            }
         }
 
-        file.flush().await?;
-        drop(file);
-
-        // Verify checksum
-        let actual_sha256 = format!("{:x}", hasher.finalize());
-        if actual_sha256 != expected_sha256 {
-            return Err(ModelError::ChecksumMismatch {
-                model: url.to_string(),
-                expected: expected_sha256.to_string(),
-                actual: actual_sha256,
-            });
+        let needed = entry
+            .total_download_size()
+            .saturating_sub(on_disk)
+            .saturating_add(DISK_SPACE_MARGIN);
+        let available = available_disk_space(&self.models_dir)?;
+        if available < needed {
+            return Err(ModelError::InsufficientDiskSpace { needed, available });
         }
-
         Ok(())
     }
 
-    /// Download a file without checksum verification (for split model parts).
-    async fn download_file<F>(
+    /// Download part `index` of a split model, retrying with exponential
+    /// backoff.
+    ///
+    /// `urls` are the part's URL at the primary source and at each mirror,
+    /// tried in turn. The part is written to a `.part` file in the download
+    /// directory and renamed into place only once it has been fully received
+    /// and, when `entry` lists them for the part, its checksum and size match.
+    async fn download_split_part<F>(
         &self,
-        url: &str,
-        temp_path: &Path,
+        urls: &[String],
+        path: &Path,
+        entry: &ModelEntry,
+        index: usize,
         progress: &F,
     ) -> Result<(), ModelError>
     where
         F: Fn(DownloadProgress),
     {
-        let response = self.client.get(url).send().await?.error_for_status()?;
-
-        let total_bytes = response.content_length().unwrap_or(0);
+        let temp_path = self.split_temp_path(path);
+        let split_count = entry.split_count;
+        let expected_sha256 = entry.part_checksum(index);
+        let expected_size = entry.part_size_bytes.get(index).copied().unwrap_or(0);
+
+        info!(
+            "Downloading split {}/{} from {}",
+            index + 1,
+            split_count,
+            urls[0]
+        );
 
-        let mut file = File::create(temp_path).await?;
-        let mut bytes_downloaded: u64 = 0;
+        let attempts = download_attempts(urls);
+        let mut mirrors = Mirrors::new(urls.to_vec());
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let url = mirrors.next_url();
+            let result = match expected_sha256 {
+                Some(expected) => {
+                    self.download_with_verification(
+                        &url,
+                        &temp_path,
+                        expected,
+                        expected_size,
+                        progress,
+                    )
+                    .await
+                }
+                None => {
+                    self.download_file(&url, &temp_path, expected_size, progress)
+                        .await
+                }
+            };
+            match result {
+                Ok(()) => {
+                    durable_rename(&temp_path, path).await?;
+                    info!(
+                        "Split {}/{} downloaded from {}",
+                        index + 1,
+                        split_count,
+                        url
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    // Keep a partial file so the next attempt can resume it,
+                    // but a complete part with the wrong checksum or size is
+                    // corrupt.
+                    if e.is_corrupt_download() {
+                        let _ = fs::remove_file(&temp_path).await;
+                    }
+                    if !retry_after_failure(attempt, attempts, &mut mirrors, &url, &e).await {
+                        return Err(ModelError::DownloadFailed {
+                            attempts: attempt,
+                            last_error: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send a download request, resuming from an existing partial file.
+    ///
+    /// If `temp_path` already holds bytes from an interrupted download, a
+    /// `Range` request is sent for the remainder and the file is opened for
+    /// append. Servers that ignore the range (200 instead of 206) or reject it
+    /// cause the file to be truncated and the download to restart from zero.
+    ///
+    /// Compressed downloads can't be resumed this way, since the bytes on disk
+    /// are decompressed and don't line up with the compressed range, so they
+    /// always restart from zero.
+    ///
+    /// Returns the response, the open file, and the number of bytes already
+    /// on disk that the response continues from.
+    async fn start_download(
+        &self,
+        url: &str,
+        temp_path: &Path,
+    ) -> Result<(reqwest::Response, File, u64), ModelError> {
+        let existing = match fs::metadata(temp_path).await {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        };
+
+        if existing > 0 {
+            let response = self
+                .get(url)
+                .header(RANGE, format!("bytes={}-", existing))
+                .send()
+                .await?;
+
+            match response.status() {
+                StatusCode::PARTIAL_CONTENT if is_zstd(url, &response) => {
+                    info!("Compressed download of {} can't resume, restarting", url);
+                }
+                // Appending any other range would corrupt the file
+                StatusCode::PARTIAL_CONTENT if content_range_start(&response) != Some(existing) => {
+                    info!("Server sent the wrong range for {}, restarting download", url);
+                }
+                StatusCode::PARTIAL_CONTENT => {
+                    info!("Resuming download of {} from byte {}", url, existing);
+                    let file = OpenOptions::new().append(true).open(temp_path).await?;
+                    return Ok((response, file, existing));
+                }
+                StatusCode::RANGE_NOT_SATISFIABLE => {
+                    debug!("Range not satisfiable for {}, restarting download", url);
+                }
+                _ => {
+                    let response = response.error_for_status()?;
+                    info!("Server ignored range request for {}, restarting download", url);
+                    let file = File::create(temp_path).await?;
+                    return Ok((response, file, 0));
+                }
+            }
+        }
+
+        let response = self.get(url).send().await?.error_for_status()?;
+        let file = File::create(temp_path).await?;
+        Ok((response, file, 0))
+    }
+
+    /// Download a file with streaming SHA256 verification.
+    ///
+    /// Resumes from a partial `temp_path` when the server supports range
+    /// requests; the bytes already on disk are fed to the hasher first. The
+    /// file must also come to `expected_size` bytes, unless that is 0. An
+    /// empty `expected_sha256` skips the checksum, leaving only the size.
+    async fn download_with_verification<F>(
+        &self,
+        url: &str,
+        temp_path: &Path,
+        expected_sha256: &str,
+        expected_size: u64,
+        progress: &F,
+    ) -> Result<(), ModelError>
+    where
+        F: Fn(DownloadProgress),
+    {
+        let (response, mut file, resumed_from) = self.start_download(url, temp_path).await?;
+
+        let total_bytes = response
+            .content_length()
+            .map(|len| len + resumed_from)
+            .unwrap_or(expected_size);
+        preallocate(&file, temp_path, resumed_from, total_bytes.saturating_sub(resumed_from))?;
 
-        let mut stream = response.bytes_stream();
+        let mut hasher = Sha256::new();
+        if resumed_from > 0 {
+            hash_file_into(temp_path, &mut hasher).await?;
+        }
+        let compressed = is_zstd(url, &response);
+        let written = stream_to_file(
+            response,
+            compressed,
+            &mut file,
+            Some(&mut hasher),
+            resumed_from,
+            total_bytes,
+            self.limits.bandwidth.as_deref(),
+            progress,
+        )
+        .await?;
+        drop(file);
+        check_size(expected_size, written)?;
 
-        while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result?;
-            file.write_all(&chunk).await?;
-            bytes_downloaded += chunk.len() as u64;
-            progress(DownloadProgress {
-                bytes_downloaded,
-                total_bytes,
+        // Verify checksum
+        let actual_sha256 = format!("{:x}", hasher.finalize());
+        if !expected_sha256.is_empty() && actual_sha256 != expected_sha256 {
+            return Err(ModelError::ChecksumMismatch {
+                model: url.to_string(),
+                expected: expected_sha256.to_string(),
+                actual: actual_sha256,
             });
         }
 
-        file.flush().await?;
         Ok(())
     }
 
-    /// Ensure a model is available, downloading if necessary.
+    /// Download a file without checksum verification (for split model parts).
     ///
-    /// This is the main entry point for getting a model ready for use.
-    pub async fn ensure_model<F>(
+    /// Resumes from a partial `temp_path` when the server supports range requests.
+    /// The file must come to `expected_size` bytes, unless that is 0.
+    async fn download_file<F>(
         &self,
-        spec: &ModelSpec,
-        progress: F,
-    ) -> Result<PathBuf, ModelError>
+        url: &str,
+        temp_path: &Path,
+        expected_size: u64,
+        progress: &F,
+    ) -> Result<(), ModelError>
     where
-        F: Fn(DownloadProgress) + Send,
+        F: Fn(DownloadProgress),
     {
-        if self.is_available(&spec.name).await {
-            return Ok(self.model_path(&spec.name));
-        }
+        let (response, mut file, resumed_from) = self.start_download(url, temp_path).await?;
 
-        self.download(&spec.name, progress).await
+        let total_bytes = response
+            .content_length()
+            .map(|len| len + resumed_from)
+            .unwrap_or(expected_size);
+        preallocate(&file, temp_path, resumed_from, total_bytes.saturating_sub(resumed_from))?;
+
+        let compressed = is_zstd(url, &response);
+        let written = stream_to_file(
+            response,
+            compressed,
+            &mut file,
+            None,
+            resumed_from,
+            total_bytes,
+            self.limits.bandwidth.as_deref(),
+            progress,
+        )
+        .await?;
+        check_size(expected_size, written)
     }
 
     /// Get the model manifest.
@@ -434,38 +1249,357 @@ impl ModelManager {
     }
 }
 
+/// Build a manifest entry describing a local GGUF file.
+///
+/// For split models `path` is the first part; the size and per-part
+/// sizes and checksums cover every part found next to it, and `sha256` is of the
+/// first part, matching the bundled manifest. The download URL is a placeholder to be filled in.
+pub async fn local_manifest_entry(
+    path: &Path,
+    metadata: &GgufMetadata,
+) -> Result<ModelEntry, ModelError> {
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model.gguf".to_string());
+    let download_url = format!("https://example.com/REPLACE-ME/{}", filename);
+    let split_count = metadata.split_count();
+
+    let (size_bytes, part_sha256, part_size_bytes) = if split_count > 1 {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut sizes = Vec::new();
+        let mut checksums = Vec::new();
+        for part in split_file_paths(dir, &download_url, split_count)? {
+            sizes.push(fs::metadata(&part).await?.len());
+            checksums.push(compute_file_sha256(&part).await?);
+        }
+        (sizes.iter().sum(), checksums, sizes)
+    } else {
+        (fs::metadata(path).await?.len(), Vec::new(), Vec::new())
+    };
+
+    Ok(ModelEntry {
+        quantization: metadata.quantization().unwrap_or("unknown").to_string(),
+        size_bytes,
+        sha256: compute_file_sha256(path).await?,
+        download_url,
+        split_count,
+        part_sha256,
+        part_size_bytes,
+        mirror_urls: Vec::new(),
+        supported_backends: vec![Backend::Cuda, Backend::Metal, Backend::Vulkan],
+        chat_template: metadata.chat_template().and_then(ChatTemplate::detect),
+    })
+}
+
 /// Generate URLs for all parts of a split GGUF model.
 ///
 /// Given the URL for part 1 (e.g., `...q4_k_m-00001-of-00003.gguf`),
-/// generates URLs for all parts by replacing the part number.
-fn split_file_urls(first_url: &str, split_count: u32) -> Vec<String> {
-    let total = format!("{:05}", split_count);
-    (1..=split_count)
-        .map(|i| {
-            let from = format!("-00001-of-{}", total);
-            let to = format!("-{:05}-of-{}", i, total);
-            first_url.replace(&from, &to)
-        })
-        .collect()
+/// generates URLs for all parts by replacing the part number. Fails if the
+/// file name has no part number, isn't part 1, or names a part count other
+/// than `split_count`.
+pub(crate) fn split_file_urls(
+    first_url: &str,
+    split_count: u32,
+) -> Result<Vec<String>, ModelError> {
+    let invalid = |reason: String| ModelError::InvalidSplitUrl {
+        url: first_url.to_string(),
+        reason,
+    };
+    let Some((prefix, part, total, suffix)) = parse_split_url(first_url) else {
+        let reason = "expected a -NNNNN-of-NNNNN part number in the file name";
+        return Err(invalid(reason.to_string()));
+    };
+    if part.parse::<u32>() != Ok(1) {
+        return Err(invalid(format!("must be the first part, not part {}", part)));
+    }
+    if total.parse::<u32>() != Ok(split_count) {
+        return Err(invalid(format!("names {} parts but split_count is {}", total, split_count)));
+    }
+
+    // Keep the URL's zero-padding; numbers wider than it just grow
+    let width = part.len();
+    Ok((1..=split_count)
+        .map(|i| format!("{}-{:0width$}-of-{}{}", prefix, i, total, suffix, width = width))
+        .collect())
+}
+
+/// Split a URL around the last `-<part>-of-<total>` in its file name,
+/// returning the text before it, the two numbers as written, and the text
+/// after.
+fn parse_split_url(url: &str) -> Option<(&str, &str, &str, &str)> {
+    let name_start = url.rfind('/').map_or(0, |i| i + 1);
+    let is_digit = |c: char| c.is_ascii_digit();
+    url[name_start..].rmatch_indices("-of-").find_map(|(at, _)| {
+        let at = name_start + at;
+        let before = &url[..at];
+        let part_start = before.trim_end_matches(is_digit).len();
+        let after = &url[at + "-of-".len()..];
+        let total_len = after.len() - after.trim_start_matches(is_digit).len();
+        let (part, total) = (&before[part_start..], &after[..total_len]);
+        let prefix = before[..part_start].strip_suffix('-')?;
+        if part.is_empty() || total.is_empty() {
+            return None;
+        }
+        Some((prefix, part, total, &after[total_len..]))
+    })
 }
 
 /// Generate local file paths for all parts of a split GGUF model.
-fn split_file_paths(models_dir: &Path, first_url: &str, split_count: u32) -> Vec<PathBuf> {
-    split_file_urls(first_url, split_count)
+fn split_file_paths(
+    models_dir: &Path,
+    first_url: &str,
+    split_count: u32,
+) -> Result<Vec<PathBuf>, ModelError> {
+    Ok(split_file_urls(first_url, split_count)?
         .into_iter()
-        .map(|url| {
-            let filename = url.rsplit('/').next().unwrap_or("unknown.gguf");
-            models_dir.join(filename)
-        })
-        .collect()
+        .map(|url| models_dir.join(local_file_name(&url)))
+        .collect())
+}
+
+/// File name a download is stored under: the last URL segment, without the
+/// `.zst` suffix of compressed downloads since they are stored decompressed.
+fn local_file_name(url: &str) -> &str {
+    let filename = url.rsplit('/').next().unwrap_or("unknown.gguf");
+    filename.strip_suffix(".zst").unwrap_or(filename)
+}
+
+/// Whether a download is zstd-compressed, either by the `.zst` URL it was
+/// requested from or a `Content-Encoding: zstd` header.
+///
+/// The requested URL is checked rather than `response.url()`, since CDNs
+/// redirect to blob URLs that drop the suffix.
+fn is_zstd(url: &str, response: &reqwest::Response) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.ends_with(".zst")
+        || response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"zstd"))
+}
+
+/// The offset of the first byte in a partial response, from its
+/// `Content-Range: bytes START-END/TOTAL` header.
+fn content_range_start(response: &reqwest::Response) -> Option<u64> {
+    let range = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let (start, _) = range.strip_prefix("bytes ")?.split_once('-')?;
+    start.parse().ok()
+}
+
+/// Free bytes available to unprivileged users on the filesystem holding `path`.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // statvfs field widths differ across platforms
+fn available_disk_space(path: &Path) -> Result<u64, std::io::Error> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Free-space probing isn't implemented here; don't block the download.
+#[cfg(not(unix))]
+fn available_disk_space(_path: &Path) -> Result<u64, std::io::Error> {
+    Ok(u64::MAX)
+}
+
+/// Stream a response body into `file`, optionally hashing it as it goes.
+///
+/// Chunks are coalesced into `WRITE_BUFFER_SIZE` writes and progress is
+/// reported at most every `PROGRESS_INTERVAL`, plus once at the end. If the
+/// stream fails, whatever was received is still written so the next attempt
+/// can resume from it.
+///
+/// `compressed` (zstd) responses are decompressed before writing and hashing,
+/// so the file and checksum are those of the plain GGUF. Progress counts
+/// bytes received over the network, which is also what `bandwidth` paces.
+///
+/// Returns the size of the file: `resumed_from` plus the bytes written.
+#[allow(clippy::too_many_arguments)]
+async fn stream_to_file<F>(
+    response: reqwest::Response,
+    compressed: bool,
+    file: &mut File,
+    mut hasher: Option<&mut Sha256>,
+    resumed_from: u64,
+    total_bytes: u64,
+    bandwidth: Option<&Bandwidth>,
+    progress: &F,
+) -> Result<u64, ModelError>
+where
+    F: Fn(DownloadProgress),
+{
+    use std::io::Write;
+
+    let mut bytes_downloaded = resumed_from;
+    let mut file_size = resumed_from;
+    let mut meter = ThroughputMeter::new(resumed_from);
+    let mut buffer = Vec::with_capacity(WRITE_BUFFER_SIZE);
+    let mut last_report: Option<Instant> = None;
+    let mut decoder = if compressed {
+        Some(zstd::stream::write::Decoder::new(Vec::new())?)
+    } else {
+        None
+    };
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk_result) = stream.next().await {
+        let chunk = match chunk_result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                write_buffer(file, &mut hasher, &mut buffer).await?;
+                file.flush().await?;
+                return Err(e.into());
+            }
+        };
+
+        match decoder.as_mut() {
+            Some(decoder) => {
+                decoder.write_all(&chunk)?;
+                buffer.append(decoder.get_mut());
+            }
+            None => buffer.extend_from_slice(&chunk),
+        }
+        if buffer.len() >= WRITE_BUFFER_SIZE {
+            file_size += buffer.len() as u64;
+            write_buffer(file, &mut hasher, &mut buffer).await?;
+        }
+
+        bytes_downloaded += chunk.len() as u64;
+        if let Some(bandwidth) = bandwidth {
+            bandwidth.pace(chunk.len()).await;
+        }
+        if last_report.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL) {
+            progress(meter.progress(bytes_downloaded, total_bytes));
+            last_report = Some(Instant::now());
+        }
+    }
+
+    if let Some(mut decoder) = decoder {
+        decoder.flush()?;
+        buffer.append(decoder.get_mut());
+    }
+    file_size += buffer.len() as u64;
+    write_buffer(file, &mut hasher, &mut buffer).await?;
+    file.flush().await?;
+    progress(meter.progress(bytes_downloaded, total_bytes));
+    Ok(file_size)
+}
+
+/// Fail if a download came to `actual` bytes instead of `expected` (0 =
+/// unknown).
+fn check_size(expected: u64, actual: u64) -> Result<(), ModelError> {
+    if expected != 0 && actual != expected {
+        return Err(ModelError::SizeMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Write out and hash the buffered bytes, leaving the buffer empty.
+async fn write_buffer(
+    file: &mut File,
+    hasher: &mut Option<&mut Sha256>,
+    buffer: &mut Vec<u8>,
+) -> Result<(), std::io::Error> {
+    file.write_all(buffer).await?;
+    if let Some(hasher) = hasher {
+        hasher.update(&buffer[..]);
+    }
+    buffer.clear();
+    Ok(())
+}
+
+/// Reserve disk space for `len` bytes of `file` starting at `offset`.
+///
+/// Lets the filesystem allocate a multi-GB download contiguously and fails
+/// with `InsufficientDiskSpace` up front instead of partway through. The
+/// file's size is left unchanged (`FALLOC_FL_KEEP_SIZE`), since resuming
+/// relies on the size matching the bytes actually written. Filesystems
+/// without fallocate support are skipped.
+#[cfg(target_os = "linux")]
+fn preallocate(file: &File, path: &Path, offset: u64, len: u64) -> Result<(), ModelError> {
+    use std::os::unix::io::AsRawFd;
+
+    if len == 0 {
+        return Ok(());
+    }
+    let result = unsafe {
+        libc::fallocate(
+            file.as_raw_fd(),
+            libc::FALLOC_FL_KEEP_SIZE,
+            offset as libc::off_t,
+            len as libc::off_t,
+        )
+    };
+    if result == 0 {
+        return Ok(());
+    }
+
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ENOSPC) | Some(libc::EDQUOT) => Err(ModelError::InsufficientDiskSpace {
+            needed: len,
+            available: available_disk_space(path.parent().unwrap_or(path)).unwrap_or(0),
+        }),
+        _ => {
+            debug!("Not preallocating {}: {}", path.display(), err);
+            Ok(())
+        }
+    }
+}
+
+/// Preallocation without changing the file size needs Linux's fallocate.
+#[cfg(not(target_os = "linux"))]
+fn preallocate(_file: &File, _path: &Path, _offset: u64, _len: u64) -> Result<(), ModelError> {
+    Ok(())
+}
+
+/// Rename `from` to `to` so that the result survives a crash.
+///
+/// The file is fsynced before the rename so its contents are on disk, and
+/// the parent directory is fsynced afterwards so the new name is too.
+/// Without this a crash shortly after a download can leave a truncated file
+/// under the final name, which `is_available` can't tell apart from a
+/// complete one when the manifest has no checksum.
+async fn durable_rename(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    File::open(from).await?.sync_all().await?;
+    fs::rename(from, to).await?;
+
+    // Directories can only be opened (and synced) this way on unix
+    #[cfg(unix)]
+    if let Some(parent) = to.parent() {
+        File::open(parent).await?.sync_all().await?;
+    }
+    Ok(())
 }
 
 /// Compute SHA256 hash of a file.
+///
+/// Hashing is CPU-bound, so it runs on the blocking pool, where concurrent
+/// calls (e.g. one per split part) hash in parallel.
 async fn compute_file_sha256(path: &Path) -> Result<String, std::io::Error> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await
+    .map_err(std::io::Error::other)?
+}
+
+/// Feed the contents of a file into a SHA256 hasher.
+async fn hash_file_into(path: &Path, hasher: &mut Sha256) -> Result<(), std::io::Error> {
     use tokio::io::AsyncReadExt;
 
     let mut file = File::open(path).await?;
-    let mut hasher = Sha256::new();
     let mut buffer = vec![0u8; 64 * 1024]; // 64KB buffer
 
     loop {
@@ -476,7 +1610,7 @@ async fn compute_file_sha256(path: &Path) -> Result<String, std::io::Error> {
         hasher.update(&buffer[..n]);
     }
 
-    Ok(format!("{:x}", hasher.finalize()))
+    Ok(())
 }
 
 #[cfg(test)]
@@ -484,6 +1618,195 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
     use crate::model::{Backend, ModelEntry};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// A request received by `MockServer`.
+    #[derive(Debug, Clone)]
+    struct MockRequest {
+        path: String,
+        /// Header names are lowercased.
+        headers: HashMap<String, String>,
+    }
+
+    /// A response returned by a `MockServer` handler.
+    struct MockResponse {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+        /// Send the body in chunks of this size with a pause after each
+        throttle: Option<(usize, Duration)>,
+    }
+
+    impl MockResponse {
+        fn ok(body: &[u8]) -> Self {
+            Self {
+                status: 200,
+                headers: Vec::new(),
+                body: body.to_vec(),
+                throttle: None,
+            }
+        }
+
+        /// Serve `body` in `chunk_size` pieces, pausing `delay` after each.
+        fn throttled(body: &[u8], chunk_size: usize, delay: Duration) -> Self {
+            Self {
+                throttle: Some((chunk_size, delay)),
+                ..Self::ok(body)
+            }
+        }
+
+        /// Serve `body`, honoring a `Range: bytes=N-` request header.
+        fn ranged(req: &MockRequest, body: &[u8]) -> Self {
+            let start = req
+                .headers
+                .get("range")
+                .and_then(|r| r.strip_prefix("bytes="))
+                .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+            match start {
+                Some(start) if start < body.len() => Self {
+                    status: 206,
+                    headers: vec![(
+                        "Content-Range".to_string(),
+                        format!("bytes {}-{}/{}", start, body.len() - 1, body.len()),
+                    )],
+                    body: body[start..].to_vec(),
+                    throttle: None,
+                },
+                Some(_) => Self {
+                    status: 416,
+                    headers: Vec::new(),
+                    body: Vec::new(),
+                    throttle: None,
+                },
+                None => Self::ok(body),
+            }
+        }
+    }
+
+    type MockHandler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+    /// Minimal HTTP/1.1 server for exercising downloads without network access.
+    struct MockServer {
+        base_url: String,
+        requests: Arc<Mutex<Vec<MockRequest>>>,
+    }
+
+    impl MockServer {
+        async fn start<H>(handler: H) -> Self
+        where
+            H: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+        {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let handler: Arc<MockHandler> = Arc::new(handler);
+
+            let recorded = Arc::clone(&requests);
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(Self::serve_connection(
+                        stream,
+                        Arc::clone(&handler),
+                        Arc::clone(&recorded),
+                    ));
+                }
+            });
+
+            Self { base_url, requests }
+        }
+
+        async fn serve_connection(
+            stream: tokio::net::TcpStream,
+            handler: Arc<MockHandler>,
+            recorded: Arc<Mutex<Vec<MockRequest>>>,
+        ) {
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            if reader.read_line(&mut line).await.is_err() {
+                return;
+            }
+            let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+
+            let mut headers = HashMap::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) if line == "\r\n" => break,
+                    Ok(_) => {
+                        if let Some((k, v)) = line.split_once(':') {
+                            headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+                        }
+                    }
+                }
+            }
+
+            let req = MockRequest { path, headers };
+            recorded.lock().unwrap().push(req.clone());
+            let resp = handler(&req);
+
+            let mut head = format!("HTTP/1.1 {} Mock\r\n", resp.status);
+            head.push_str("Connection: close\r\n");
+            let has_length = resp
+                .headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case("content-length"));
+            if !has_length {
+                head.push_str(&format!("Content-Length: {}\r\n", resp.body.len()));
+            }
+            for (k, v) in &resp.headers {
+                head.push_str(&format!("{}: {}\r\n", k, v));
+            }
+            head.push_str("\r\n");
+
+            let mut stream = reader.into_inner();
+            let _ = stream.write_all(head.as_bytes()).await;
+            match resp.throttle {
+                Some((chunk_size, delay)) => {
+                    for chunk in resp.body.chunks(chunk_size) {
+                        let _ = stream.write_all(chunk).await;
+                        let _ = stream.flush().await;
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                None => {
+                    let _ = stream.write_all(&resp.body).await;
+                }
+            }
+            let _ = stream.shutdown().await;
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("{}{}", self.base_url, path)
+        }
+
+        fn requests(&self) -> Vec<MockRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// Manifest with a single-file entry for "hello world" served from `url`.
+    fn hello_manifest(name: &str, url: String) -> ModelManifest {
+        let mut models = HashMap::new();
+        models.insert(
+            name.to_string(),
+            ModelEntry {
+                quantization: "test".to_string(),
+                size_bytes: 11,
+                sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+                download_url: url,
+                split_count: 1,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda],
+                chat_template: None,
+            },
+        );
+        ModelManifest { models }
+    }
 
     fn test_manifest() -> ModelManifest {
         let mut models = HashMap::new();
@@ -495,7 +1818,11 @@ mod tests {
                 sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(), // SHA256 of empty file
                 download_url: "https://example.com/test-model.gguf".to_string(),
                 split_count: 1,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
                 supported_backends: vec![Backend::Cuda],
+                chat_template: None,
             },
         );
         ModelManifest { models }
@@ -508,6 +1835,13 @@ mod tests {
             manager.model_path("qwen2.5-7b-instruct-q4"),
             PathBuf::from("/tmp/models/qwen2.5-7b-instruct-q4_k_m-00001-of-00002.gguf")
         );
+        assert_eq!(
+            manager.all_model_paths("qwen2.5-7b-instruct-q4"),
+            vec![
+                PathBuf::from("/tmp/models/qwen2.5-7b-instruct-q4_k_m-00001-of-00002.gguf"),
+                PathBuf::from("/tmp/models/qwen2.5-7b-instruct-q4_k_m-00002-of-00002.gguf"),
+            ]
+        );
     }
 
     #[tokio::test]
@@ -519,6 +1853,63 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_remove_partial_downloads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = checked_split_manifest(
+            "https://example.com/checked-00001-of-00002.gguf".to_string(),
+        );
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+        let download_dir = temp_dir.path().join(".download");
+        fs::create_dir_all(&download_dir).await.unwrap();
+        let partial = download_dir.join("checked-00002-of-00002.gguf.part");
+        fs::write(&partial, b"part").await.unwrap();
+        let complete = temp_dir.path().join("checked-00001-of-00002.gguf");
+        fs::write(&complete, b"part one").await.unwrap();
+
+        manager.remove_partial_downloads("checked").await;
+        assert!(!partial.exists());
+        assert!(complete.exists());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_listed_models() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manifest = checked_split_manifest(
+            "https://example.com/checked-00001-of-00002.gguf".to_string(),
+        );
+        manifest.models.extend(
+            hello_manifest("old", "https://example.com/old.gguf".to_string()).models,
+        );
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let download_dir = temp_dir.path().join(".download");
+        fs::create_dir_all(&download_dir).await.unwrap();
+        let kept = [
+            temp_dir.path().join("checked-00001-of-00002.gguf"),
+            temp_dir.path().join("checked-00002-of-00002.gguf"),
+            download_dir.join("checked-00002-of-00002.gguf.part"),
+            temp_dir.path().join("notes.txt"),
+        ];
+        let stale = [
+            download_dir.join("old.gguf.part"),
+            temp_dir.path().join("old.gguf"),
+            temp_dir.path().join("unknown.gguf"),
+        ];
+        for path in kept.iter().chain(&stale) {
+            fs::write(path, b"x").await.unwrap();
+        }
+
+        let listed = manager.prunable(&["checked"]).await.unwrap();
+        assert_eq!(listed, stale);
+        assert!(stale.iter().all(|p| p.exists()), "listing removes nothing");
+
+        let removed = manager.prune(&["checked"]).await.unwrap();
+        assert_eq!(removed, stale);
+        assert!(stale.iter().all(|p| !p.exists()));
+        assert!(kept.iter().all(|p| p.exists()));
+    }
+
     #[tokio::test]
     async fn test_not_available_when_missing() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -566,6 +1957,72 @@ mod tests {
         assert!(matches!(result, Err(ModelError::NotInManifest(_))));
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_preallocate_reserves_space() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("model.gguf.part");
+        let file = File::create(&path).await.unwrap();
+
+        preallocate(&file, &path, 0, 4 * 1024 * 1024).unwrap();
+
+        let meta = fs::metadata(&path).await.unwrap();
+        assert_eq!(meta.len(), 0, "size is kept so resume still works");
+        // st_blocks is in 512-byte units; filesystems without fallocate skip it
+        if meta.blocks() > 0 {
+            assert!(meta.blocks() * 512 >= 4 * 1024 * 1024);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_preallocate_reports_insufficient_space() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("model.gguf.part");
+        let file = File::create(&path).await.unwrap();
+
+        // Only meaningful where the filesystem supports fallocate
+        use std::os::unix::fs::MetadataExt;
+        preallocate(&file, &path, 0, 4096).unwrap();
+        if fs::metadata(&path).await.unwrap().blocks() == 0 {
+            return;
+        }
+
+        let available = available_disk_space(temp_dir.path()).unwrap();
+        let err = preallocate(&file, &path, 0, available + 1024 * 1024 * 1024).unwrap_err();
+        assert!(
+            matches!(err, ModelError::InsufficientDiskSpace { .. }),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_durable_rename() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("model.gguf.part");
+        let to = temp_dir.path().join("model.gguf");
+        fs::write(&from, b"model bytes").await.unwrap();
+        fs::write(&to, b"stale").await.unwrap();
+
+        durable_rename(&from, &to).await.unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read(&to).await.unwrap(), b"model bytes");
+    }
+
+    #[tokio::test]
+    async fn test_durable_rename_missing_source() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let to = temp_dir.path().join("model.gguf");
+
+        let result = durable_rename(&temp_dir.path().join("missing.part"), &to).await;
+        assert!(result.is_err());
+        assert!(!to.exists());
+    }
+
     #[tokio::test]
     async fn test_compute_file_sha256() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -637,7 +2094,11 @@ mod tests {
                 sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
                 download_url: "https://httpbin.org/base64/aGVsbG8gd29ybGQ=".to_string(),
                 split_count: 1,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
                 supported_backends: vec![Backend::Cuda],
+                chat_template: None,
             },
         );
         let manifest = ModelManifest { models };
@@ -689,7 +2150,11 @@ mod tests {
                 sha256: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
                 download_url: "https://httpbin.org/base64/aGVsbG8gd29ybGQ=".to_string(),
                 split_count: 1,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
                 supported_backends: vec![Backend::Cuda],
+                chat_template: None,
             },
         );
         let manifest = ModelManifest { models };
@@ -702,20 +2167,868 @@ mod tests {
         assert!(matches!(result, Err(ModelError::DownloadFailed { .. })));
     }
 
+    #[tokio::test]
+    async fn test_download_resumes_partial_file_with_range() {
+        let server = MockServer::start(|req| MockResponse::ranged(req, b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = hello_manifest("resume", server.url("/resume.gguf"));
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        // Simulate an interrupted download
+        fs::create_dir_all(manager.download_dir()).await.unwrap();
+        fs::write(manager.temp_path("resume"), b"hello ").await.unwrap();
+
+        let path = manager.download("resume", |_| {}).await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/resume.gguf");
+        assert_eq!(requests[0].headers.get("range").map(String::as_str), Some("bytes=6-"));
+        assert!(manager.verify("resume").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_uses_injected_client() {
+        let server = MockServer::start(|_| MockResponse::ok(b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = hello_manifest("injected", server.url("/injected.gguf"));
+        let client = reqwest::Client::builder()
+            .user_agent("tsuku-llm-test/1.0")
+            .build()
+            .unwrap();
+        let manager = ModelManager::with_client(temp_dir.path().to_path_buf(), manifest, client);
+
+        manager.download("injected", |_| {}).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].headers.get("user-agent").map(String::as_str),
+            Some("tsuku-llm-test/1.0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_falls_back_to_mirror() {
+        tokio::time::pause();
+        let primary =
+            MockServer::start(|_| MockResponse { status: 503, ..MockResponse::ok(b"") }).await;
+        let mirror = MockServer::start(|_| MockResponse::ok(b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manifest = hello_manifest("mirrored", primary.url("/mirrored.gguf"));
+        let entry = manifest.models.get_mut("mirrored").unwrap();
+        entry.mirror_urls = vec![mirror.url("/mirrored.gguf")];
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let path = manager.download("mirrored", |_| {}).await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(mirror.requests().len(), 1);
+        assert!(manager.verify("mirrored").await.unwrap());
+    }
+
+    #[test]
+    fn test_mirrors_keep_their_turn_after_removal() {
+        let mut mirrors = Mirrors::new(vec!["a".into(), "b".into(), "c".into()]);
+        assert_eq!(mirrors.next_url(), "a");
+        mirrors.remove("a");
+        assert_eq!(mirrors.next_url(), "b");
+        assert_eq!(mirrors.next_url(), "c");
+        assert_eq!(mirrors.next_url(), "b");
+
+        mirrors.remove("c");
+        assert_eq!(mirrors.next_url(), "b");
+        mirrors.remove("b");
+        assert!(mirrors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_tries_each_mirror_after_dropping_primary() {
+        tokio::time::pause();
+        let primary =
+            MockServer::start(|_| MockResponse { status: 404, ..MockResponse::ok(b"") }).await;
+        let flaky =
+            MockServer::start(|_| MockResponse { status: 503, ..MockResponse::ok(b"") }).await;
+        let good = MockServer::start(|_| MockResponse::ok(b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manifest = hello_manifest("mirrored", primary.url("/mirrored.gguf"));
+        let entry = manifest.models.get_mut("mirrored").unwrap();
+        entry.mirror_urls = vec![flaky.url("/mirrored.gguf"), good.url("/mirrored.gguf")];
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        manager.download("mirrored", |_| {}).await.unwrap();
+
+        // Dropping the primary doesn't skip the mirror that followed it
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(flaky.requests().len(), 1);
+        assert_eq!(good.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_fails_fast_on_client_error() {
+        let server =
+            MockServer::start(|_| MockResponse { status: 404, ..MockResponse::ok(b"") }).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(
+            temp_dir.path().to_path_buf(),
+            hello_manifest("missing", server.url("/missing.gguf")),
+        );
+
+        let result = manager.download("missing", |_| {}).await;
+
+        assert!(matches!(result, Err(ModelError::DownloadFailed { attempts: 1, .. })));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_server_error() {
+        tokio::time::pause();
+        let server =
+            MockServer::start(|_| MockResponse { status: 503, ..MockResponse::ok(b"") }).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(
+            temp_dir.path().to_path_buf(),
+            hello_manifest("flaky", server.url("/flaky.gguf")),
+        );
+
+        let result = manager.download("flaky", |_| {}).await;
+
+        assert!(matches!(
+            result,
+            Err(ModelError::DownloadFailed { attempts: DOWNLOAD_ATTEMPTS, .. })
+        ));
+        assert_eq!(server.requests().len(), DOWNLOAD_ATTEMPTS as usize);
+    }
+
+    #[tokio::test]
+    async fn test_download_sends_hf_token() {
+        let server = MockServer::start(|req| {
+            match req.headers.get("authorization").map(String::as_str) {
+                Some("Bearer hf_test") => MockResponse::ok(b"hello world"),
+                _ => MockResponse { status: 401, ..MockResponse::ok(b"") },
+            }
+        })
+        .await;
+        let url = server.url("/gated.gguf");
+        let host = reqwest::Url::parse(&url).unwrap().host_str().unwrap().to_string();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(
+            temp_dir.path().to_path_buf(),
+            hello_manifest("gated", url.clone()),
+        )
+        .with_hf_auth(Some(HfAuth::new("hf_test".to_string()).with_host(&host)));
+        manager.download("gated", |_| {}).await.unwrap();
+        assert!(manager.verify("gated").await.unwrap());
+
+        // Without the token, or for a host it isn't meant for, it's not sent
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager =
+            ModelManager::with_manifest(temp_dir.path().to_path_buf(), hello_manifest("gated", url))
+                .with_hf_auth(Some(HfAuth::new("hf_test".to_string())));
+        let result = manager.download("gated", |_| {}).await;
+        assert!(matches!(result, Err(ModelError::DownloadFailed { .. })));
+        assert!(server.requests().iter().skip(1).all(|r| !r.headers.contains_key("authorization")));
+    }
+
+    #[test]
+    fn test_hf_auth_applies_to_huggingface_only() {
+        let auth = HfAuth::new("hf_test".to_string());
+        assert!(auth.applies_to("https://huggingface.co/Qwen/repo/resolve/main/model.gguf"));
+        assert!(auth.applies_to("https://hf.co/Qwen/repo/resolve/main/model.gguf"));
+        assert!(!auth.applies_to("https://mirror.example.com/model.gguf"));
+        assert!(!auth.applies_to("https://hf.co.evil.example/model.gguf"));
+        assert!(!auth.applies_to("https://huggingface.co.evil.example/model.gguf"));
+        assert!(!format!("{:?}", auth).contains("hf_test"));
+
+        let auth = auth.with_host("Mirror.Example.com");
+        assert!(auth.applies_to("https://mirror.example.com/model.gguf"));
+        assert!(auth.applies_to("https://huggingface.co/Qwen/repo/resolve/main/model.gguf"));
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_server_ignores_range() {
+        let server = MockServer::start(|_| MockResponse::ok(b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = hello_manifest("no-range", server.url("/no-range.gguf"));
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        fs::create_dir_all(manager.download_dir()).await.unwrap();
+        fs::write(manager.temp_path("no-range"), b"hello ").await.unwrap();
+
+        let path = manager.download("no-range", |_| {}).await.unwrap();
+
+        // The partial bytes must not be duplicated in front of the full body
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+        assert!(manager.verify("no-range").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_range_starts_elsewhere() {
+        // A 206 for the whole file rather than the requested offset
+        let server = MockServer::start(|req| match req.headers.get("range") {
+            Some(_) => MockResponse {
+                status: 206,
+                headers: vec![("Content-Range".to_string(), "bytes 0-10/11".to_string())],
+                ..MockResponse::ok(b"hello world")
+            },
+            None => MockResponse::ok(b"hello world"),
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = hello_manifest("bad-range", server.url("/bad-range.gguf"));
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        fs::create_dir_all(manager.download_dir()).await.unwrap();
+        fs::write(manager.temp_path("bad-range"), b"hello ").await.unwrap();
+
+        let path = manager.download("bad-range", |_| {}).await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[1].headers.contains_key("range"));
+        assert!(manager.verify("bad-range").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_split_parts_in_parallel() {
+        let server = MockServer::start(|req| {
+            let part = req.path.trim_start_matches("/split-q4-").trim_end_matches("-of-00003.gguf");
+            MockResponse::ok(format!("part {}", part).as_bytes())
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut models = HashMap::new();
+        models.insert(
+            "split".to_string(),
+            ModelEntry {
+                quantization: "q4".to_string(),
+                size_bytes: 30,
+                sha256: "".to_string(),
+                download_url: server.url("/split-q4-00001-of-00003.gguf"),
+                split_count: 3,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda],
+                chat_template: None,
+            },
+        );
+        let manifest = ModelManifest { models };
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&updates);
+        let path = manager
+            .download("split", move |p| recorded.lock().unwrap().push(p))
+            .await
+            .unwrap();
+
+        assert_eq!(path, temp_dir.path().join("split-q4-00001-of-00003.gguf"));
+        for i in 1..=3 {
+            let part = temp_dir.path().join(format!("split-q4-0000{}-of-00003.gguf", i));
+            assert_eq!(fs::read(&part).await.unwrap(), format!("part 0000{}", i).as_bytes());
+        }
+        assert_eq!(server.requests().len(), 3);
+
+        // Progress covers all parts, not just the one that finished last
+        let updates = updates.lock().unwrap().clone();
+        assert_eq!(updates.last().map(|p| p.bytes_downloaded), Some(30));
+        assert!(updates.windows(2).all(|w| w[0].bytes_downloaded <= w[1].bytes_downloaded));
+
+        // ...and says which part each update is about
+        let mut indices: Vec<usize> = updates.iter().map(|p| p.part.unwrap().index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices, vec![1, 2, 3]);
+        for p in &updates {
+            let part = p.part.unwrap();
+            assert_eq!(part.count, 3);
+            assert!(part.bytes_downloaded <= part.total_bytes);
+            assert!(part.total_bytes <= p.total_bytes);
+        }
+        let part = updates.iter().rev().find_map(|p| p.part.filter(|part| part.index == 2));
+        assert_eq!(part.map(|part| part.bytes_downloaded), Some(10));
+
+        // Temp files were renamed into place
+        let mut leftovers = fs::read_dir(manager.download_dir()).await.unwrap();
+        assert!(leftovers.next_entry().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_local_manifest_entry_from_fixture() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("custom-q4_k_m.gguf");
+        let bytes = crate::gguf::testing::build_gguf(
+            &[("general.architecture", "qwen2")],
+            &[("general.file_type", 15)],
+        );
+        fs::write(&path, &bytes).await.unwrap();
+
+        let metadata = crate::gguf::read_metadata(&path).unwrap();
+        let entry = local_manifest_entry(&path, &metadata).await.unwrap();
+
+        assert_eq!(entry.size_bytes, bytes.len() as u64);
+        assert_eq!(entry.sha256, format!("{:x}", Sha256::digest(&bytes)));
+        assert_eq!(entry.quantization, "q4_k_m");
+        assert_eq!(entry.split_count, 1);
+        assert!(entry.download_url.ends_with("/custom-q4_k_m.gguf"));
+
+        let json = entry.to_json();
+        assert_eq!(json["size_bytes"], bytes.len() as u64);
+        assert_eq!(json["supported_backends"][0], "cuda");
+    }
+
+    /// Two-part split manifest with per-part checksums of "part one" / "part two".
+    fn checked_split_manifest(first_url: String) -> ModelManifest {
+        let mut models = HashMap::new();
+        models.insert(
+            "checked".to_string(),
+            ModelEntry {
+                quantization: "q4".to_string(),
+                size_bytes: 16,
+                sha256: "".to_string(),
+                download_url: first_url,
+                split_count: 2,
+                part_sha256: vec![
+                    format!("{:x}", Sha256::digest(b"part one")),
+                    format!("{:x}", Sha256::digest(b"part two")),
+                ],
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda],
+                chat_template: None,
+            },
+        );
+        ModelManifest { models }
+    }
+
+    #[tokio::test]
+    async fn test_verify_split_removes_corrupted_part() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = checked_split_manifest(
+            "https://example.com/checked-00001-of-00002.gguf".to_string(),
+        );
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let good = temp_dir.path().join("checked-00001-of-00002.gguf");
+        let bad = temp_dir.path().join("checked-00002-of-00002.gguf");
+        fs::write(&good, b"part one").await.unwrap();
+        fs::write(&bad, b"part 2!!").await.unwrap();
+
+        assert!(!manager.verify("checked").await.unwrap());
+        assert!(good.exists(), "valid part should be kept");
+        assert!(!bad.exists(), "corrupted part should be deleted");
+    }
+
+    #[tokio::test]
+    async fn test_verify_split_parts_concurrently() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let contents: [&[u8]; 3] = [b"part one", b"part two", b"part three"];
+        let mut models = HashMap::new();
+        models.insert(
+            "three".to_string(),
+            ModelEntry {
+                quantization: "q4".to_string(),
+                size_bytes: 26,
+                sha256: "".to_string(),
+                download_url: "https://example.com/three-00001-of-00003.gguf".to_string(),
+                split_count: 3,
+                part_sha256: contents
+                    .iter()
+                    .map(|c| format!("{:x}", Sha256::digest(c)))
+                    .collect(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda],
+                chat_template: None,
+            },
+        );
+        let manager =
+            ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest { models });
+
+        let paths: Vec<PathBuf> = (1..=3)
+            .map(|i| temp_dir.path().join(format!("three-0000{}-of-00003.gguf", i)))
+            .collect();
+        for (path, content) in paths.iter().zip(contents) {
+            fs::write(path, content).await.unwrap();
+        }
+        assert!(manager.verify("three").await.unwrap());
+
+        // One bad part fails the whole model
+        fs::write(&paths[1], b"tampered").await.unwrap();
+        assert!(!manager.verify("three").await.unwrap());
+        assert!(paths[0].exists());
+        assert!(!paths[1].exists());
+        assert!(paths[2].exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_split_refetches_corrupted_part() {
+        let server = MockServer::start(|req| {
+            if req.path.contains("00001-of") {
+                MockResponse::ok(b"part one")
+            } else {
+                MockResponse::ok(b"part two")
+            }
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = checked_split_manifest(server.url("/checked-00001-of-00002.gguf"));
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let bad = temp_dir.path().join("checked-00002-of-00002.gguf");
+        fs::write(temp_dir.path().join("checked-00001-of-00002.gguf"), b"part one")
+            .await
+            .unwrap();
+        fs::write(&bad, b"part 2!!").await.unwrap();
+
+        manager.download("checked", |_| {}).await.unwrap();
+
+        assert_eq!(fs::read(&bad).await.unwrap(), b"part two");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1, "only the corrupted part is re-downloaded");
+        assert_eq!(requests[0].path, "/checked-00002-of-00002.gguf");
+        assert!(manager.verify("checked").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_decompresses_zst_url() {
+        let plain = b"GGUF plain model bytes".repeat(100);
+        let compressed = zstd::encode_all(&plain[..], 3).unwrap();
+        let server = MockServer::start(move |_| MockResponse::ok(&compressed)).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let mut manifest = hello_manifest("tiny", server.url("/tiny.gguf.zst"));
+        let entry = manifest.models.get_mut("tiny").unwrap();
+        entry.sha256 = format!("{:x}", Sha256::digest(&plain));
+        entry.size_bytes = plain.len() as u64;
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let path = manager.download("tiny", |_| {}).await.unwrap();
+        assert_eq!(path, temp_dir.path().join("tiny.gguf"));
+        assert_eq!(fs::read(&path).await.unwrap(), plain);
+        assert!(manager.verify("tiny").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_download_decompresses_zst_url_after_redirect() {
+        let plain = b"GGUF plain model bytes".repeat(100);
+        let compressed = zstd::encode_all(&plain[..], 3).unwrap();
+        // Like a CDN, the `.zst` URL redirects to a blob URL without the suffix
+        let server = MockServer::start(move |req| match req.path.as_str() {
+            "/tiny.gguf.zst" => MockResponse {
+                status: 302,
+                headers: vec![("Location".to_string(), "/blobs/3f2a9c".to_string())],
+                ..MockResponse::ok(b"")
+            },
+            _ => MockResponse::ok(&compressed),
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let mut manifest = hello_manifest("tiny", server.url("/tiny.gguf.zst"));
+        let entry = manifest.models.get_mut("tiny").unwrap();
+        entry.sha256 = format!("{:x}", Sha256::digest(&plain));
+        entry.size_bytes = plain.len() as u64;
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let path = manager.download("tiny", |_| {}).await.unwrap();
+        assert_eq!(fs::read(&path).await.unwrap(), plain);
+        let requests = server.requests();
+        assert_eq!(requests.last().unwrap().path, "/blobs/3f2a9c");
+    }
+
+    #[tokio::test]
+    async fn test_download_rejects_short_file() {
+        let server = MockServer::start(|req| match req.path.as_str() {
+            // Promises more than it sends, so the stream ends early
+            "/cut-off.gguf" => MockResponse {
+                headers: vec![("Content-Length".to_string(), "20".to_string())],
+                ..MockResponse::ok(b"hello world")
+            },
+            _ => MockResponse::ok(b"hello world"),
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest::new());
+        let temp_path = temp_dir.path().join("short.part");
+
+        // No checksum to catch it: the manifest size is the only check
+        let err = manager
+            .download_with_verification(&server.url("/short.gguf"), &temp_path, "", 20, &|_| {})
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ModelError::SizeMismatch { expected: 20, actual: 11 }),
+            "{}",
+            err
+        );
+
+        let _ = fs::remove_file(&temp_path).await;
+        let err = manager
+            .download_file(&server.url("/short.gguf"), &temp_path, 20, &|_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ModelError::SizeMismatch { .. }), "{}", err);
+
+        let _ = fs::remove_file(&temp_path).await;
+        // The body ends before its Content-Length, which the client reports
+        let err = manager
+            .download_with_verification(&server.url("/cut-off.gguf"), &temp_path, "", 20, &|_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ModelError::Network(_)), "{}", err);
+
+        let _ = fs::remove_file(&temp_path).await;
+        manager
+            .download_with_verification(&server.url("/short.gguf"), &temp_path, "", 11, &|_| {})
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_decompresses_zstd_content_encoding() {
+        let plain = b"GGUF plain model bytes".repeat(100);
+        let expected = format!("{:x}", Sha256::digest(&plain));
+        let compressed = zstd::encode_all(&plain[..], 3).unwrap();
+        let server = MockServer::start(move |_| MockResponse {
+            headers: vec![("Content-Encoding".to_string(), "zstd".to_string())],
+            ..MockResponse::ok(&compressed)
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest::new());
+
+        let temp_path = temp_dir.path().join("encoded.part");
+        manager
+            .download_with_verification(
+                &server.url("/encoded.gguf"),
+                &temp_path,
+                &expected,
+                plain.len() as u64,
+                &|_| {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(fs::read(&temp_path).await.unwrap(), plain);
+    }
+
+    #[test]
+    fn test_split_file_paths_strip_zst() {
+        let paths = split_file_paths(
+            Path::new("/models"),
+            "https://example.com/m-00001-of-00002.gguf.zst",
+            2,
+        )
+        .unwrap();
+        assert_eq!(paths[0], PathBuf::from("/models/m-00001-of-00002.gguf"));
+        assert_eq!(paths[1], PathBuf::from("/models/m-00002-of-00002.gguf"));
+    }
+
+    /// Collects the fields of every tracing event as strings.
+    #[derive(Clone, Default)]
+    struct CapturedEvents(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for CapturedEvents {
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Visitor(HashMap<String, String>);
+            impl tracing::field::Visit for Visitor {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0.insert(field.name().to_string(), format!("{:?}", value));
+                }
+                fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                    self.0.insert(field.name().to_string(), value.to_string());
+                }
+            }
+            let mut visitor = Visitor(HashMap::new());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_retry_emits_structured_event() {
+        use tracing_subscriber::layer::SubscriberExt;
+        tokio::time::pause();
+
+        let served = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&served);
+        let server = MockServer::start(move |_| {
+            let mut count = counter.lock().unwrap();
+            *count += 1;
+            if *count == 1 {
+                MockResponse { status: 500, ..MockResponse::ok(b"") }
+            } else {
+                MockResponse::ok(b"hello world")
+            }
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let url = server.url("/flaky.gguf");
+        let manager = ModelManager::with_manifest(
+            temp_dir.path().to_path_buf(),
+            hello_manifest("flaky", url.clone()),
+        );
+
+        let events = CapturedEvents::default();
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(events.clone()),
+        );
+        manager.download("flaky", |_| {}).await.unwrap();
+
+        let events = events.0.lock().unwrap();
+        let retry = events
+            .iter()
+            .find(|e| e.get("message").is_some_and(|m| m.contains("retrying")))
+            .expect("a retry event");
+        assert_eq!(retry["attempt"], "1");
+        assert_eq!(retry["url"], url);
+        assert_eq!(retry["error_kind"], "http_status");
+        assert_eq!(retry["backoff_ms"], "1000");
+    }
+
+    #[tokio::test]
+    async fn test_download_throttles_progress_callbacks() {
+        // 100 small chunks spread over ~200ms
+        let body: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let expected = format!("{:x}", Sha256::digest(&body));
+        let served = body.clone();
+        let server = MockServer::start(move |_| {
+            MockResponse::throttled(&served, 100, Duration::from_millis(2))
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest::new());
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&updates);
+        let temp_path = temp_dir.path().join("chunked.part");
+        manager
+            .download_with_verification(
+                &server.url("/chunked.gguf"),
+                &temp_path,
+                &expected,
+                0,
+                &move |p| recorded.lock().unwrap().push(p),
+            )
+            .await
+            .unwrap();
+
+        let updates = updates.lock().unwrap().clone();
+        assert!(
+            updates.len() < 20,
+            "expected throttled progress, got {} callbacks for 100 chunks",
+            updates.len()
+        );
+        assert_eq!(updates.last().unwrap().bytes_downloaded, 10_000);
+        assert_eq!(fs::read(&temp_path).await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn test_download_bandwidth_cap_paces_without_changing_content() {
+        let server = MockServer::start(|_| MockResponse::ok(b"hello world")).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manifest = hello_manifest("capped", server.url("/capped.gguf"));
+        // 11 bytes at 20 B/s can't finish in under 550ms
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest)
+            .with_download_limits(DownloadLimits::new(Some(20), Some(1)));
+
+        let start = Instant::now();
+        let path = manager.download("capped", |_| {}).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(550), "{:?}", start.elapsed());
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello world");
+        assert!(manager.verify("capped").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_bandwidth_paces_chunks_without_banking_idle_time() {
+        assert!(DownloadLimits::new(None, None).bandwidth.is_none());
+        assert!(DownloadLimits::new(Some(0), None).bandwidth.is_none());
+
+        let limits = DownloadLimits::new(Some(1000), None);
+        let bandwidth = limits.bandwidth.as_deref().unwrap();
+        let start = Instant::now();
+        for _ in 0..5 {
+            bandwidth.pace(100).await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(500), "{:?}", start.elapsed());
+
+        // Idling doesn't earn a burst afterwards
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let start = Instant::now();
+        bandwidth.pace(100).await;
+        assert!(start.elapsed() >= Duration::from_millis(100), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_download_limit_serializes_models() {
+        let server = MockServer::start(|_| {
+            MockResponse::throttled(b"hello world", 6, Duration::from_millis(100))
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manifest = hello_manifest("first", server.url("/first.gguf"));
+        manifest
+            .models
+            .extend(hello_manifest("second", server.url("/second.gguf")).models);
+        let limits = DownloadLimits::new(None, Some(1));
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest)
+            .with_download_limits(limits);
+
+        let start = Instant::now();
+        let (first, second) = tokio::join!(
+            manager.download("first", |_| {}),
+            manager.download("second", |_| {})
+        );
+        first.unwrap();
+        second.unwrap();
+
+        // Each response takes 100ms; at once they'd take about that long
+        assert!(start.elapsed() >= Duration::from_millis(200), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_download_reports_rate_and_eta() {
+        // 20 chunks of 1000 bytes every 50ms is ~20 KB/s
+        let body = vec![b'x'; 20_000];
+        let served = body.clone();
+        let server = MockServer::start(move |_| {
+            MockResponse::throttled(&served, 1000, Duration::from_millis(50))
+        })
+        .await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest::new());
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&updates);
+        let temp_path = temp_dir.path().join("throttled.part");
+        manager
+            .download_file(&server.url("/throttled.gguf"), &temp_path, 0, &move |p| {
+                recorded.lock().unwrap().push(p)
+            })
+            .await
+            .unwrap();
+
+        let updates = updates.lock().unwrap().clone();
+        let last = updates.last().unwrap();
+        assert_eq!(last.bytes_downloaded, 20_000);
+        assert!(
+            (10_000.0..=40_000.0).contains(&last.bytes_per_second),
+            "rate {} outside tolerance band",
+            last.bytes_per_second
+        );
+        assert_eq!(last.eta_seconds, Some(0));
+
+        // Midway through, the remaining half should take about half a second
+        let mid = updates.iter().find(|p| p.bytes_downloaded >= 10_000).unwrap();
+        assert!(mid.eta_seconds.is_some_and(|eta| eta <= 2));
+    }
+
+    #[test]
+    fn test_download_progress_display() {
+        const MB: u64 = 1024 * 1024;
+        let progress = DownloadProgress::new(512 * MB, 2048 * MB, 8.0 * MB as f64);
+        assert_eq!(progress.to_string(), "512.0 MB / 2.0 GB at 8.0 MB/s, ETA 3m12s");
+
+        let progress = DownloadProgress::new(900, 0, 0.0);
+        assert_eq!(progress.to_string(), "900 B at 0 B/s");
+
+        let tracker = SplitProgress::new(3, 0);
+        let progress = tracker.update(1, &DownloadProgress::new(900, 0, 0.0));
+        assert_eq!(progress.to_string(), "900 B at 0 B/s (part 2 of 3)");
+    }
+
+    #[test]
+    fn test_download_progress_eta_unknown_without_total() {
+        let progress = DownloadProgress::new(5_000, 0, 1_000.0);
+        assert_eq!(progress.eta_seconds, None);
+
+        let progress = DownloadProgress::new(5_000, 10_000, 1_000.0);
+        assert_eq!(progress.eta_seconds, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_check_disk_space() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut manifest = hello_manifest("tiny", "https://example.com/tiny.gguf".to_string());
+        let mut huge = manifest.get("tiny").unwrap().clone();
+        huge.size_bytes = u64::MAX / 2;
+        manifest.models.insert("huge".to_string(), huge);
+        let manager = ModelManager::with_manifest(temp_dir.path().to_path_buf(), manifest);
+
+        let tiny = manager.manifest().get("tiny").unwrap();
+        assert!(manager.check_disk_space("tiny", tiny).await.is_ok());
+
+        let huge = manager.manifest().get("huge").unwrap();
+        let result = manager.check_disk_space("huge", huge).await;
+        assert!(matches!(result, Err(ModelError::InsufficientDiskSpace { .. })));
+
+        // download() refuses before touching the network
+        let result = manager.download("huge", |_| {}).await;
+        assert!(matches!(result, Err(ModelError::InsufficientDiskSpace { .. })));
+    }
+
     #[test]
     fn test_split_file_urls() {
         let first_url = "https://example.com/model-q4_k_m-00001-of-00003.gguf";
-        let urls = split_file_urls(first_url, 3);
+        let urls = split_file_urls(first_url, 3).unwrap();
         assert_eq!(urls.len(), 3);
         assert_eq!(urls[0], "https://example.com/model-q4_k_m-00001-of-00003.gguf");
         assert_eq!(urls[1], "https://example.com/model-q4_k_m-00002-of-00003.gguf");
         assert_eq!(urls[2], "https://example.com/model-q4_k_m-00003-of-00003.gguf");
     }
 
+    #[test]
+    fn test_split_file_urls_keep_padding() {
+        let first_url = "https://example.com/m-01-of-12.gguf?download=true";
+        let urls = split_file_urls(first_url, 12).unwrap();
+        assert_eq!(urls[1], "https://example.com/m-02-of-12.gguf?download=true");
+        assert_eq!(urls[11], "https://example.com/m-12-of-12.gguf?download=true");
+
+        // Part numbers outgrow the padding rather than being cut off
+        let urls = split_file_urls("https://example.com/m-1-of-10.gguf", 10).unwrap();
+        assert_eq!(urls[9], "https://example.com/m-10-of-10.gguf");
+    }
+
+    #[test]
+    fn test_split_file_urls_reject_mismatched_count() {
+        let err = split_file_urls("https://example.com/m-00001-of-00003.gguf", 2).unwrap_err();
+        assert!(matches!(err, ModelError::InvalidSplitUrl { .. }), "{}", err);
+        assert!(err.to_string().contains("names 00003 parts but split_count is 2"), "{}", err);
+
+        let err = split_file_urls("https://example.com/m-00002-of-00003.gguf", 3).unwrap_err();
+        assert!(err.to_string().contains("first part"), "{}", err);
+    }
+
+    #[test]
+    fn test_split_file_urls_reject_missing_pattern() {
+        for url in [
+            "https://example.com/model-q4_k_m.gguf",
+            "https://example.com/00001-of-00003/model.gguf",
+            "https://example.com/model-of-00003.gguf",
+        ] {
+            let err = split_file_urls(url, 3).unwrap_err();
+            assert!(matches!(err, ModelError::InvalidSplitUrl { .. }), "{}: {}", url, err);
+        }
+    }
+
     #[test]
     fn test_split_file_paths() {
         let first_url = "https://example.com/model-q4_k_m-00001-of-00003.gguf";
-        let paths = split_file_paths(Path::new("/tmp/models"), first_url, 3);
+        let paths = split_file_paths(Path::new("/tmp/models"), first_url, 3).unwrap();
         assert_eq!(paths.len(), 3);
         assert_eq!(paths[0], PathBuf::from("/tmp/models/model-q4_k_m-00001-of-00003.gguf"));
         assert_eq!(paths[1], PathBuf::from("/tmp/models/model-q4_k_m-00002-of-00003.gguf"));
@@ -733,7 +3046,11 @@ mod tests {
                 sha256: "".to_string(),
                 download_url: "https://example.com/split-model-q4_k_m-00001-of-00003.gguf".to_string(),
                 split_count: 3,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
                 supported_backends: vec![Backend::Cuda],
+                chat_template: None,
             },
         );
         let manifest = ModelManifest { models };
@@ -746,6 +3063,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_size_on_disk_sums_split_parts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut models = HashMap::new();
+        models.insert(
+            "split-model".to_string(),
+            ModelEntry {
+                quantization: "q4_k_m".to_string(),
+                size_bytes: 60,
+                sha256: "".to_string(),
+                download_url: "https://example.com/split-model-q4_k_m-00001-of-00003.gguf".to_string(),
+                split_count: 3,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
+                supported_backends: vec![Backend::Cuda],
+                chat_template: None,
+            },
+        );
+        let manager =
+            ModelManager::with_manifest(temp_dir.path().to_path_buf(), ModelManifest { models });
+        assert_eq!(manager.size_on_disk("split-model"), 0);
+
+        for (i, size) in [10, 20, 30].into_iter().enumerate() {
+            let part = format!("split-model-q4_k_m-{:05}-of-00003.gguf", i + 1);
+            std::fs::write(temp_dir.path().join(part), vec![0u8; size]).unwrap();
+        }
+        assert_eq!(manager.size_on_disk("split-model"), 60);
+
+        std::fs::write(temp_dir.path().join("single.gguf"), b"12345").unwrap();
+        assert_eq!(manager.size_on_disk("single"), 5);
+    }
+
+    #[test]
+    fn test_total_download_size_sums_part_sizes() {
+        let mut manifest =
+            checked_split_manifest("https://example.com/checked-00001-of-00002.gguf".to_string());
+        let mut entry = manifest.models.remove("checked").unwrap();
+        entry.download_url = "https://example.com/three-00001-of-00003.gguf".to_string();
+        entry.split_count = 3;
+        entry.part_sha256 = Vec::new();
+        entry.size_bytes = 1;
+        entry.part_size_bytes = vec![1_000, 2_500, 700];
+        manifest.models.insert("three".to_string(), entry.clone());
+        entry.part_size_bytes = Vec::new();
+        manifest.models.insert("totals-only".to_string(), entry);
+
+        let manager = ModelManager::with_manifest(PathBuf::from("/tmp/models"), manifest);
+        assert_eq!(manager.total_download_size("three"), 4_200);
+        assert_eq!(manager.total_download_size("totals-only"), 1);
+        assert_eq!(manager.total_download_size("missing"), 0);
+    }
+
     #[tokio::test]
     async fn test_verify_skips_empty_checksum() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -758,7 +3128,11 @@ mod tests {
                 sha256: "".to_string(),
                 download_url: "https://example.com/no-checksum.gguf".to_string(),
                 split_count: 1,
+                part_sha256: Vec::new(),
+                part_size_bytes: Vec::new(),
+                mirror_urls: Vec::new(),
                 supported_backends: vec![Backend::Cuda],
+                chat_template: None,
             },
         );
         let manifest = ModelManifest { models };
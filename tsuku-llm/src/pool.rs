@@ -0,0 +1,190 @@
+//! Pool of inference contexts.
+//!
+//! A llama context can only run one generation at a time, so the server keeps
+//! several and hands one to each request. Requests beyond the pool size wait
+//! for a context to be returned.
+//!
+//! Each context keeps the prompt prefix it last decoded in its KV cache,
+//! so a request sharing that prefix only decodes what comes after it.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::generation::{InferenceContext, PrefixCache};
+
+/// A fixed set of contexts shared between requests.
+pub struct ContextPool<C> {
+    /// Contexts not currently checked out, with the prefix each one holds.
+    available: Mutex<Vec<(C, PrefixCache)>>,
+    /// One permit per context; holding a permit guarantees one is available.
+    permits: Arc<Semaphore>,
+}
+
+impl<C: InferenceContext> ContextPool<C> {
+    /// Create a pool from pre-created contexts.
+    pub fn new(contexts: Vec<C>) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(contexts.len())),
+            available: Mutex::new(
+                contexts
+                    .into_iter()
+                    .map(|c| (c, PrefixCache::default()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Check out a context, waiting until one is free.
+    ///
+    /// The KV cache still holds the previous request's tokens; decode the
+    /// prompt through `PooledContext::parts` so the prefix cache can reuse
+    /// or clear it. The context goes back to the pool when the returned
+    /// guard is dropped.
+    pub async fn acquire(self: &Arc<Self>) -> PooledContext<C> {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("context pool semaphore is never closed");
+
+        let slot = self
+            .available
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .expect("a permit guarantees an available context");
+
+        PooledContext {
+            slot: Some(slot),
+            pool: Arc::clone(self),
+            _permit: permit,
+        }
+    }
+}
+
+/// A context checked out of a `ContextPool`.
+pub struct PooledContext<C: InferenceContext> {
+    slot: Option<(C, PrefixCache)>,
+    pool: Arc<ContextPool<C>>,
+    // Released after the context is back in the pool (fields drop after `drop`)
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<C: InferenceContext> PooledContext<C> {
+    /// The context together with the record of what its KV cache holds.
+    pub fn parts(&mut self) -> (&mut C, &mut PrefixCache) {
+        let (context, prefix) = self.slot.as_mut().expect("context present until drop");
+        (context, prefix)
+    }
+}
+
+impl<C: InferenceContext> Deref for PooledContext<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.slot.as_ref().expect("context present until drop").0
+    }
+}
+
+impl<C: InferenceContext> DerefMut for PooledContext<C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.parts().0
+    }
+}
+
+impl<C: InferenceContext> Drop for PooledContext<C> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            self.pool
+                .available
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::generation::testing::MockContext;
+    use crate::generation::{self, GenerationConfig};
+    use crate::llama::Sampler;
+    use tokio::sync::Barrier;
+
+    fn pool(size: usize) -> Arc<ContextPool<MockContext>> {
+        let contexts = (0..size)
+            .map(|_| MockContext::new(vec![5, 6, 7], 63))
+            .collect();
+        Arc::new(ContextPool::new(contexts))
+    }
+
+    #[tokio::test]
+    async fn test_prefix_cache_kept_across_checkouts() {
+        let pool = pool(1);
+        let system = vec![1; 50];
+
+        let mut ctx = pool.acquire().await;
+        let (context, prefix) = ctx.parts();
+        prefix.decode_prompt(context, &system, &[2]).unwrap();
+        drop(ctx);
+
+        let mut ctx = pool.acquire().await;
+        let (context, prefix) = ctx.parts();
+        prefix.decode_prompt(context, &system, &[3]).unwrap();
+        assert_eq!(ctx.decoded_tokens, 52);
+        assert_eq!(ctx.kv_clears, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_completions_run_in_parallel() {
+        let pool = pool(2);
+        let barrier = Arc::new(Barrier::new(2));
+
+        // Each request holds its context until the other one also has one,
+        // which deadlocks if the pool serializes them.
+        let request = |pool: Arc<ContextPool<MockContext>>, barrier: Arc<Barrier>| async move {
+            let mut ctx = pool.acquire().await;
+            barrier.wait().await;
+            let config = GenerationConfig {
+                max_tokens: 10,
+                timeout: Duration::from_secs(60),
+                length_penalty: 0.0,
+                logit_bias: Vec::new(),
+                repetition_penalty: 1.0,
+            };
+            generation::generate(&mut *ctx, &Sampler::greedy(), 1, &config, |_, _| true).tokens
+        };
+        let both = futures_util::future::join(
+            tokio::spawn(request(pool.clone(), barrier.clone())),
+            tokio::spawn(request(pool.clone(), barrier.clone())),
+        );
+
+        let (a, b) = tokio::time::timeout(Duration::from_secs(5), both)
+            .await
+            .expect("completions should not block each other");
+        assert_eq!(a.unwrap(), vec![5, 6, 7]);
+        assert_eq!(b.unwrap(), vec![5, 6, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_returned_context() {
+        let pool = pool(1);
+        let held = pool.acquire().await;
+
+        let waiting = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.acquire().await.decoded_tokens }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        drop(held);
+        assert_eq!(waiting.await.unwrap(), 0);
+    }
+}
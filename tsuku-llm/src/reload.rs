@@ -0,0 +1,291 @@
+//! Swapping the served model without restarting the server.
+//!
+//! Requests take a snapshot of the current model when they start and keep
+//! it until they finish. A replacement turns new requests away, waits for
+//! the running ones to drop their snapshots, and unloads the old model
+//! before loading the new one, so the two never need memory at once. If
+//! the new model fails to load, the old one is loaded again.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// How often a replacement checks whether running requests have finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Why a model couldn't be replaced.
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError<E> {
+    /// Another replacement hasn't finished yet.
+    #[error("a model reload is already in progress")]
+    InProgress,
+    /// Loading the new model failed; the previous model was loaded again.
+    #[error(transparent)]
+    Load(E),
+    /// Loading the new model failed and so did loading the previous one
+    /// again; the slot is empty until the next successful replacement.
+    #[error("{load}; reloading the previous model also failed: {restore}")]
+    Restore { load: E, restore: E },
+}
+
+/// Holds the model currently being served.
+pub struct ModelSlot<T> {
+    current: RwLock<Option<Arc<T>>>,
+    replacing: AtomicBool,
+}
+
+impl<T> ModelSlot<T> {
+    pub fn new(model: T) -> Self {
+        Self {
+            current: RwLock::new(Some(Arc::new(model))),
+            replacing: AtomicBool::new(false),
+        }
+    }
+
+    /// A slot with no model, as a failed replacement leaves it.
+    #[cfg(test)]
+    pub fn empty() -> Self {
+        Self {
+            current: RwLock::new(None),
+            replacing: AtomicBool::new(false),
+        }
+    }
+
+    /// Snapshot of the current model, or `None` if a failed replacement
+    /// left the slot empty.
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Mutable access before the slot is shared with any request.
+    ///
+    /// # Panics
+    ///
+    /// If a snapshot of the model is still held.
+    pub fn get_mut(&mut self) -> &mut T {
+        let current = self.current.get_mut().unwrap_or_else(|e| e.into_inner());
+        current
+            .as_mut()
+            .and_then(Arc::get_mut)
+            .expect("model isn't shared yet")
+    }
+
+    /// Whether a replacement is under way. New requests should be turned
+    /// away meanwhile.
+    pub fn is_replacing(&self) -> bool {
+        self.replacing.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no request holds a snapshot, then take the model out.
+    async fn drain(&self) -> Option<T> {
+        loop {
+            {
+                let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+                match Arc::try_unwrap(current.take()?) {
+                    Ok(model) => return Some(model),
+                    Err(model) => *current = Some(model),
+                }
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> ModelSlot<T> {
+    /// Replace the model with the one `load` produces.
+    ///
+    /// Once running requests finish, the old model is handed to `unload`
+    /// (and freed when it returns), then `load` runs. If it fails, `restore`
+    /// gets what `unload` returned and loads the old model again. Returns
+    /// what `unload` returned, or `None` if the slot was empty. The
+    /// replacement runs as its own task, so it completes even if the caller
+    /// stops waiting.
+    pub async fn replace<U, R, L, F, RL, E>(
+        self: &Arc<Self>,
+        unload: U,
+        load: L,
+        restore: F,
+    ) -> Result<Option<R>, ReloadError<E>>
+    where
+        U: FnOnce(T) -> R + Send + 'static,
+        R: Send + 'static,
+        L: Future<Output = Result<T, E>> + Send + 'static,
+        F: FnOnce(&R) -> RL + Send + 'static,
+        RL: Future<Output = Result<T, E>> + Send + 'static,
+        E: Send + 'static,
+    {
+        if self.replacing.swap(true, Ordering::SeqCst) {
+            return Err(ReloadError::InProgress);
+        }
+        let slot = Arc::clone(self);
+        let task = tokio::spawn(async move {
+            let _done =
+                crate::scopeguard::guard((), |_| slot.replacing.store(false, Ordering::SeqCst));
+            let previous = slot.drain().await.map(unload);
+            let (model, failed) = match load.await {
+                Ok(model) => (model, None),
+                Err(load) => {
+                    let Some(previous) = &previous else {
+                        return Err(ReloadError::Load(load));
+                    };
+                    match restore(previous).await {
+                        Ok(model) => (model, Some(ReloadError::Load(load))),
+                        Err(restore) => return Err(ReloadError::Restore { load, restore }),
+                    }
+                }
+            };
+            *slot.current.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(model));
+            if let Some(e) = failed {
+                return Err(e);
+            }
+            Ok(previous)
+        });
+        match task.await {
+            Ok(result) => result,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replace_waits_for_snapshots_then_swaps_model() {
+        let slot = Arc::new(ModelSlot::new("small".to_string()));
+        let in_flight = slot.get().unwrap();
+
+        let (unloaded_tx, unloaded_rx) = tokio::sync::oneshot::channel();
+        let replace = tokio::spawn({
+            let slot = slot.clone();
+            async move {
+                let unload = move |old: String| {
+                    unloaded_tx.send(()).unwrap();
+                    old
+                };
+                let load = async { Ok::<_, String>("large".to_string()) };
+                slot.replace(unload, load, |_| async { unreachable!() })
+                    .await
+            }
+        });
+
+        // Turned away while the old model drains; a second reload too
+        tokio::time::sleep(DRAIN_POLL_INTERVAL * 3).await;
+        assert!(slot.is_replacing());
+        let second = slot
+            .replace(
+                |old| old,
+                async { Ok::<_, String>("other".to_string()) },
+                |_| async { unreachable!() },
+            )
+            .await;
+        assert!(matches!(second, Err(ReloadError::InProgress)));
+        assert_eq!(*slot.get().unwrap(), "small");
+
+        // The old model is only unloaded once the request using it is done
+        drop(in_flight);
+        unloaded_rx.await.unwrap();
+        let previous = replace.await.unwrap().unwrap();
+
+        assert_eq!(previous.as_deref(), Some("small"));
+        assert_eq!(*slot.get().unwrap(), "large");
+        assert!(!slot.is_replacing());
+    }
+
+    #[tokio::test]
+    async fn test_old_model_is_unloaded_before_loading() {
+        let slot = Arc::new(ModelSlot::new(1));
+        let loaded_models = Arc::new(std::sync::atomic::AtomicUsize::new(1));
+
+        let unloading = loaded_models.clone();
+        let loading = loaded_models.clone();
+        slot.replace(
+            move |_| unloading.fetch_sub(1, Ordering::SeqCst),
+            async move {
+                // Only one model is ever resident
+                assert_eq!(loading.fetch_add(1, Ordering::SeqCst), 0);
+                Ok::<_, String>(2)
+            },
+            |_| async { unreachable!() },
+        )
+        .await
+        .unwrap();
+        assert_eq!(*slot.get().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replace_finishes_when_caller_stops_waiting() {
+        let slot = Arc::new(ModelSlot::new("small"));
+        let (loaded_tx, loaded_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let load = async {
+            loaded_rx.await.unwrap();
+            Ok::<_, String>("large")
+        };
+        let replace = slot.replace(|_| (), load, |_| async { unreachable!() });
+        // Give up on the reload while the new model is loading
+        let _ = tokio::time::timeout(DRAIN_POLL_INTERVAL, replace).await;
+        assert!(slot.is_replacing());
+
+        loaded_tx.send(()).unwrap();
+        while slot.is_replacing() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(*slot.get().unwrap(), "large");
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_restores_previous_model() {
+        let slot = Arc::new(ModelSlot::new("small"));
+        let result = slot
+            .replace(
+                |old| old,
+                async { Err::<&str, _>("out of memory".to_string()) },
+                |previous| {
+                    let previous = *previous;
+                    async move { Ok(previous) }
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(ReloadError::Load(e)) if e == "out of memory"));
+        assert_eq!(*slot.get().unwrap(), "small");
+        assert!(!slot.is_replacing());
+    }
+
+    #[tokio::test]
+    async fn test_failed_restore_leaves_slot_empty() {
+        let slot = Arc::new(ModelSlot::new("small"));
+        let result = slot
+            .replace(
+                |_| (),
+                async { Err::<&str, _>("out of memory".to_string()) },
+                |_| async { Err("file removed".to_string()) },
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ReloadError::Restore { load, restore })
+                if load == "out of memory" && restore == "file removed"
+        ));
+        assert!(slot.get().is_none());
+        assert!(!slot.is_replacing());
+
+        // The next replacement fills it again
+        let previous = slot
+            .replace(
+                |_| (),
+                async { Ok::<_, String>("small") },
+                |_| async { unreachable!() },
+            )
+            .await;
+        assert!(matches!(previous, Ok(None)));
+        assert_eq!(*slot.get().unwrap(), "small");
+    }
+}
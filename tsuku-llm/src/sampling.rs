@@ -0,0 +1,108 @@
+//! Named sampling presets.
+//!
+//! Most callers want "deterministic" or "a bit more varied" rather than a
+//! particular temperature, so requests pick a preset and may still override
+//! its individual parameters.
+
+use crate::llama::Sampler;
+use crate::proto::{self, CompletionRequest};
+
+/// A curated set of sampling parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SamplingPreset {
+    /// Greedy decoding (temperature 0); the same prompt gives the same output.
+    #[default]
+    Precise,
+    /// Temperature 0.7, top-k 40, top-p 0.9.
+    Balanced,
+    /// Temperature 1.0, top-k 100, top-p 0.95.
+    Creative,
+}
+
+impl SamplingPreset {
+    /// The sampler this preset stands for.
+    pub fn sampler(self) -> Sampler {
+        let (temperature, top_k, top_p) = match self {
+            // top-k and top-p only apply if the request raises the temperature
+            Self::Precise => (0.0, 20, 0.8),
+            Self::Balanced => (0.7, 40, 0.9),
+            Self::Creative => (1.0, 100, 0.95),
+        };
+        Sampler::new(temperature, top_k, top_p)
+    }
+
+    /// The preset a request names, if any.
+    fn from_proto(preset: i32) -> Option<Self> {
+        match proto::SamplingPreset::try_from(preset) {
+            Ok(proto::SamplingPreset::Precise) => Some(Self::Precise),
+            Ok(proto::SamplingPreset::Balanced) => Some(Self::Balanced),
+            Ok(proto::SamplingPreset::Creative) => Some(Self::Creative),
+            _ => None,
+        }
+    }
+}
+
+/// The sampler for a request: its preset (or the server default), with any
+/// explicitly set parameters taking precedence.
+pub fn sampler_for(req: &CompletionRequest, default: SamplingPreset) -> Sampler {
+    let preset = SamplingPreset::from_proto(req.preset).unwrap_or(default);
+    let mut sampler = preset.sampler();
+    if let Some(temperature) = req.temperature {
+        sampler.temperature = temperature.max(0.0);
+    }
+    if let Some(top_k) = req.top_k {
+        sampler.top_k = top_k.max(0) as usize;
+    }
+    if let Some(top_p) = req.top_p {
+        sampler.top_p = top_p.clamp(0.0, 1.0);
+    }
+    sampler
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(sampler: Sampler) -> (f32, usize, f32) {
+        (sampler.temperature, sampler.top_k, sampler.top_p)
+    }
+
+    #[test]
+    fn test_presets_map_to_documented_parameters() {
+        assert_eq!(params(SamplingPreset::Precise.sampler()), (0.0, 20, 0.8));
+        assert_eq!(params(SamplingPreset::Balanced.sampler()), (0.7, 40, 0.9));
+        assert_eq!(params(SamplingPreset::Creative.sampler()), (1.0, 100, 0.95));
+    }
+
+    #[test]
+    fn test_request_preset_overrides_server_default() {
+        let req = CompletionRequest {
+            preset: proto::SamplingPreset::Creative as i32,
+            ..Default::default()
+        };
+        assert_eq!(
+            params(sampler_for(&req, SamplingPreset::Precise)),
+            params(SamplingPreset::Creative.sampler())
+        );
+
+        let unset = CompletionRequest::default();
+        assert_eq!(
+            params(sampler_for(&unset, SamplingPreset::Balanced)),
+            params(SamplingPreset::Balanced.sampler())
+        );
+    }
+
+    #[test]
+    fn test_explicit_parameters_override_preset() {
+        let req = CompletionRequest {
+            preset: proto::SamplingPreset::Creative as i32,
+            temperature: Some(0.3),
+            top_p: Some(0.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            params(sampler_for(&req, SamplingPreset::Precise)),
+            (0.3, 100, 0.5)
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Basic JSON Schema checks for tool-call arguments.
+//!
+//! Covers the subset tool definitions use in practice: `type`, `required`,
+//! `properties`, `items` and `enum`. Anything else in the schema is ignored,
+//! so an unfamiliar schema accepts rather than rejects.
+
+use serde_json::Value;
+
+/// Check `value` against `schema`, returning a description of the first
+/// mismatch.
+pub fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    validate_at(schema, value, "arguments")
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(t) => type_matches(t, value),
+            // A list of allowed types
+            Value::Array(types) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|t| type_matches(t, value)),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!("{} should be of type {}", path, expected));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!("{} is not one of the allowed values", path));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        for key in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(key) {
+                return Err(format!("{} is missing required field '{}'", path, key));
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, property) in properties {
+                if let Some(field) = object.get(key) {
+                    validate_at(property, field, &format!("{}.{}", path, key))?;
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string"},
+                "depth": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "mode": {"enum": ["fast", "full"]}
+            },
+            "required": ["url"]
+        })
+    }
+
+    #[test]
+    fn test_validate_accepts_matching_arguments() {
+        let args = json!({"url": "https://example.com", "depth": 2, "tags": ["a"], "mode": "fast"});
+        assert!(validate(&schema(), &args).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_required_field() {
+        let err = validate(&schema(), &json!({"depth": 2})).unwrap_err();
+        assert!(err.contains("'url'"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_types() {
+        let err = validate(&schema(), &json!({"url": 7})).unwrap_err();
+        assert!(err.contains("arguments.url"), "{}", err);
+
+        assert!(validate(&schema(), &json!({"url": "x", "depth": 1.5})).is_err());
+        assert!(validate(&schema(), &json!({"url": "x", "tags": [1]})).is_err());
+        assert!(validate(&schema(), &json!({"url": "x", "mode": "slow"})).is_err());
+        assert!(validate(&schema(), &json!(["url"])).is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! The gRPC service offered while the model is downloading.
+//!
+//! The socket is bound before the download starts, so clients connecting
+//! during a long download get a not-ready status instead of a refused
+//! connection, and can cancel the download with `CancelStartup`.
+
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use futures_util::Stream;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::listener::{self, Connection};
+use crate::models::{ModelError, ModelManager};
+use crate::proto::inference_service_server::{InferenceService, InferenceServiceServer};
+use crate::proto::{
+    CancelStartupRequest, CancelStartupResponse, CompletionRequest, CompletionResponse,
+    CountTokensRequest, CountTokensResponse, DetokenizeRequest, DetokenizeResponse, EmbedRequest,
+    EmbedResponse, MetricsRequest, MetricsResponse, PingRequest, PingResponse, ReloadModelRequest,
+    ReloadModelResponse, ShutdownRequest, ShutdownResponse, StatusRequest, StatusResponse,
+    TokenizeRequest, TokenizeResponse,
+};
+
+/// Exit code when a client cancelled the startup download.
+pub const EXIT_CANCELLED: i32 = 3;
+
+/// How a startup phase ended.
+#[derive(Debug)]
+pub enum Outcome<T> {
+    /// The work ran to completion.
+    Finished(T),
+    /// A client called `CancelStartup`.
+    Cancelled,
+    /// The terminate signal fired first.
+    Terminated,
+}
+
+impl<T> Outcome<T> {
+    /// The code to exit with when startup ended early, or `None` if the
+    /// work finished.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Outcome::Finished(_) => None,
+            Outcome::Cancelled => Some(EXIT_CANCELLED),
+            Outcome::Terminated => Some(0),
+        }
+    }
+}
+
+/// Service answering requests until the model is loaded.
+struct StartupServer {
+    model_name: String,
+    cancel_tx: mpsc::Sender<()>,
+}
+
+fn still_downloading() -> Status {
+    Status::unavailable("Model is still downloading")
+}
+
+#[tonic::async_trait]
+impl InferenceService for StartupServer {
+    async fn complete(
+        &self,
+        _request: Request<CompletionRequest>,
+    ) -> Result<Response<CompletionResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    type CompleteStreamStream = ReceiverStream<Result<CompletionResponse, Status>>;
+
+    async fn complete_stream(
+        &self,
+        _request: Request<CompletionRequest>,
+    ) -> Result<Response<Self::CompleteStreamStream>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn embed(
+        &self,
+        _request: Request<EmbedRequest>,
+    ) -> Result<Response<EmbedResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn count_tokens(
+        &self,
+        _request: Request<CountTokensRequest>,
+    ) -> Result<Response<CountTokensResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn tokenize(
+        &self,
+        _request: Request<TokenizeRequest>,
+    ) -> Result<Response<TokenizeResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn detokenize(
+        &self,
+        _request: Request<DetokenizeRequest>,
+    ) -> Result<Response<DetokenizeResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn shutdown(
+        &self,
+        _request: Request<ShutdownRequest>,
+    ) -> Result<Response<ShutdownResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn get_status(
+        &self,
+        _request: Request<StatusRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        Ok(Response::new(StatusResponse {
+            ready: false,
+            model_name: self.model_name.clone(),
+            addon_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        }))
+    }
+
+    async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+        Ok(Response::new(PingResponse { ready: false }))
+    }
+
+    async fn get_metrics(
+        &self,
+        _request: Request<MetricsRequest>,
+    ) -> Result<Response<MetricsResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn reload_model(
+        &self,
+        _request: Request<ReloadModelRequest>,
+    ) -> Result<Response<ReloadModelResponse>, Status> {
+        Err(still_downloading())
+    }
+
+    async fn cancel_startup(
+        &self,
+        _request: Request<CancelStartupRequest>,
+    ) -> Result<Response<CancelStartupResponse>, Status> {
+        info!("Startup cancelled by client");
+        // A full channel means a cancellation is already pending
+        let _ = self.cancel_tx.try_send(());
+        Ok(Response::new(CancelStartupResponse {}))
+    }
+}
+
+/// Run `work` while serving the startup service on `incoming`.
+///
+/// The server stops before this returns, leaving `incoming` for the main
+/// server to take over.
+pub async fn serve_during<W, S, T>(
+    work: W,
+    incoming: Pin<&mut S>,
+    model_name: String,
+    auth: listener::TcpAuth,
+    terminate: T,
+) -> Outcome<W::Output>
+where
+    W: Future,
+    S: Stream<Item = io::Result<Connection>>,
+    T: Future<Output = ()>,
+{
+    let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+
+    let server = tonic::transport::Server::builder()
+        .add_service(InferenceServiceServer::with_interceptor(
+            StartupServer {
+                model_name,
+                cancel_tx,
+            },
+            auth,
+        ))
+        .serve_with_incoming_shutdown(incoming, async {
+            let _ = stop_rx.await;
+        });
+
+    let phase = async {
+        let outcome = tokio::select! {
+            output = work => Outcome::Finished(output),
+            Some(()) = cancel_rx.recv() => Outcome::Cancelled,
+            () = terminate => Outcome::Terminated,
+        };
+        let _ = stop_tx.send(());
+        outcome
+    };
+
+    let (outcome, served) = tokio::join!(phase, server);
+    if let Err(e) = served {
+        warn!("Startup server error: {}", e);
+    }
+    outcome
+}
+
+/// Download `model_name` with `serve_during`, so clients can watch it and
+/// cancel it. A cancelled download's partial files are removed.
+pub async fn download_model<S, T>(
+    model_manager: &ModelManager,
+    model_name: &str,
+    incoming: Pin<&mut S>,
+    auth: listener::TcpAuth,
+    terminate: T,
+) -> Outcome<Result<PathBuf, ModelError>>
+where
+    S: Stream<Item = io::Result<Connection>>,
+    T: Future<Output = ()>,
+{
+    let download = model_manager.download(model_name, |progress| {
+        info!("Download progress: {}", progress);
+    });
+    let outcome = serve_during(download, incoming, model_name.to_string(), auth, terminate).await;
+    if let Outcome::Cancelled = outcome {
+        model_manager.remove_partial_downloads(model_name).await;
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelManifest;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, UnixListener};
+
+    /// Serve the start of a response and then nothing more, like a stalled
+    /// download. Returns the URL to download from.
+    async fn stalled_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/test.gguf", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut request = [0; 1024];
+                    let _ = stream.read(&mut request).await;
+                    let head = "HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\nGGUF";
+                    let _ = stream.write_all(head.as_bytes()).await;
+                    std::future::pending::<()>().await;
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_cancel_startup_stops_the_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("llm.sock");
+        let incoming = listener::incoming(UnixListener::bind(&socket).unwrap(), None);
+        let mut incoming = std::pin::pin!(incoming);
+
+        let models_dir = dir.path().join("models");
+        let manifest: ModelManifest = serde_json::from_value(serde_json::json!({
+            "test": {"quantization": "q4", "size_bytes": 1000, "sha256": "",
+                "download_url": stalled_server().await, "split_count": 1,
+                "supported_backends": ["cuda"]}
+        }))
+        .unwrap();
+        let manager = ModelManager::with_manifest(models_dir.clone(), manifest);
+        let partial = models_dir.join(".download").join("test.gguf.part");
+
+        let client = async {
+            // Cancel once the download is under way
+            while !partial.exists() {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+
+            let channel = tonic::transport::Endpoint::from_static("http://localhost")
+                .connect_with_connector(tower::service_fn(move |_| {
+                    let socket = socket.clone();
+                    async move {
+                        let stream = tokio::net::UnixStream::connect(socket).await?;
+                        Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+                    }
+                }))
+                .await
+                .unwrap();
+            let mut grpc = tonic::client::Grpc::new(channel);
+
+            grpc.ready().await.unwrap();
+            let codec: tonic::codec::ProstCodec<StatusRequest, StatusResponse> = Default::default();
+            let status = grpc
+                .unary(
+                    Request::new(StatusRequest {}),
+                    tonic::codegen::http::uri::PathAndQuery::from_static(
+                        "/tsuku.llm.v1.InferenceService/GetStatus",
+                    ),
+                    codec,
+                )
+                .await
+                .unwrap()
+                .into_inner();
+            assert!(!status.ready);
+            assert_eq!(status.model_name, "test");
+
+            grpc.ready().await.unwrap();
+            let codec: tonic::codec::ProstCodec<CancelStartupRequest, CancelStartupResponse> =
+                Default::default();
+            grpc.unary(
+                Request::new(CancelStartupRequest {}),
+                tonic::codegen::http::uri::PathAndQuery::from_static(
+                    "/tsuku.llm.v1.InferenceService/CancelStartup",
+                ),
+                codec,
+            )
+            .await
+            .unwrap();
+        };
+
+        // A download that never finishes on its own
+        let startup = download_model(
+            &manager,
+            "test",
+            incoming.as_mut(),
+            listener::TcpAuth::new(None),
+            std::future::pending(),
+        );
+        let (outcome, ()) = tokio::join!(startup, client);
+        assert!(matches!(outcome, Outcome::Cancelled));
+        assert_eq!(outcome.exit_code(), Some(EXIT_CANCELLED));
+        assert!(!partial.exists(), "the partial download is removed");
+    }
+}
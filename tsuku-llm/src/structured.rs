@@ -0,0 +1,151 @@
+//! Single-value answers for classification and extraction tasks.
+//!
+//! A request's `response_schema` asks for one label out of a list, or one
+//! number. Grammar-constrained sampling is disabled (see `complete`), so the
+//! expected answer is described in the system prompt and parsed back out of
+//! the model's output.
+
+use crate::proto::{structured_result::Value, ResponseSchema, StructuredResult};
+
+/// Instructions describing the expected answer, for the system prompt.
+pub fn instructions(schema: &ResponseSchema) -> Option<String> {
+    if !schema.enum_values.is_empty() {
+        Some(format!(
+            "Answer with exactly one of the following labels and nothing else: {}",
+            schema.enum_values.join(", ")
+        ))
+    } else if schema.number {
+        Some("Answer with a single number and nothing else.".to_string())
+    } else {
+        None
+    }
+}
+
+/// Parse the answer out of the model's output, if it contains one.
+pub fn parse(schema: &ResponseSchema, content: &str) -> Option<StructuredResult> {
+    let value = if !schema.enum_values.is_empty() {
+        Value::Label(find_label(&schema.enum_values, content)?.to_string())
+    } else if schema.number {
+        Value::Number(find_number(content)?)
+    } else {
+        return None;
+    };
+    Some(StructuredResult { value: Some(value) })
+}
+
+/// The label the output names, ignoring case and surrounding punctuation.
+///
+/// When the output is more than a bare label, the label mentioned first as
+/// a whole word wins (the longest one, if several start at the same place),
+/// so "no" isn't found in "know".
+fn find_label<'a>(labels: &'a [String], content: &str) -> Option<&'a str> {
+    let labels = labels.iter().filter(|l| !l.is_empty());
+    let answer = content
+        .trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+        .to_lowercase();
+    if let Some(label) = labels.clone().find(|l| l.to_lowercase() == answer) {
+        return Some(label);
+    }
+
+    let content = content.to_lowercase();
+    labels
+        .filter_map(|l| find_word(&content, &l.to_lowercase()).map(|pos| (pos, l)))
+        .min_by_key(|&(pos, l)| (pos, std::cmp::Reverse(l.len())))
+        .map(|(_, l)| l.as_str())
+}
+
+/// Byte offset of the first occurrence of `word` in `text` that isn't part
+/// of a longer word.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    text.match_indices(word).map(|(pos, _)| pos).find(|&pos| {
+        !is_word_char(text[..pos].chars().next_back())
+            && !is_word_char(text[pos + word.len()..].chars().next())
+    })
+}
+
+/// The first number in the output.
+fn find_number(content: &str) -> Option<f64> {
+    content
+        .split_whitespace()
+        .map(|word| {
+            word.trim_start_matches(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+                .trim_end_matches(|c: char| !c.is_ascii_digit())
+        })
+        .filter_map(|word| word.parse::<f64>().ok())
+        .find(|n| n.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(values: &[&str]) -> ResponseSchema {
+        ResponseSchema {
+            enum_values: values.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn label(result: Option<StructuredResult>) -> Option<String> {
+        match result?.value? {
+            Value::Label(label) => Some(label),
+            Value::Number(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_enum_label() {
+        let schema = labels(&["positive", "negative", "neutral"]);
+        assert_eq!(
+            label(parse(&schema, "positive")).as_deref(),
+            Some("positive")
+        );
+        assert_eq!(
+            label(parse(&schema, " \"Negative\".\n")).as_deref(),
+            Some("negative")
+        );
+        assert_eq!(
+            label(parse(&schema, "The sentiment is neutral, not negative.")).as_deref(),
+            Some("neutral")
+        );
+        assert_eq!(label(parse(&schema, "I can't tell.")), None);
+    }
+
+    #[test]
+    fn test_parse_enum_prefers_longer_label_at_same_position() {
+        let schema = labels(&["no", "none"]);
+        assert_eq!(
+            label(parse(&schema, "None of them apply")).as_deref(),
+            Some("none")
+        );
+    }
+
+    #[test]
+    fn test_parse_enum_matches_whole_words() {
+        let schema = labels(&["yes", "no"]);
+        assert_eq!(
+            label(parse(&schema, "I don't know, but yes")).as_deref(),
+            Some("yes")
+        );
+        assert_eq!(label(parse(&schema, "Nothing to say")), None);
+    }
+
+    #[test]
+    fn test_parse_number() {
+        let schema = ResponseSchema {
+            number: true,
+            ..Default::default()
+        };
+        let number = |content| match parse(&schema, content)?.value? {
+            Value::Number(n) => Some(n),
+            Value::Label(_) => None,
+        };
+        assert_eq!(number("42"), Some(42.0));
+        assert_eq!(number("The answer is -3.5."), Some(-3.5));
+        assert_eq!(number("About $1200 in total"), Some(1200.0));
+        assert_eq!(number("none"), None);
+
+        assert!(parse(&ResponseSchema::default(), "42").is_none());
+    }
+}
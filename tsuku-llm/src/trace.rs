@@ -0,0 +1,128 @@
+//! Step-by-step generation traces for offline debugging.
+//!
+//! A trace is a JSONL file with one line per generated token: its position,
+//! id, text piece and log-probability, and the most likely alternatives the
+//! model weighed against it. Much heavier than debug logging, so it's only
+//! written for one request at a time (see `--trace-next`).
+
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use crate::generation::InferenceContext;
+
+/// Alternatives recorded per step.
+const TOP_ALTERNATIVES: usize = 5;
+
+/// Writes one JSON line per generated token.
+pub struct Trace<W: Write> {
+    out: W,
+    /// Position of the next token.
+    pos: usize,
+}
+
+impl<W: Write> Trace<W> {
+    /// Start a trace for a generation following a `prompt_len`-token prompt.
+    pub fn new(out: W, prompt_len: usize) -> Self {
+        Self {
+            out,
+            pos: prompt_len,
+        }
+    }
+
+    /// Record that `token` was chosen from the context's current logits.
+    ///
+    /// Call it before the token is decoded, while the logits it was sampled
+    /// from are still the last ones.
+    pub fn step<C: InferenceContext + ?Sized>(&mut self, ctx: &C, token: i32) -> io::Result<()> {
+        let logits = ctx.get_logits(-1);
+        let log_sum_exp = log_sum_exp(logits);
+        let logprob = |token: usize| logits[token] - log_sum_exp;
+        let piece = |token: i32| {
+            ctx.token_bytes(token)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default()
+        };
+
+        let mut ranked: Vec<usize> = (0..logits.len()).collect();
+        let top = TOP_ALTERNATIVES.min(ranked.len());
+        let by_logit = |a: &usize, b: &usize| logits[*b].total_cmp(&logits[*a]).then(a.cmp(b));
+        if top < ranked.len() {
+            ranked.select_nth_unstable_by(top, by_logit);
+        }
+        ranked.truncate(top);
+        ranked.sort_unstable_by(by_logit);
+        let alternatives: Vec<_> = ranked
+            .into_iter()
+            .map(|t| json!({"token": t, "piece": piece(t as i32), "logprob": logprob(t)}))
+            .collect();
+
+        let line = json!({
+            "pos": self.pos,
+            "token": token,
+            "piece": piece(token),
+            "logprob": usize::try_from(token).ok().filter(|&t| t < logits.len()).map(logprob),
+            "top": alternatives,
+        });
+        self.pos += 1;
+        writeln!(self.out, "{}", line)
+    }
+
+    /// Flush buffered lines.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// `ln(sum(exp(x)))`, computed without overflow.
+fn log_sum_exp(logits: &[f32]) -> f32 {
+    let max = logits.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    max + logits.iter().map(|x| (x - max).exp()).sum::<f32>().ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generation::testing::MockContext;
+    use crate::generation::{self, GenerationConfig};
+    use crate::llama::Sampler;
+    use std::time::Duration;
+
+    #[test]
+    fn test_trace_records_each_step() {
+        let mut ctx = MockContext::new(vec![5, 6], 63);
+        let config = GenerationConfig {
+            max_tokens: 10,
+            timeout: Duration::from_secs(60),
+            length_penalty: 0.0,
+            logit_bias: Vec::new(),
+            repetition_penalty: 1.0,
+        };
+        let mut trace = Trace::new(Vec::new(), 3);
+        generation::generate(&mut ctx, &Sampler::greedy(), 3, &config, |ctx, token| {
+            trace.step(ctx, token).unwrap();
+            true
+        });
+
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&trace.out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        for (step, (line, token)) in lines.iter().zip([5, 6]).enumerate() {
+            assert_eq!(line["pos"], 3 + step);
+            assert_eq!(line["token"], token);
+            assert_eq!(line["piece"], format!("<{}>", token));
+            let logprob = line["logprob"].as_f64().unwrap();
+            assert!(logprob < 0.0 && logprob > -0.1);
+
+            let top = line["top"].as_array().unwrap();
+            assert_eq!(top.len(), TOP_ALTERNATIVES);
+            assert_eq!(top[0]["token"], token);
+            // The runners-up tie, so they come in token order
+            assert_eq!(top[1]["token"], 0);
+            assert!(top[1]["logprob"].as_f64().unwrap() < logprob);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Deep model verification.
+//!
+//! A matching SHA256 only proves the file is the one the manifest describes,
+//! not that it is usable: a faithful copy of a broken quantization passes the
+//! checksum. Deep verification loads the model and decodes a short prompt,
+//! checking that the resulting logits are finite.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::llama::{ContextParams, LlamaContext, LlamaError, LlamaModel, ModelParams};
+
+/// Prompt decoded during deep verification.
+const PROBE_PROMPT: &str = "Hello";
+
+/// Context size for the probe decode; only a handful of tokens are used.
+const PROBE_CTX: u32 = 512;
+
+/// Reasons a model fails deep verification.
+#[derive(Error, Debug)]
+pub enum DeepVerifyError {
+    #[error("model failed to load: {0}")]
+    Llama(#[from] LlamaError),
+
+    #[error("probe prompt produced no tokens")]
+    EmptyPrompt,
+
+    #[error("decode produced {bad} non-finite logits out of {total}")]
+    NonFiniteLogits { bad: usize, total: usize },
+}
+
+/// Load the model at `path` and decode a probe prompt, checking the logits.
+///
+/// This is a blocking call that loads the full model; run it on a blocking
+/// thread from async code.
+pub fn deep_verify(path: &Path) -> Result<(), DeepVerifyError> {
+    let model = Arc::new(LlamaModel::load_from_file(path, ModelParams::for_gpu())?);
+    let mut ctx = LlamaContext::new(
+        model,
+        ContextParams {
+            n_ctx: PROBE_CTX,
+            n_batch: PROBE_CTX,
+            ..Default::default()
+        },
+    )?;
+
+    let tokens = ctx.tokenize(PROBE_PROMPT, true, false)?;
+    if tokens.is_empty() {
+        return Err(DeepVerifyError::EmptyPrompt);
+    }
+    ctx.decode(&tokens, 0)?;
+
+    check_logits(ctx.get_logits(tokens.len() as i32 - 1))
+}
+
+/// Fail if any logit is NaN or infinite.
+fn check_logits(logits: &[f32]) -> Result<(), DeepVerifyError> {
+    let bad = logits.iter().filter(|l| !l.is_finite()).count();
+    if bad > 0 || logits.is_empty() {
+        return Err(DeepVerifyError::NonFiniteLogits {
+            bad,
+            total: logits.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_logits_accepts_finite() {
+        assert!(check_logits(&[0.5, -3.0, 12.0]).is_ok());
+    }
+
+    #[test]
+    fn test_check_logits_rejects_nan() {
+        let result = check_logits(&[0.5, f32::NAN, f32::INFINITY]);
+        assert!(matches!(
+            result,
+            Err(DeepVerifyError::NonFiniteLogits { bad: 2, total: 3 })
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a model in TSUKU_LLM_TEST_MODEL"]
+    fn test_deep_verify_real_model() {
+        let path = crate::llama::test_model_path();
+        deep_verify(&path).expect("known-good model should pass deep verify");
+    }
+
+    #[test]
+    fn test_deep_verify_rejects_header_only_model() {
+        // A header-only GGUF has metadata but no tensors, so it can't load
+        let dir = tempfile::tempdir().unwrap();
+        let bad = dir.path().join("bad.gguf");
+        let bytes = crate::gguf::testing::build_gguf(&[("general.architecture", "llama")], &[]);
+        std::fs::write(&bad, bytes).unwrap();
+        assert!(deep_verify(&bad).is_err());
+    }
+}